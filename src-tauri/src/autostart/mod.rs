@@ -0,0 +1,133 @@
+use serde::Serialize;
+use tauri::Manager;
+use tauri_plugin_autostart::ManagerExt;
+
+use crate::config::getters::get_autostart_status;
+use crate::config::setters::set_autostart_status;
+use crate::logging::{log_error, log_info, log_warn};
+use crate::BackendCommunicator;
+
+/// Autostart according to the config and the login item registered with the OS.
+#[derive(Debug, Clone, Serialize)]
+pub struct AutostartStatus {
+    pub enabled: bool,                    // is_auto_start_enabled in the config
+    pub login_item_enabled: Option<bool>, // Registered to launch at login, None if it could not be read.
+    pub in_sync: bool,
+    pub error: Option<String>,
+}
+
+fn is_login_item_enabled(backend_communicator: &BackendCommunicator) -> Result<bool, String> {
+    backend_communicator
+        .front_end_window
+        .app_handle()
+        .autolaunch()
+        .is_enabled()
+        .map_err(|err| format!("Unable to read the launch at login setting. Err: {}", err))
+}
+
+fn set_login_item(enabled: bool, backend_communicator: &BackendCommunicator) -> Result<(), String> {
+    let app_handle = backend_communicator.front_end_window.app_handle();
+    let autolaunch = app_handle.autolaunch();
+    let result = if enabled {
+        autolaunch.enable()
+    } else {
+        autolaunch.disable()
+    };
+    result.map_err(|err| format!("Unable to change the launch at login setting. Err: {}", err))
+}
+
+fn get_status(enabled: bool, backend_communicator: &BackendCommunicator) -> AutostartStatus {
+    match is_login_item_enabled(backend_communicator) {
+        Ok(login_item_enabled) => AutostartStatus {
+            enabled,
+            login_item_enabled: Some(login_item_enabled),
+            in_sync: enabled == login_item_enabled,
+            error: None,
+        },
+        Err(err) => AutostartStatus {
+            enabled,
+            login_item_enabled: None,
+            in_sync: false,
+            error: Some(err),
+        },
+    }
+}
+
+/// Compare the config with the login item registered with the OS.
+pub fn get_autostart(backend_communicator: &BackendCommunicator) -> AutostartStatus {
+    get_status(
+        get_autostart_status(backend_communicator),
+        backend_communicator,
+    )
+}
+
+/// Register or remove the login item to match the config. The config is leading, it is what the
+/// user chose in the GUI. Mismatches which remain are logged & reported.
+pub fn reconcile_autostart(backend_communicator: &BackendCommunicator) -> AutostartStatus {
+    let autostart_status = get_autostart(backend_communicator);
+    if autostart_status.in_sync {
+        return autostart_status;
+    }
+
+    log_warn!(
+        format!(
+            "Launch at login is {} while autostart is {} in the config. Updating launch at login.",
+            match autostart_status.login_item_enabled {
+                Some(true) => "registered",
+                Some(false) => "not registered",
+                None => "unknown",
+            },
+            if autostart_status.enabled {
+                "on"
+            } else {
+                "off"
+            }
+        ),
+        backend_communicator,
+    );
+    if let Err(err) = set_login_item(autostart_status.enabled, backend_communicator) {
+        log_error!(err, backend_communicator);
+    }
+
+    let autostart_status = get_autostart(backend_communicator);
+    if !autostart_status.in_sync {
+        log_error!(
+            format!(
+                "Launch at login does not match autostart in the config. {}",
+                autostart_status.error.clone().unwrap_or_default()
+            ),
+            backend_communicator,
+        );
+    }
+    autostart_status
+}
+
+/// Turn autostart on or off, in the config and as login item.
+pub fn set_autostart(enabled: bool, backend_communicator: &BackendCommunicator) -> AutostartStatus {
+    if let Err(err) = set_autostart_status(enabled, backend_communicator) {
+        let mut autostart_status = get_autostart(backend_communicator);
+        autostart_status.error = Some(err);
+        return autostart_status;
+    }
+
+    let autostart_status = match set_login_item(enabled, backend_communicator) {
+        Ok(_) => get_status(enabled, backend_communicator),
+        Err(err) => {
+            log_error!(err.clone(), backend_communicator);
+            AutostartStatus {
+                error: Some(err),
+                ..get_status(enabled, backend_communicator)
+            }
+        }
+    };
+
+    if autostart_status.in_sync && enabled {
+        log_info!("Your node will automatically launch when you login. Make sure to also automatically start Docker Desktop when you log in. (https://docs.docker.com/desktop/settings/windows/#general).".to_string(), backend_communicator);
+    } else if autostart_status.in_sync {
+        log_info!(
+            "Your node will not automatically launch at system startup.".to_string(),
+            backend_communicator,
+        );
+    }
+    autostart_status
+}
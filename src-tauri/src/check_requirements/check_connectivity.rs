@@ -0,0 +1,119 @@
+use chrono::{DateTime, Utc};
+use reqwest::header::DATE;
+use reqwest::Client;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::config::RequirementsConfig;
+use crate::BackendCommunicator;
+
+use super::check_edge::get_edge_index_url;
+
+const INDEX_PROBE_TIMEOUT: Duration = Duration::from_secs(15);
+/// Successful index probes are reused for the connectivity and clock checks.
+const INDEX_PROBE_CACHE_SECONDS: i64 = 600;
+
+/// Result of reaching the index.
+#[derive(Clone)]
+struct IndexProbe {
+    index_url: String,
+    probed_at: DateTime<Utc>,
+    server_time: Option<DateTime<Utc>>, // From the Date header, None if the index did not send it.
+}
+
+static INDEX_PROBE_CACHE: Mutex<Option<IndexProbe>> = Mutex::new(None);
+
+/// Reach the index of the configured network. Failed probes are not cached, so a recovered
+/// index is noticed by the next check.
+async fn probe_index(backend_communicator: &BackendCommunicator) -> Result<IndexProbe, String> {
+    let index_url = get_edge_index_url(backend_communicator);
+
+    if let Ok(index_probe_cache) = INDEX_PROBE_CACHE.lock() {
+        if let Some(index_probe) = index_probe_cache.as_ref() {
+            let age_seconds = Utc::now()
+                .signed_duration_since(index_probe.probed_at)
+                .num_seconds();
+            if index_probe.index_url == index_url && age_seconds < INDEX_PROBE_CACHE_SECONDS {
+                return Ok(index_probe.clone());
+            }
+        }
+    }
+
+    let client = Client::builder()
+        .timeout(INDEX_PROBE_TIMEOUT)
+        .build()
+        .map_err(|err| format!("Unable to create index http client. Err: {}", err))?;
+    let response = client
+        .get(&index_url)
+        .send()
+        .await
+        .map_err(|err| format!("Unable to reach the index at {}. Err: {}", index_url, err))?;
+    if response.status().is_server_error() {
+        return Err(format!(
+            "The index at {} returned http status {}.",
+            index_url,
+            response.status()
+        ));
+    }
+
+    let server_time = response
+        .headers()
+        .get(DATE)
+        .and_then(|date| date.to_str().ok())
+        .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+        .map(|date| date.with_timezone(&Utc));
+    let index_probe = IndexProbe {
+        index_url,
+        probed_at: Utc::now(),
+        server_time,
+    };
+    if let Ok(mut index_probe_cache) = INDEX_PROBE_CACHE.lock() {
+        *index_probe_cache = Some(index_probe.clone());
+    }
+    Ok(index_probe)
+}
+
+/// Checks whether the index of the configured network can be reached.
+pub async fn check_index_connectivity(
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let index_probe = probe_index(backend_communicator).await?;
+    Ok(format!("Index {} reachable.", index_probe.index_url))
+}
+
+/// Checks the system clock against the time of the index. A wrong clock makes the index reject
+/// the messages of the device.
+pub async fn check_clock(
+    requirements_config: &RequirementsConfig,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let index_probe = match probe_index(backend_communicator).await {
+        Ok(index_probe) => index_probe,
+        Err(err) => return Ok(format!("Clock not compared, {}", err)),
+    };
+    let server_time = match index_probe.server_time {
+        Some(server_time) => server_time,
+        None => return Ok("Clock not compared, the index did not send its time.".to_string()),
+    };
+
+    let clock_skew_seconds = index_probe
+        .probed_at
+        .signed_duration_since(server_time)
+        .num_seconds();
+    if clock_skew_seconds.abs() <= requirements_config.max_clock_skew_seconds {
+        Ok(format!(
+            "System clock is within {} seconds of the index.",
+            clock_skew_seconds.abs()
+        ))
+    } else {
+        Err(format!(
+            "System clock is {} seconds {} the index. Enable automatic time synchronization.",
+            clock_skew_seconds.abs(),
+            if clock_skew_seconds > 0 {
+                "ahead of"
+            } else {
+                "behind"
+            }
+        ))
+    }
+}
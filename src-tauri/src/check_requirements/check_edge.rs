@@ -1,25 +1,37 @@
+use crate::cli_versions::archive_current_edge_cli;
+use crate::config::getters::get_download_config;
 use crate::config::getters::get_edge_binary_name;
 use crate::config::getters::get_network;
+use crate::config::getters::get_network_definition;
+use crate::config::getters::get_pinned_cli_checksum;
+use crate::config::getters::get_pinned_cli_version;
+use crate::logging::{log_debug, log_error, log_info, log_warn};
+use crate::notifications::{notify, NodeEvent};
 use crate::utility::download_file;
-use crate::utility::log_and_emit;
+#[cfg(target_family = "unix")]
+use crate::utility::set_unix_permissions;
 use crate::BackendCommunicator;
+use chrono::{DateTime, Utc};
 use sha2::{Digest, Sha256};
 use std::fs::File;
 use std::path::Path;
+use std::sync::Mutex;
 use std::{fs, io};
 
+use crate::check_requirements::check_signature::verify_edge_cli_signature;
 use crate::check_requirements::check_specifications::{get_os_info, get_processor_info};
 use crate::check_requirements::pretty_check_string;
 
 /// Create an edge url based on url components
 fn create_edge_url(
+    base_url: String,
     net: String,
     os: String,
     arch: String,
     version: String,
     filename: String,
 ) -> String {
-    let mut edge_url = String::from("https://files.edge.network/cli");
+    let mut edge_url = base_url.trim_end_matches(['/', '\\']).to_string(); // eg. https://files.edge.network/cli
 
     fn add_url_component(mut edge_url: String, comp: String) -> String {
         edge_url.push_str(&String::from("/"));
@@ -36,17 +48,34 @@ fn create_edge_url(
     edge_url
 }
 
-/// Downloads checksum of latest edge binary for system
-async fn get_edge_cli_checksum(
+/// Remote checksum per url, so frequent requirement checks do not re-download it every time.
+static CHECKSUM_CACHE: Mutex<Option<(String, DateTime<Utc>, String)>> = Mutex::new(None);
+
+/// Maximum age of a cached checksum for requirement checks.
+pub(crate) const CHECKSUM_CACHE_SECONDS: i64 = 600;
+
+/// Downloads checksum of latest edge binary for system.
+/// A checksum downloaded less than `max_age_seconds` ago for the same url is reused.
+pub(crate) async fn get_edge_cli_checksum(
+    max_age_seconds: i64,
     backend_communicator: &BackendCommunicator,
 ) -> Result<String, String> {
     let backend_communicator = backend_communicator;
     let checksum_url = get_edge_cli_checksum_url(backend_communicator);
 
+    if let Ok(checksum_cache) = CHECKSUM_CACHE.lock() {
+        if let Some((cached_url, fetched_at, cached_checksum)) = checksum_cache.as_ref() {
+            let age_seconds = Utc::now().signed_duration_since(*fetched_at).num_seconds();
+            if *cached_url == checksum_url && age_seconds < max_age_seconds {
+                return Ok(cached_checksum.clone());
+            }
+        }
+    }
+
     let filename = String::from("checksum");
     let filepath = format!("{}{}", backend_communicator.data_dir.clone(), filename);
 
-    log_and_emit(
+    log_debug!(
         format!("Downloading checksum from URL: {}", checksum_url.clone()),
         backend_communicator,
     );
@@ -63,59 +92,69 @@ async fn get_edge_cli_checksum(
 
     let checksum: String;
     match fs::read_to_string(filepath) {
-        Ok(checksum_str) => checksum = checksum_str, // Checksum is SHA256
+        Ok(checksum_str) => checksum = normalise_checksum(&checksum_str), // Checksum is SHA256
         Err(err) => {
             let error_message = err.to_string();
             return Err(error_message);
         }
     };
 
+    if let Ok(mut checksum_cache) = CHECKSUM_CACHE.lock() {
+        *checksum_cache = Some((checksum_url, Utc::now(), checksum.clone()));
+    }
+
     Ok(checksum)
 }
 
 // Create index url based on config value of network
 pub fn get_edge_index_url(backend_communicator: &BackendCommunicator) -> String {
     let network = get_network(backend_communicator);
-    let mainnet = String::from("mainnet");
-    let mainnet_index = String::from("https://index.xe.network");
-    let testnet = String::from("testnet");
-    let testnet_index = String::from("https://index.test.network");
-
-    if network == mainnet {
-        let ok_message = format!(
-            "Derived index url {} based on network in config {}",
-            mainnet_index, network
-        );
-        log_and_emit(ok_message, backend_communicator);
-        mainnet_index
-    } else if network == testnet {
-        let ok_message = format!(
-            "Derived index url {} based on network in config {}",
-            testnet_index, network
-        );
-        log_and_emit(ok_message, backend_communicator);
-        testnet_index
-    } else {
-        let err_message = format!(
-            "Unable to derive edge_index_url based on network in config: {}",
-            network
-        );
-        log_and_emit(err_message, backend_communicator);
-
-        String::from("ERR, see log")
+    match get_network_definition(&network, backend_communicator) {
+        Ok(network_definition) => {
+            let ok_message = format!(
+                "Derived index url {} based on network in config {}",
+                network_definition.index_url, network
+            );
+            log_info!(ok_message, backend_communicator);
+            network_definition.index_url
+        }
+        Err(_) => String::from("ERR, see log"),
     }
 }
 
+/// Create the staking page url of the wallet based on config value of network
+pub fn get_edge_staking_url(backend_communicator: &BackendCommunicator) -> Result<String, String> {
+    let network = get_network(backend_communicator);
+    let network_definition = get_network_definition(&network, backend_communicator)?;
+    let staking_url = format!(
+        "{}/staking",
+        network_definition.wallet_url.trim_end_matches('/')
+    );
+
+    let ok_message = format!(
+        "Derived staking wallet url {} based on network in config: {}",
+        staking_url, network
+    );
+    log_info!(ok_message, backend_communicator);
+    Ok(staking_url)
+}
+
 // Create URL based on user's system to filename.
 fn get_edge_file_url(filename: String, backend_communicator: &BackendCommunicator) -> String {
-    let net = get_network(backend_communicator);
+    let network = get_network(backend_communicator);
     let os_info = get_os_info(backend_communicator);
     let os = os_info.cli_os_name;
     let processor_info = get_processor_info(backend_communicator);
     let arch = processor_info.cli_architecture_name;
-    let version = String::from("latest");
+    let version = get_pinned_cli_version(backend_communicator); // eg. latest
+    let base_url = match get_network_definition(&network, backend_communicator) {
+        Ok(network_definition) if !network_definition.cli_base_url.is_empty() => {
+            network_definition.cli_base_url
+        }
+        _ => get_download_config(backend_communicator).base_url,
+    };
 
-    create_edge_url(net, os, arch, version, filename)
+    create_edge_url(base_url, network.to_string(), os, arch, version, filename)
 }
 
 /// Returns the checksum url
@@ -146,7 +185,9 @@ pub async fn is_edge_correctly_downloaded(
     let edge_cli_path = Path::new(&filepath);
 
     if edge_cli_path.exists() {
-        let get_edge_cli_checksum_future = get_edge_cli_checksum(backend_communicator).await;
+        let get_edge_cli_checksum_future =
+            get_edge_cli_checksum(CHECKSUM_CACHE_SECONDS, backend_communicator).await;
+        let checksum_downloaded = get_edge_cli_checksum_future.is_ok();
         let calculated_checksum = match get_edge_cli_checksum_future {
             Ok(ok_checksum_str) => ok_checksum_str,
             Err(err_checksum_str) => {
@@ -163,8 +204,14 @@ pub async fn is_edge_correctly_downloaded(
             }
         };
 
+        // A rolled back binary of unknown version is pinned by its checksum.
+        if get_pinned_cli_checksum(backend_communicator).as_deref() == Some(hash_string.as_str()) {
+            let success_message = String::from("Pinned Edge CLI installed for your system.");
+            return Ok(success_message);
+        }
+
         if calculated_checksum.eq(&hash_string) {
-            log_and_emit("CLI installed correctly!".to_string(), backend_communicator);
+            log_info!("CLI installed correctly!".to_string(), backend_communicator);
             let success_message = String::from("Latest Edge CLI installed for your system.");
             Ok(success_message)
         } else {
@@ -173,6 +220,20 @@ pub async fn is_edge_correctly_downloaded(
                 calculated_checksum, hash_string
             );
 
+            // An installed binary with a different checksum than the published one is outdated.
+            if checksum_downloaded {
+                if let Err(err) = notify(
+                    NodeEvent::CliUpdateAvailable,
+                    "Edge CLI update available.",
+                    "A newer Edge CLI is available. Update it via the Staking GUI.",
+                    backend_communicator,
+                )
+                .await
+                {
+                    log_error!(err, backend_communicator);
+                }
+            }
+
             Err(checksums_do_not_match_err)
         }
     } else {
@@ -181,8 +242,17 @@ pub async fn is_edge_correctly_downloaded(
     }
 }
 
+/// Lowercase SHA256 from a checksum or checksum file formatted as `<checksum>  <filename>`.
+pub(crate) fn normalise_checksum(checksum_str: &str) -> String {
+    checksum_str
+        .split_whitespace()
+        .next()
+        .unwrap_or("")
+        .to_lowercase()
+}
+
 /// Hash file with SHA256
-fn hash_file(
+pub(crate) fn hash_file(
     file_path: &Path,
     backend_communicator: &BackendCommunicator,
 ) -> Result<String, String> {
@@ -203,7 +273,7 @@ fn hash_file(
         Ok(_) => (),
         Err(err_str) => {
             let err_msg = format!("Unable to copy binary to hasher. Err {}", err_str);
-            log_and_emit(err_msg.clone(), backend_communicator);
+            log_error!(err_msg.clone(), backend_communicator);
             return Err(err_msg);
         }
     }
@@ -213,73 +283,130 @@ fn hash_file(
     Ok(hash_string)
 }
 
-/// Download the fitting Edge CLI based on user's system.
-/// Returns true if latest binary installed.
-pub(crate) async fn get_edge_cli_binary(backend_communicator: &BackendCommunicator) -> bool {
-    let edge_binary_filename = get_edge_binary_name(backend_communicator);
+/// Path the Edge CLI is downloaded to before it is verified, next to the installed binary.
+fn get_staged_edge_cli_path(backend_communicator: &BackendCommunicator) -> String {
+    format!(
+        "{}{}.download",
+        backend_communicator.data_dir.clone(),
+        get_edge_binary_name(backend_communicator)
+    )
+}
+
+/// Download the latest Edge CLI next to the installed one and verify its checksum & signature.
+/// Returns the path of the verified download, the installed binary is left untouched.
+pub(crate) async fn download_verified_edge_cli(
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let staged_filepath = get_staged_edge_cli_path(backend_communicator);
+    let remote_checksum = get_edge_cli_checksum(0, backend_communicator)
+        .await
+        .map_err(|err| format!("Unable to download Edge CLI checksum. Err: {}", err))?;
+
+    let cli_download_url = get_edge_cli_download_url_from_frontend(backend_communicator);
+    log_debug!(
+        format!("Download Url: {}", cli_download_url),
+        backend_communicator,
+    );
+    download_file(
+        cli_download_url,
+        staged_filepath.clone(),
+        backend_communicator,
+    )
+    .await?;
+
+    let verify_result = match hash_file(Path::new(&staged_filepath), backend_communicator) {
+        Ok(hash_string) if hash_string == remote_checksum => {
+            verify_edge_cli_signature(&staged_filepath, backend_communicator).await
+        }
+        Ok(hash_string) => Err(format!(
+            "Edge CLI not correctly downloaded. Download checksum: {} . Calculated checksum: {} .",
+            remote_checksum, hash_string
+        )),
+        Err(err) => Err(err),
+    };
+    if let Err(err) = verify_result {
+        let _ = fs::remove_file(&staged_filepath);
+        return Err(err);
+    }
+    Ok(staged_filepath)
+}
+
+/// Archive the installed Edge CLI and move a verified download into its place.
+pub(crate) async fn install_staged_edge_cli(
+    staged_filepath: &str,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
     let edge_binary_filepath = format!(
         "{}{}",
         backend_communicator.data_dir.clone(),
-        edge_binary_filename
+        get_edge_binary_name(backend_communicator)
     );
 
+    // Keep the installed binary so it can be rolled back to.
+    if let Err(err) = archive_current_edge_cli(backend_communicator).await {
+        log_warn!(
+            format!(
+                "Continuing without archiving the current Edge CLI. Err: {}",
+                err
+            ),
+            backend_communicator,
+        );
+    }
+
+    if let Err(err) = fs::rename(staged_filepath, &edge_binary_filepath) {
+        let _ = fs::remove_file(staged_filepath);
+        return Err(format!(
+            "Unable to install the downloaded Edge CLI. Err: {}",
+            err
+        ));
+    }
+    #[cfg(target_family = "unix")]
+    set_unix_permissions(edge_binary_filepath.clone(), backend_communicator);
+
+    Ok("Latest Edge CLI downloaded & correctly installed.".to_string())
+}
+
+/// Download the fitting Edge CLI based on user's system.
+/// The download is verified before it replaces the installed binary, which stays in place otherwise.
+/// Returns true if latest binary installed.
+pub(crate) async fn get_edge_cli_binary(backend_communicator: &BackendCommunicator) -> bool {
+    // Refresh the cached checksum, the check below compares against the latest published one.
+    if let Err(err) = get_edge_cli_checksum(0, backend_communicator).await {
+        log_error!(
+            format!("Unable to download Edge CLI checksum. Err: {}", err),
+            backend_communicator,
+        );
+    }
+
     if (is_edge_correctly_downloaded(backend_communicator).await).is_ok() {
         let ok_msg = pretty_check_string::pretty_ok_str(
             &String::from("Latest Edge CLI is already correctly installed."),
             false,
         );
-        log_and_emit(ok_msg, backend_communicator);
+        log_info!(ok_msg, backend_communicator);
         return true;
     }
 
-    let cli_download_url = get_edge_cli_download_url_from_frontend(backend_communicator);
-    log_and_emit(
-        format!("Download Url: {}", cli_download_url),
-        backend_communicator,
-    );
-
-    let download_file_future = download_file(
-        cli_download_url,
-        edge_binary_filepath.clone(),
-        backend_communicator,
-    )
-    .await;
-
-    match download_file_future {
-        Ok(_) => {}
+    let staged_filepath = match download_verified_edge_cli(backend_communicator).await {
+        Ok(staged_filepath) => staged_filepath,
         Err(err) => {
-            let err_msg = err;
-            log_and_emit(err_msg, backend_communicator);
+            log_error!(err, backend_communicator);
+            log_error!(
+                "File was not downloaded correctly, kept the installed Edge CLI.".to_string(),
+                backend_communicator,
+            );
             return false;
         }
-    }
+    };
 
-    let is_edge_correctly_downloaded_future_post_download_cli =
-        is_edge_correctly_downloaded(backend_communicator).await;
-    match is_edge_correctly_downloaded_future_post_download_cli {
-        Ok(_) => {
-            let ok_msg = pretty_check_string::pretty_ok_str(
-                &String::from("Latest Edge CLI downloaded & correctly installed."),
-                false,
-            );
-            log_and_emit(ok_msg, backend_communicator);
+    match install_staged_edge_cli(&staged_filepath, backend_communicator).await {
+        Ok(ok_message) => {
+            let ok_msg = pretty_check_string::pretty_ok_str(&ok_message, false);
+            log_info!(ok_msg, backend_communicator);
             true
         }
-        Err(_) => {
-            let err_msg = "File was not downloaded correctly. Attempting to remove automatically."
-                .to_string();
-            log_and_emit(err_msg, backend_communicator);
-            match fs::remove_file(edge_binary_filepath.clone()) {
-                Ok(_) => {
-                    let ok_msg = "Removed incorrect downloaded file automatically.".to_string();
-                    log_and_emit(ok_msg, backend_communicator);
-                }
-                Err(_) => {
-                    let err_msg =
-                        "Failed to remove incorrect downloaded file. Try rerunning.".to_string();
-                    log_and_emit(err_msg, backend_communicator);
-                }
-            }
+        Err(err) => {
+            log_error!(err, backend_communicator);
             false
         }
     }
@@ -0,0 +1,171 @@
+use std::net::TcpListener;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use sysinfo::{DiskExt, System, SystemExt};
+
+use crate::config::RequirementsConfig;
+use crate::docker::{get_docker_root_dir, get_edge_device_container_state};
+use crate::process_runner::run_process;
+use crate::BackendCommunicator;
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+const BYTES_PER_GB: u64 = 1024 * 1024 * 1024;
+const VIRTUALIZATION_CHECK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Enabling virtualization needs a reboot, so once enabled it is not checked again.
+static VIRTUALIZATION_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Checks whether enough memory is available for the device container.
+pub fn check_memory(requirements_config: &RequirementsConfig) -> Result<String, String> {
+    let mut system = System::new();
+    system.refresh_memory();
+    let available_memory_mb = system.available_memory() / BYTES_PER_MB;
+
+    if available_memory_mb >= requirements_config.min_free_memory_mb {
+        Ok(format!("{} MB memory available.", available_memory_mb))
+    } else {
+        Err(format!(
+            "Only {} MB memory available, at least {} MB is needed. Close other programs or add memory.",
+            available_memory_mb, requirements_config.min_free_memory_mb
+        ))
+    }
+}
+
+/// Available space on the disk holding a path, the disk with the longest matching mount point.
+fn get_available_disk_space(system: &System, path: &Path) -> Option<u64> {
+    system
+        .disks()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
+
+/// Checks the free disk space of the data directory and, if it is on this host, the Docker data
+/// directory. With Docker Desktop the Docker data directory is inside its VM.
+pub async fn check_disk(
+    requirements_config: &RequirementsConfig,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let mut system = System::new();
+    system.refresh_disks_list();
+
+    let mut checked_dirs = vec![("data directory", backend_communicator.data_dir.clone())];
+    if let Ok(docker_root_dir) = get_docker_root_dir(backend_communicator).await {
+        if Path::new(&docker_root_dir).exists() {
+            checked_dirs.push(("Docker data directory", docker_root_dir));
+        }
+    }
+
+    let min_free_disk_bytes = requirements_config.min_free_disk_gb * BYTES_PER_GB;
+    let mut ok_messages: Vec<String> = vec![];
+    for (dir_name, dir) in checked_dirs {
+        let available_disk_space = match get_available_disk_space(&system, Path::new(&dir)) {
+            Some(available_disk_space) => available_disk_space,
+            None => continue, // Disk not found, eg. a network share.
+        };
+        if available_disk_space < min_free_disk_bytes {
+            return Err(format!(
+                "Only {} GB free for the {} {}, at least {} GB is needed.",
+                available_disk_space / BYTES_PER_GB,
+                dir_name,
+                dir,
+                requirements_config.min_free_disk_gb
+            ));
+        }
+        ok_messages.push(format!(
+            "{} GB free for the {}.",
+            available_disk_space / BYTES_PER_GB,
+            dir_name
+        ));
+    }
+
+    if ok_messages.is_empty() {
+        Ok("Free disk space could not be determined.".to_string())
+    } else {
+        Ok(ok_messages.join(" "))
+    }
+}
+
+/// Checks whether the required ports are not used by other programs.
+/// Skipped while the device runs, as the device itself then uses them.
+pub async fn check_ports(
+    requirements_config: &RequirementsConfig,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    if requirements_config.required_ports.is_empty() {
+        return Ok("No required ports configured.".to_string());
+    }
+    if let Ok(container_state) = get_edge_device_container_state(backend_communicator).await {
+        if container_state == "running" {
+            return Ok("Device is running, ports not checked.".to_string());
+        }
+    }
+
+    let used_ports: Vec<String> = requirements_config
+        .required_ports
+        .iter()
+        .filter(|port| TcpListener::bind(("0.0.0.0", **port)).is_err())
+        .map(|port| port.to_string())
+        .collect();
+    if used_ports.is_empty() {
+        Ok("Required ports are free.".to_string())
+    } else {
+        Err(format!(
+            "Ports {} are used by another program. Stop the program or change its port.",
+            used_ports.join(", ")
+        ))
+    }
+}
+
+/// Checks whether hardware virtualization is enabled. Docker Desktop needs it on Windows & macOS.
+pub async fn check_virtualization(
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    if cfg!(target_os = "linux") {
+        return Ok("Virtualization not needed on Linux.".to_string());
+    }
+    if VIRTUALIZATION_ENABLED.load(Ordering::Relaxed) {
+        return Ok("Virtualization enabled.".to_string());
+    }
+
+    let (program, args): (&str, &[&str]) = if cfg!(target_os = "windows") {
+        // A running hypervisor (Hyper-V, WSL 2) hides the firmware setting, so check both.
+        (
+            "powershell",
+            &[
+                "-NoProfile",
+                "-Command",
+                "(Get-CimInstance Win32_ComputerSystem).HypervisorPresent -or (Get-CimInstance Win32_Processor | Select-Object -First 1).VirtualizationFirmwareEnabled",
+            ],
+        )
+    } else {
+        ("sysctl", &["-n", "kern.hv_support"])
+    };
+
+    match run_process(
+        program,
+        args,
+        VIRTUALIZATION_CHECK_TIMEOUT,
+        backend_communicator,
+    )
+    .await
+    {
+        Ok(output) => match output.stdout.trim() {
+            "True" | "1" => {
+                VIRTUALIZATION_ENABLED.store(true, Ordering::Relaxed);
+                Ok("Virtualization enabled.".to_string())
+            }
+            "False" | "0" => Err("Virtualization is not enabled. Enable it (VT-x / AMD-V / SVM) in your BIOS or UEFI settings.".to_string()),
+            other => Ok(format!(
+                "Virtualization status could not be determined. Output: {}",
+                other
+            )),
+        },
+        Err(err) => Ok(format!(
+            "Virtualization status could not be determined. Err: {}",
+            err
+        )),
+    }
+}
@@ -0,0 +1,194 @@
+use minisign_verify::{PublicKey, Signature};
+use std::fs;
+use std::path::Path;
+
+use crate::check_requirements::check_edge::get_edge_cli_download_url_from_frontend;
+use crate::config::getters::get_cli_config;
+use crate::logging::{log_error, log_info, log_warn};
+use crate::utility::download_optional_file;
+use crate::BackendCommunicator;
+
+/// Base64 minisign public keys of the Edge release signing key, always trusted.
+/// Add the published key here, configured `trusted_public_keys` are trusted in addition.
+const EDGE_RELEASE_PUBLIC_KEYS: &[&str] = &[];
+
+/// Collect the embedded and configured public keys. Unreadable keys are logged and skipped.
+fn get_trusted_public_keys(backend_communicator: &BackendCommunicator) -> Vec<PublicKey> {
+    let configured_public_keys = get_cli_config(backend_communicator).trusted_public_keys;
+    let mut trusted_public_keys: Vec<PublicKey> = vec![];
+
+    for public_key_str in EDGE_RELEASE_PUBLIC_KEYS
+        .iter()
+        .copied()
+        .chain(configured_public_keys.iter().map(String::as_str))
+    {
+        match PublicKey::from_base64(public_key_str.trim()) {
+            Ok(public_key) => trusted_public_keys.push(public_key),
+            Err(err) => log_error!(
+                format!(
+                    "Ignoring invalid public key {}. Err: {}",
+                    public_key_str, err
+                ),
+                backend_communicator,
+            ),
+        }
+    }
+    trusted_public_keys
+}
+
+/// Fail if signatures are required, otherwise warn that the signature check was skipped.
+fn skip_or_require_signature(
+    require_signature: bool,
+    reason: &str,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    if require_signature {
+        let err_message = format!("Signature required, but {}", reason);
+        log_error!(err_message.clone(), backend_communicator);
+        Err(err_message)
+    } else {
+        let ok_message = format!("Skipped signature check, {}", reason);
+        log_warn!(ok_message.clone(), backend_communicator);
+        Ok(ok_message)
+    }
+}
+
+/// Verify a binary against a signature with the trusted keys.
+/// If no key is trusted the signature can not be checked, which only fails if signatures are required.
+fn verify_with_trusted_keys(
+    bin_path_str: &str,
+    signature_path_str: &str,
+    require_signature: bool,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let trusted_public_keys = get_trusted_public_keys(backend_communicator);
+    if trusted_public_keys.is_empty() {
+        return skip_or_require_signature(
+            require_signature,
+            "no trusted public key is available. Add the Edge release key to trusted_public_keys in config.",
+            backend_communicator,
+        );
+    }
+
+    verify_signature_file(
+        bin_path_str,
+        signature_path_str,
+        &trusted_public_keys,
+        backend_communicator,
+    )
+}
+
+/// Verify a downloaded Edge CLI against the detached minisign signature published next to it
+/// (`<binary url>.minisig`). A missing signature is only an error if `require_signature` is set
+/// in config. A signature which does not match or can not be verified is always an error.
+pub(crate) async fn verify_edge_cli_signature(
+    bin_path_str: &str,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let require_signature = get_cli_config(backend_communicator).require_signature;
+    let signature_path_str = format!("{}.minisig", bin_path_str);
+    let signature_url = format!(
+        "{}.minisig",
+        get_edge_cli_download_url_from_frontend(backend_communicator)
+    );
+
+    match download_optional_file(
+        signature_url,
+        signature_path_str.clone(),
+        backend_communicator,
+    )
+    .await
+    {
+        Ok(true) => (),
+        Ok(false) => {
+            return skip_or_require_signature(
+                require_signature,
+                "no signature is published for this Edge CLI.",
+                backend_communicator,
+            )
+        }
+        Err(err) => {
+            return skip_or_require_signature(
+                require_signature,
+                &format!("the signature could not be downloaded. Err: {}", err),
+                backend_communicator,
+            )
+        }
+    }
+
+    let verify_result = verify_with_trusted_keys(
+        bin_path_str,
+        &signature_path_str,
+        require_signature,
+        backend_communicator,
+    );
+    let _ = fs::remove_file(&signature_path_str);
+    verify_result
+}
+
+/// Verify a local Edge CLI binary against the signature next to it (`<binary>.minisig`), if any.
+pub(crate) fn verify_local_edge_cli_signature(
+    bin_path_str: &str,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let require_signature = get_cli_config(backend_communicator).require_signature;
+    let signature_path_str = format!("{}.minisig", bin_path_str);
+
+    if !Path::new(&signature_path_str).exists() {
+        return skip_or_require_signature(
+            require_signature,
+            &format!("{} was not found.", signature_path_str),
+            backend_communicator,
+        );
+    }
+
+    verify_with_trusted_keys(
+        bin_path_str,
+        &signature_path_str,
+        require_signature,
+        backend_communicator,
+    )
+}
+
+/// Verify a binary against a minisign signature file. Passes if any trusted key matches.
+fn verify_signature_file(
+    bin_path_str: &str,
+    signature_path_str: &str,
+    trusted_public_keys: &[PublicKey],
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let signature;
+    match Signature::from_file(signature_path_str) {
+        Ok(ok_signature) => signature = ok_signature,
+        Err(err) => {
+            let err_message = format!("Unable to read Edge CLI signature. Err: {}", err);
+            log_error!(err_message.clone(), backend_communicator);
+            return Err(err_message);
+        }
+    }
+
+    let edge_cli_binary;
+    match fs::read(bin_path_str) {
+        Ok(ok_binary) => edge_cli_binary = ok_binary,
+        Err(err) => {
+            let err_message = format!("Unable to read Edge CLI for signature check. Err: {}", err);
+            log_error!(err_message.clone(), backend_communicator);
+            return Err(err_message);
+        }
+    }
+
+    let is_signature_valid = trusted_public_keys.iter().any(|public_key| {
+        public_key
+            .verify(&edge_cli_binary, &signature, false)
+            .is_ok()
+    });
+    if is_signature_valid {
+        let ok_message = "Edge CLI signature verified.".to_string();
+        log_info!(ok_message.clone(), backend_communicator);
+        Ok(ok_message)
+    } else {
+        let err_message = "Edge CLI signature is invalid or made with an untrusted key. The binary will not be used.".to_string();
+        log_error!(err_message.clone(), backend_communicator);
+        Err(err_message)
+    }
+}
@@ -1,14 +1,17 @@
 use crate::BackendCommunicator;
 
 use raw_cpuid::CpuId;
+use serde::Serialize;
 use std::collections::HashMap;
 
+#[derive(Serialize)]
 pub struct OsInfo {
     pub raw_os_name: String, // eg. Windows
     pub os_name_supported: bool,
     pub cli_os_name: String, // eg. windows, matches CLI naming
 }
 
+#[derive(Serialize)]
 pub struct ProcessorInfo {
     pub raw_processor_brand: String, // eg. GenuineIntel
     pub processor_brand_supported: bool,
@@ -1,33 +1,133 @@
 use chrono::{DateTime, Utc};
+use serde::Deserialize;
 
+use crate::check_requirements::pretty_check_string::{pretty_err_str, pretty_ok_str};
+use crate::config::getters::get_requirements_config;
+use crate::docker::docker_info::DockerInfo;
 use crate::docker::get_docker_status;
+use crate::logging::{log_error, log_info};
+use crate::node_status::{set_last_requirement_report, RequirementCheckResult, RequirementReport};
+use crate::notifications::{notify, NodeEvent};
 use crate::BackendCommunicator;
-use crate::{
-    check_requirements::pretty_check_string::{pretty_err_str, pretty_ok_str},
-    utility::log_and_emit,
-};
 
 use self::check_edge::is_edge_correctly_downloaded;
 
+pub mod check_connectivity;
 pub mod check_edge;
+pub mod check_resources;
+pub mod check_signature;
 pub mod check_specifications;
 pub mod pretty_check_string;
 
-/// Runs all system requirement checks
+/// Selection of system requirement checks to run.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct RequirementChecks {
+    pub os: bool,
+    pub processor: bool,
+    pub docker: bool,
+    pub edge_cli_binary: bool, // Is the latest Edge CLI installed?
+    pub memory: bool,
+    pub disk: bool,
+    pub ports: bool,
+    pub virtualization: bool,
+    pub clock: bool,
+    pub index_connectivity: bool,
+}
+
+impl RequirementChecks {
+    /// Only Docker, eg. before moving device data.
+    pub fn docker_only() -> Self {
+        RequirementChecks {
+            docker: true,
+            ..Default::default()
+        }
+    }
+
+    /// Every check.
+    pub fn all() -> Self {
+        RequirementChecks {
+            os: true,
+            processor: true,
+            docker: true,
+            edge_cli_binary: true,
+            memory: true,
+            disk: true,
+            ports: true,
+            virtualization: true,
+            clock: true,
+            index_connectivity: true,
+        }
+    }
+
+    /// Checks before running an Edge CLI command. The additional checks enabled in the config
+    /// only run before commands which start the device, stopping it is never blocked by them.
+    pub fn before_edge_cli_command(
+        check_edge_cli_binary: bool,
+        starts_device: bool,
+        backend_communicator: &BackendCommunicator,
+    ) -> Self {
+        let requirements_config = get_requirements_config(backend_communicator);
+        RequirementChecks {
+            os: true,
+            processor: true,
+            docker: true,
+            edge_cli_binary: check_edge_cli_binary,
+            memory: starts_device && requirements_config.check_memory,
+            disk: starts_device && requirements_config.check_disk,
+            ports: starts_device && requirements_config.check_ports,
+            virtualization: starts_device && requirements_config.check_virtualization,
+            clock: starts_device && requirements_config.check_clock,
+            index_connectivity: starts_device && requirements_config.check_index_connectivity,
+        }
+    }
+}
+
+/// Log the result of a check and add it to the results. Returns whether the check passed.
+fn record_check_result(
+    name: &str,
+    check_result: Result<String, String>,
+    result_string: &mut String,
+    check_results: &mut Vec<RequirementCheckResult>,
+    backend_communicator: &BackendCommunicator,
+) -> bool {
+    let passed = check_result.is_ok();
+    let message = match check_result {
+        Ok(ok_message) => ok_message,
+        Err(err_message) => err_message,
+    };
+    let pretty_message = if passed {
+        let pretty_ok_message = pretty_ok_str(&message, true);
+        log_info!(pretty_ok_message.clone(), backend_communicator);
+        pretty_ok_message
+    } else {
+        let pretty_err_message = pretty_err_str(&message, true);
+        log_error!(pretty_err_message.clone(), backend_communicator);
+        pretty_err_message
+    };
+    result_string.push_str(&pretty_message);
+    check_results.push(RequirementCheckResult {
+        name: name.to_string(),
+        passed,
+        message,
+    });
+    passed
+}
+
+/// Runs the selected system requirement checks
 pub async fn main(
-    check_os: bool,
-    check_processor: bool,
-    check_docker: bool,
-    check_edge_cli_binary: bool,
+    checks: &RequirementChecks,
     backend_communicator: &BackendCommunicator,
 ) -> Result<String, String> {
     let mut result_string = String::from("");
     let dt: DateTime<Utc> = Utc::now();
     let mut all_requirements_passed = true; // stays true unless at least one does not pass
+    let mut check_results: Vec<RequirementCheckResult> = vec![];
+    let mut docker_report: Option<DockerInfo> = None;
 
     // Check OS
-    if check_os {
-        log_and_emit(
+    if checks.os {
+        log_info!(
             "Checking if Operating System (OS) is supported".to_string(),
             backend_communicator,
         );
@@ -35,20 +135,30 @@ pub async fn main(
         if os_info.os_name_supported {
             let ok_os_name_str = &os_info.cli_os_name;
             let pretty_ok_os_name_str = pretty_check_string::pretty_ok_str(ok_os_name_str, true);
-            log_and_emit(pretty_ok_os_name_str.clone(), backend_communicator);
+            log_info!(pretty_ok_os_name_str.clone(), backend_communicator);
             result_string.push_str(&pretty_ok_os_name_str);
+            check_results.push(RequirementCheckResult {
+                name: "os".to_string(),
+                passed: true,
+                message: ok_os_name_str.to_string(),
+            });
         } else {
             all_requirements_passed = false;
             let err_os_name_str = &os_info.cli_os_name;
             let pretty_err_os_name = pretty_check_string::pretty_err_str(err_os_name_str, true);
-            log_and_emit(pretty_err_os_name.clone(), backend_communicator);
+            log_error!(pretty_err_os_name.clone(), backend_communicator);
             result_string.push_str(&pretty_err_os_name);
+            check_results.push(RequirementCheckResult {
+                name: "os".to_string(),
+                passed: false,
+                message: err_os_name_str.to_string(),
+            });
         }
     }
 
     // Check Processor Arch eg. Arch64
-    if check_processor {
-        log_and_emit(
+    if checks.processor {
+        log_info!(
             "Checking if processor is supported".to_string(),
             backend_communicator,
         );
@@ -56,8 +166,13 @@ pub async fn main(
         if processor_info.full_architecture_supported {
             let ok_cli_arch_str = &processor_info.cli_architecture_name;
             let pretty_ok_cli_arch_str = pretty_check_string::pretty_ok_str(ok_cli_arch_str, true);
-            log_and_emit(pretty_ok_cli_arch_str.clone(), backend_communicator);
+            log_info!(pretty_ok_cli_arch_str.clone(), backend_communicator);
             result_string.push_str(&pretty_ok_cli_arch_str);
+            check_results.push(RequirementCheckResult {
+                name: "processor".to_string(),
+                passed: true,
+                message: ok_cli_arch_str.to_string(),
+            });
         } else {
             all_requirements_passed = false;
             let err_cli_arch_str = format!(
@@ -66,37 +181,132 @@ pub async fn main(
             );
             let pretty_err_cli_arch_str =
                 pretty_check_string::pretty_err_str(&err_cli_arch_str, true);
-            log_and_emit(pretty_err_cli_arch_str.clone(), backend_communicator);
+            log_error!(pretty_err_cli_arch_str.clone(), backend_communicator);
             result_string.push_str(&pretty_err_cli_arch_str);
+            check_results.push(RequirementCheckResult {
+                name: "processor".to_string(),
+                passed: false,
+                message: err_cli_arch_str,
+            });
         }
     }
 
     // Check Docker
-    if check_docker {
-        log_and_emit(
+    if checks.docker {
+        log_info!(
             "Checking if Docker is installed & running correctly".to_string(),
             backend_communicator,
         );
-        match get_docker_status(backend_communicator) {
-            Ok(docker_ok_string) => {
-                let pretty_docker_ok_string =
-                    pretty_check_string::pretty_ok_str(&docker_ok_string, true);
-                log_and_emit(pretty_docker_ok_string.clone(), backend_communicator);
-                result_string.push_str(&pretty_docker_ok_string);
-            }
-            Err(docker_not_ok_string) => {
-                all_requirements_passed = false;
-                let pretty_docker_not_ok_string =
-                    pretty_check_string::pretty_err_str(&docker_not_ok_string, true);
-                log_and_emit(pretty_docker_not_ok_string.clone(), backend_communicator);
-                result_string.push_str(&pretty_docker_not_ok_string);
+        let check_result = match get_docker_status(backend_communicator).await {
+            Ok(docker_info) => {
+                let check_result = docker_info.to_check_result();
+                docker_report = Some(docker_info);
+                check_result
             }
-        }
+            Err(docker_not_ok_string) => Err(docker_not_ok_string),
+        };
+        all_requirements_passed &= record_check_result(
+            "docker",
+            check_result,
+            &mut result_string,
+            &mut check_results,
+            backend_communicator,
+        );
+    }
+
+    // Check resources & connectivity
+    let requirements_config = get_requirements_config(backend_communicator);
+    if checks.virtualization {
+        log_info!(
+            "Checking if virtualization is enabled".to_string(),
+            backend_communicator,
+        );
+        let check_result = check_resources::check_virtualization(backend_communicator).await;
+        all_requirements_passed &= record_check_result(
+            "virtualization",
+            check_result,
+            &mut result_string,
+            &mut check_results,
+            backend_communicator,
+        );
+    }
+    if checks.memory {
+        log_info!(
+            "Checking if enough memory is available".to_string(),
+            backend_communicator,
+        );
+        let check_result = check_resources::check_memory(&requirements_config);
+        all_requirements_passed &= record_check_result(
+            "memory",
+            check_result,
+            &mut result_string,
+            &mut check_results,
+            backend_communicator,
+        );
+    }
+    if checks.disk {
+        log_info!(
+            "Checking if enough disk space is free".to_string(),
+            backend_communicator,
+        );
+        let check_result =
+            check_resources::check_disk(&requirements_config, backend_communicator).await;
+        all_requirements_passed &= record_check_result(
+            "disk",
+            check_result,
+            &mut result_string,
+            &mut check_results,
+            backend_communicator,
+        );
+    }
+    if checks.ports {
+        log_info!(
+            "Checking if required ports are free".to_string(),
+            backend_communicator,
+        );
+        let check_result =
+            check_resources::check_ports(&requirements_config, backend_communicator).await;
+        all_requirements_passed &= record_check_result(
+            "ports",
+            check_result,
+            &mut result_string,
+            &mut check_results,
+            backend_communicator,
+        );
+    }
+    if checks.index_connectivity {
+        log_info!(
+            "Checking if the index can be reached".to_string(),
+            backend_communicator,
+        );
+        let check_result = check_connectivity::check_index_connectivity(backend_communicator).await;
+        all_requirements_passed &= record_check_result(
+            "index_connectivity",
+            check_result,
+            &mut result_string,
+            &mut check_results,
+            backend_communicator,
+        );
+    }
+    if checks.clock {
+        log_info!(
+            "Checking if the system clock is correct".to_string(),
+            backend_communicator,
+        );
+        let check_result =
+            check_connectivity::check_clock(&requirements_config, backend_communicator).await;
+        all_requirements_passed &= record_check_result(
+            "clock",
+            check_result,
+            &mut result_string,
+            &mut check_results,
+            backend_communicator,
+        );
     }
 
     // Check Edge CLI Download
-    if check_edge_cli_binary {
-        log_and_emit(
+    if checks.edge_cli_binary {
+        log_info!(
             "Checking if Edge is downloaded correctly".to_string(),
             backend_communicator,
         );
@@ -106,44 +316,71 @@ pub async fn main(
             Ok(edge_downloaded_correctly) => {
                 let pretty_edge_downloaded_correctly =
                     pretty_ok_str(&edge_downloaded_correctly, false);
-                log_and_emit(
+                log_info!(
                     pretty_edge_downloaded_correctly.clone(),
                     backend_communicator,
                 );
                 result_string.push_str(&pretty_edge_downloaded_correctly);
+                check_results.push(RequirementCheckResult {
+                    name: "edge_cli".to_string(),
+                    passed: true,
+                    message: edge_downloaded_correctly,
+                });
             }
             Err(edge_not_downloaded_correctly) => {
                 all_requirements_passed = false;
                 let pretty_edge_not_downloaded_correctly =
                     pretty_err_str(&edge_not_downloaded_correctly, false);
-                log_and_emit(
+                log_info!(
                     pretty_edge_not_downloaded_correctly.clone(),
                     backend_communicator,
                 );
                 result_string.push_str(&pretty_edge_not_downloaded_correctly);
+                check_results.push(RequirementCheckResult {
+                    name: "edge_cli".to_string(),
+                    passed: false,
+                    message: edge_not_downloaded_correctly,
+                });
             }
         }
     }
 
-    log_and_emit("Checked requirements.".to_string(), backend_communicator);
+    log_info!("Checked requirements.".to_string(), backend_communicator);
 
     let requirements_last_checked = format!(
         "Requirements last checked on: {} ",
         dt.format("%d %B %Y %H:%M:%S %Z")
     );
-    log_and_emit(requirements_last_checked.clone(), backend_communicator);
+    log_info!(requirements_last_checked.clone(), backend_communicator);
     result_string.push_str(&requirements_last_checked);
 
+    set_last_requirement_report(RequirementReport {
+        checked_at: dt.timestamp(),
+        passed: all_requirements_passed,
+        checks: check_results,
+        docker: docker_report,
+    });
+
     if all_requirements_passed {
         let ok_all_requirements_passed = "Passed requirements.".to_string();
         let pretty_ok_all_requirements_passed = pretty_ok_str(&ok_all_requirements_passed, false);
-        log_and_emit(pretty_ok_all_requirements_passed, backend_communicator);
+        log_info!(pretty_ok_all_requirements_passed, backend_communicator);
         Ok(result_string)
     } else {
         let err_all_requirements_passed = "Did not pass all requirements.".to_string();
         let pretty_err_all_requirements_passed =
             pretty_err_str(&err_all_requirements_passed, false);
-        log_and_emit(pretty_err_all_requirements_passed, backend_communicator);
+        log_error!(pretty_err_all_requirements_passed, backend_communicator);
+        if let Err(err) = notify(
+            NodeEvent::RequirementCheckFailed,
+            "System requirements not met.",
+            &result_string,
+            backend_communicator,
+        )
+        .await
+        {
+            log_error!(err, backend_communicator);
+        }
         Err(result_string)
     }
 }
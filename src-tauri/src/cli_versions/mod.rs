@@ -0,0 +1,346 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::check_requirements::check_edge::{hash_file, normalise_checksum};
+use crate::check_requirements::check_signature::verify_local_edge_cli_signature;
+use crate::config::getters::{get_cli_config, get_edge_binary_name, get_network};
+use crate::config::setters::{set_pinned_cli_checksum, set_pinned_cli_version};
+use crate::control_edge_cli::get_edge_cli_version;
+use crate::logging::{log_error, log_info, log_warn};
+#[cfg(target_family = "unix")]
+use crate::utility::set_unix_permissions;
+use crate::BackendCommunicator;
+
+pub mod update_checker;
+
+const ARCHIVE_METADATA_FILENAME: &str = "cli_version.json";
+
+/// A previously installed Edge CLI binary kept for rollback.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ArchivedEdgeCli {
+    pub id: String,       // Directory name, unique per binary
+    pub version: String,  // As reported by the binary, "unknown" if it could not be read
+    pub checksum: String, // SHA256 of the binary
+    pub archived_at: i64, // Unix timestamp in seconds
+}
+
+/// Directory holding the archived binaries of the current network. eg. appdata/cli_versions/mainnet/
+fn get_archive_dir(backend_communicator: &BackendCommunicator) -> PathBuf {
+    let network = get_network(backend_communicator);
+    Path::new(&backend_communicator.data_dir)
+        .join("cli_versions")
+        .join(network.as_str())
+}
+
+/// Id of the archive entry of a binary, derived from its checksum.
+pub(crate) fn get_archive_id(checksum: &str) -> String {
+    checksum.chars().take(16).collect()
+}
+
+/// Extract a version number such as 1.2.3 from the `--version` output of the Edge CLI.
+pub fn parse_cli_version(version_output: &str) -> Option<String> {
+    version_output
+        .split_whitespace()
+        .map(|token| {
+            token
+                .trim_start_matches('v')
+                .trim_end_matches(|c: char| !c.is_ascii_alphanumeric())
+        })
+        .find(|token| token.starts_with(|c: char| c.is_ascii_digit()) && token.contains('.'))
+        .map(|token| token.to_string())
+}
+
+/// Returns all archived binaries of the current network, newest first.
+pub fn list_archived_edge_clis(backend_communicator: &BackendCommunicator) -> Vec<ArchivedEdgeCli> {
+    let archive_dir = get_archive_dir(backend_communicator);
+    let mut archived_edge_clis: Vec<ArchivedEdgeCli> = vec![];
+
+    let entries = match fs::read_dir(&archive_dir) {
+        Ok(entries) => entries,
+        Err(_) => return archived_edge_clis, // Nothing archived yet.
+    };
+
+    for entry in entries.flatten() {
+        let metadata_path = entry.path().join(ARCHIVE_METADATA_FILENAME);
+        let metadata_str = match fs::read_to_string(&metadata_path) {
+            Ok(metadata_str) => metadata_str,
+            Err(_) => continue,
+        };
+        match serde_json::from_str::<ArchivedEdgeCli>(&metadata_str) {
+            Ok(archived_edge_cli) => archived_edge_clis.push(archived_edge_cli),
+            Err(err) => {
+                let err_message = format!(
+                    "Ignoring unreadable archived CLI metadata {}. Err: {}",
+                    metadata_path.display(),
+                    err
+                );
+                log_error!(err_message, backend_communicator);
+            }
+        }
+    }
+
+    archived_edge_clis.sort_by_key(|archived_edge_cli| Reverse(archived_edge_cli.archived_at));
+    archived_edge_clis
+}
+
+/// Copy the installed Edge CLI into the archive before it gets replaced.
+/// Skips binaries which are already archived and prunes the archive afterwards.
+pub async fn archive_current_edge_cli(
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let bin_name = get_edge_binary_name(backend_communicator);
+    let bin_path_str = format!("{}{}", backend_communicator.data_dir, bin_name);
+    let bin_path = Path::new(&bin_path_str);
+
+    if !bin_path.exists() {
+        return Ok("No installed Edge CLI to archive.".to_string());
+    }
+
+    let checksum = hash_file(bin_path, backend_communicator)?;
+    let id = get_archive_id(&checksum);
+
+    let archive_entry_dir = get_archive_dir(backend_communicator).join(&id);
+    if archive_entry_dir.join(ARCHIVE_METADATA_FILENAME).exists() {
+        return Ok(format!("Edge CLI {} already archived.", id));
+    }
+
+    let version = match get_edge_cli_version(backend_communicator).await {
+        Ok(version_output) => {
+            parse_cli_version(&version_output).unwrap_or_else(|| "unknown".to_string())
+        }
+        Err(_) => "unknown".to_string(),
+    };
+
+    if let Err(err) = fs::create_dir_all(&archive_entry_dir) {
+        let err_message = format!(
+            "Unable to create CLI archive directory {}. Err: {}",
+            archive_entry_dir.display(),
+            err
+        );
+        log_error!(err_message.clone(), backend_communicator);
+        return Err(err_message);
+    }
+
+    if let Err(err) = fs::copy(bin_path, archive_entry_dir.join(&bin_name)) {
+        let err_message = format!("Unable to archive current Edge CLI. Err: {}", err);
+        log_error!(err_message.clone(), backend_communicator);
+        return Err(err_message);
+    }
+
+    let archived_edge_cli = ArchivedEdgeCli {
+        id: id.clone(),
+        version: version.clone(),
+        checksum,
+        archived_at: Utc::now().timestamp(),
+    };
+    let metadata_str = match serde_json::to_string_pretty(&archived_edge_cli) {
+        Ok(metadata_str) => metadata_str,
+        Err(err) => return Err(err.to_string()),
+    };
+    if let Err(err) = fs::write(
+        archive_entry_dir.join(ARCHIVE_METADATA_FILENAME),
+        metadata_str,
+    ) {
+        let err_message = format!("Unable to write archived CLI metadata. Err: {}", err);
+        log_error!(err_message.clone(), backend_communicator);
+        return Err(err_message);
+    }
+
+    let ok_message = format!("Archived Edge CLI version {} ({}).", version, id);
+    log_info!(ok_message.clone(), backend_communicator);
+
+    prune_archived_edge_clis(backend_communicator);
+    Ok(ok_message)
+}
+
+/// Remove the oldest archived binaries beyond the configured retention.
+fn prune_archived_edge_clis(backend_communicator: &BackendCommunicator) {
+    let keep_previous_binaries = get_cli_config(backend_communicator).keep_previous_binaries;
+    let archive_dir = get_archive_dir(backend_communicator);
+
+    for archived_edge_cli in list_archived_edge_clis(backend_communicator)
+        .iter()
+        .skip(keep_previous_binaries)
+    {
+        match fs::remove_dir_all(archive_dir.join(&archived_edge_cli.id)) {
+            Ok(_) => log_info!(
+                format!(
+                    "Removed old archived Edge CLI version {} ({}).",
+                    archived_edge_cli.version, archived_edge_cli.id
+                ),
+                backend_communicator,
+            ),
+            Err(err) => log_error!(
+                format!(
+                    "Unable to remove old archived Edge CLI {}. Err: {}",
+                    archived_edge_cli.id, err
+                ),
+                backend_communicator,
+            ),
+        }
+    }
+}
+
+/// Install an archived binary as the current Edge CLI.
+/// The currently installed binary is archived first so the rollback itself can be undone.
+/// The archived version is pinned so the latest CLI is not installed over it, by checksum if the
+/// version is unknown.
+pub async fn rollback_edge_cli(
+    id: String,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let archived_edge_cli;
+    match list_archived_edge_clis(backend_communicator)
+        .into_iter()
+        .find(|archived_edge_cli| archived_edge_cli.id == id)
+    {
+        Some(found) => archived_edge_cli = found,
+        None => {
+            let err_message = format!("No archived Edge CLI with id {} found.", id);
+            log_error!(err_message.clone(), backend_communicator);
+            return Err(err_message);
+        }
+    }
+
+    let bin_name = get_edge_binary_name(backend_communicator);
+    let archived_bin_path = get_archive_dir(backend_communicator)
+        .join(&archived_edge_cli.id)
+        .join(&bin_name);
+
+    let archived_checksum = hash_file(&archived_bin_path, backend_communicator)?;
+    if archived_checksum != archived_edge_cli.checksum {
+        let err_message = format!(
+            "Archived Edge CLI {} is corrupted. Expected checksum {}, calculated {}.",
+            archived_edge_cli.id, archived_edge_cli.checksum, archived_checksum
+        );
+        log_error!(err_message.clone(), backend_communicator);
+        return Err(err_message);
+    }
+
+    // Copy next to the binary first, then rename, so a failed copy never leaves a half binary.
+    // Copied before archiving the current binary, as archiving may prune the rollback target.
+    let bin_path_str = format!("{}{}", backend_communicator.data_dir, bin_name);
+    let temp_bin_path_str = format!("{}.rollback", bin_path_str);
+    if let Err(err) = fs::copy(&archived_bin_path, &temp_bin_path_str) {
+        let err_message = format!("Unable to copy archived Edge CLI. Err: {}", err);
+        log_error!(err_message.clone(), backend_communicator);
+        return Err(err_message);
+    }
+
+    if let Err(err) = archive_current_edge_cli(backend_communicator).await {
+        let _ = fs::remove_file(&temp_bin_path_str);
+        return Err(err);
+    }
+
+    if let Err(err) = fs::rename(&temp_bin_path_str, &bin_path_str) {
+        let err_message = format!("Unable to replace Edge CLI. Err: {}", err);
+        log_error!(err_message.clone(), backend_communicator);
+        return Err(err_message);
+    }
+
+    let network = get_network(backend_communicator);
+    if archived_edge_cli.version != "unknown" {
+        set_pinned_cli_version(
+            network.to_string(),
+            archived_edge_cli.version.clone(),
+            backend_communicator,
+        )?;
+    } else {
+        set_pinned_cli_checksum(
+            network.to_string(),
+            archived_edge_cli.checksum.clone(),
+            backend_communicator,
+        )?;
+    }
+
+    let ok_message = format!(
+        "Rolled back Edge CLI to version {} ({}).",
+        archived_edge_cli.version, archived_edge_cli.id
+    );
+    log_info!(ok_message.clone(), backend_communicator);
+    Ok(ok_message)
+}
+
+/// Install an Edge CLI binary from a local file, eg. on hosts without internet access.
+/// `checksum` is either the SHA256 of the binary or the path to a checksum file.
+/// A signature next to the binary (`<binary>.minisig`) is verified as well.
+pub async fn install_edge_cli_from_file(
+    binary_path_str: String,
+    checksum: String,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let binary_path = Path::new(&binary_path_str);
+    if !binary_path.is_file() {
+        let err_message = format!("Edge CLI binary {} not found.", binary_path_str);
+        log_error!(err_message.clone(), backend_communicator);
+        return Err(err_message);
+    }
+
+    let expected_checksum = if Path::new(&checksum).is_file() {
+        match fs::read_to_string(&checksum) {
+            Ok(checksum_str) => normalise_checksum(&checksum_str),
+            Err(err) => {
+                let err_message =
+                    format!("Unable to read checksum file {}. Err: {}", checksum, err);
+                log_error!(err_message.clone(), backend_communicator);
+                return Err(err_message);
+            }
+        }
+    } else {
+        normalise_checksum(&checksum)
+    };
+
+    let calculated_checksum = hash_file(binary_path, backend_communicator)?;
+    if calculated_checksum != expected_checksum {
+        let err_message = format!(
+            "Checksum of {} does not match. Expected checksum {}, calculated {}.",
+            binary_path_str, expected_checksum, calculated_checksum
+        );
+        log_error!(err_message.clone(), backend_communicator);
+        return Err(err_message);
+    }
+
+    verify_local_edge_cli_signature(&binary_path_str, backend_communicator)?;
+
+    // Copy next to the binary first, then rename, so a failed copy never leaves a half binary.
+    let bin_path_str = format!(
+        "{}{}",
+        backend_communicator.data_dir,
+        get_edge_binary_name(backend_communicator)
+    );
+    let temp_bin_path_str = format!("{}.install", bin_path_str);
+    if let Err(err) = fs::copy(binary_path, &temp_bin_path_str) {
+        let err_message = format!(
+            "Unable to copy Edge CLI from {}. Err: {}",
+            binary_path_str, err
+        );
+        log_error!(err_message.clone(), backend_communicator);
+        return Err(err_message);
+    }
+
+    if let Err(err) = archive_current_edge_cli(backend_communicator).await {
+        log_warn!(
+            format!(
+                "Continuing without archiving the current Edge CLI. Err: {}",
+                err
+            ),
+            backend_communicator,
+        );
+    }
+
+    if let Err(err) = fs::rename(&temp_bin_path_str, &bin_path_str) {
+        let err_message = format!("Unable to replace Edge CLI. Err: {}", err);
+        log_error!(err_message.clone(), backend_communicator);
+        return Err(err_message);
+    }
+
+    #[cfg(target_family = "unix")]
+    set_unix_permissions(bin_path_str, backend_communicator);
+
+    let ok_message = format!("Installed Edge CLI from {}.", binary_path_str);
+    log_info!(ok_message.clone(), backend_communicator);
+    Ok(ok_message)
+}
@@ -0,0 +1,302 @@
+use chrono::Utc;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::check_requirements::check_edge::{
+    download_verified_edge_cli, get_edge_cli_binary, get_edge_cli_checksum, hash_file,
+    install_staged_edge_cli,
+};
+use crate::cli_versions::{get_archive_id, rollback_edge_cli};
+use crate::config::getters::{get_cli_config, get_edge_binary_name, get_pinned_cli_checksum};
+use crate::config::CliUpdatePolicy;
+use crate::control_edge_cli::{device_start_from_frontend, device_stop_from_frontend};
+use crate::docker::get_edge_device_container_state;
+use crate::logging::{log_error, log_info, log_warn};
+use crate::notifications::{notify, NodeEvent};
+use crate::shutdown::spawn_background_task;
+use crate::BackendCommunicator;
+
+/// Give Docker and the frontend time to start before the first check.
+const STARTUP_DELAY_SECONDS: u64 = 300;
+
+/// Result of the last comparison between the installed and the published Edge CLI.
+#[derive(Debug, Serialize, Clone)]
+pub struct CliUpdateStatus {
+    pub checked_at: i64, // Unix timestamp in seconds
+    pub installed_checksum: String,
+    pub remote_checksum: String,
+    pub update_available: bool,
+}
+
+static LAST_CLI_UPDATE_STATUS: Mutex<Option<CliUpdateStatus>> = Mutex::new(None);
+
+/// Prevents two automatic updates from swapping the binary at the same time.
+static CLI_UPDATE_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+
+/// Marks an update as in progress until dropped, also when the update task is cancelled.
+struct CliUpdateGuard;
+
+impl CliUpdateGuard {
+    fn acquire() -> Option<Self> {
+        if CLI_UPDATE_IN_PROGRESS.swap(true, Ordering::SeqCst) {
+            None
+        } else {
+            Some(CliUpdateGuard)
+        }
+    }
+}
+
+impl Drop for CliUpdateGuard {
+    fn drop(&mut self) {
+        CLI_UPDATE_IN_PROGRESS.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Whether an automatic update is installing the Edge CLI right now.
+pub fn is_cli_update_in_progress() -> bool {
+    CLI_UPDATE_IN_PROGRESS.load(Ordering::SeqCst)
+}
+
+/// Returns the cached result of the last update check, if any.
+pub fn get_last_cli_update_status() -> Option<CliUpdateStatus> {
+    match LAST_CLI_UPDATE_STATUS.lock() {
+        Ok(last_cli_update_status) => last_cli_update_status.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+}
+
+/// Compare the checksum of the installed Edge CLI to the published checksum.
+/// The result is cached and a notification is sent when an update is available.
+pub async fn check_for_cli_update(
+    backend_communicator: &BackendCommunicator,
+) -> Result<CliUpdateStatus, String> {
+    let bin_name = get_edge_binary_name(backend_communicator);
+    let bin_path_str = format!("{}{}", backend_communicator.data_dir, bin_name);
+    let bin_path = Path::new(&bin_path_str);
+    if !bin_path.exists() {
+        return Err(String::from("Edge CLI not yet installed via GUI."));
+    }
+
+    let remote_checksum = get_edge_cli_checksum(0, backend_communicator).await?;
+    let installed_checksum = hash_file(bin_path, backend_communicator)?;
+    // A binary pinned by checksum is kept until another version is pinned.
+    let is_pinned = get_pinned_cli_checksum(backend_communicator).as_deref()
+        == Some(installed_checksum.as_str());
+    let update_available = !is_pinned && remote_checksum != installed_checksum;
+
+    let cli_update_status = CliUpdateStatus {
+        checked_at: Utc::now().timestamp(),
+        installed_checksum,
+        remote_checksum,
+        update_available,
+    };
+    if let Ok(mut last_cli_update_status) = LAST_CLI_UPDATE_STATUS.lock() {
+        *last_cli_update_status = Some(cli_update_status.clone());
+    }
+
+    if update_available {
+        log_info!(
+            "A newer Edge CLI is available.".to_string(),
+            backend_communicator,
+        );
+        if let Err(err) = notify(
+            NodeEvent::CliUpdateAvailable,
+            "Edge CLI update available.",
+            "A newer Edge CLI is available. Update it via the Staking GUI.",
+            backend_communicator,
+        )
+        .await
+        {
+            log_error!(err, backend_communicator);
+        }
+    } else {
+        log_info!(
+            "Installed Edge CLI is up to date.".to_string(),
+            backend_communicator,
+        );
+    }
+
+    Ok(cli_update_status)
+}
+
+/// Apply the configured update policy after a check found a newer Edge CLI.
+pub async fn apply_cli_update_policy(
+    cli_update_status: &CliUpdateStatus,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    if !cli_update_status.update_available {
+        return Ok("Installed Edge CLI is up to date.".to_string());
+    }
+
+    match get_cli_config(backend_communicator).update_policy {
+        CliUpdatePolicy::NotifyOnly => {
+            Ok("Edge CLI update available. Update policy is notify only.".to_string())
+        }
+        CliUpdatePolicy::AutoUpdateWhenIdle => {
+            if is_device_running(backend_communicator).await? {
+                let ok_message =
+                    "Edge CLI update postponed until the device is not running.".to_string();
+                log_info!(ok_message.clone(), backend_communicator);
+                Ok(ok_message)
+            } else {
+                install_cli_update(backend_communicator).await
+            }
+        }
+        CliUpdatePolicy::AutoUpdateAndRestart => {
+            if !is_device_running(backend_communicator).await? {
+                return install_cli_update(backend_communicator).await;
+            }
+            install_cli_update_and_restart(backend_communicator).await
+        }
+    }
+}
+
+async fn is_device_running(backend_communicator: &BackendCommunicator) -> Result<bool, String> {
+    let container_state = get_edge_device_container_state(backend_communicator).await?;
+    Ok(container_state == "running")
+}
+
+fn mark_cli_up_to_date() {
+    if let Ok(mut last_cli_update_status) = LAST_CLI_UPDATE_STATUS.lock() {
+        if let Some(cli_update_status) = last_cli_update_status.as_mut() {
+            cli_update_status.update_available = false;
+        }
+    }
+}
+
+/// Install the latest (or pinned) Edge CLI, unless another update is running.
+async fn install_cli_update(backend_communicator: &BackendCommunicator) -> Result<String, String> {
+    let cli_update_guard = match CliUpdateGuard::acquire() {
+        Some(cli_update_guard) => cli_update_guard,
+        None => return Err("An Edge CLI update is already in progress.".to_string()),
+    };
+
+    let installed = get_edge_cli_binary(backend_communicator).await;
+    drop(cli_update_guard);
+
+    if installed {
+        mark_cli_up_to_date();
+        Ok("Edge CLI updated automatically.".to_string())
+    } else {
+        Err("Automatic Edge CLI update failed.".to_string())
+    }
+}
+
+/// Install the latest (or pinned) Edge CLI while the device runs, unless another update is running.
+async fn install_cli_update_and_restart(
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let cli_update_guard = match CliUpdateGuard::acquire() {
+        Some(cli_update_guard) => cli_update_guard,
+        None => return Err("An Edge CLI update is already in progress.".to_string()),
+    };
+
+    let update_result = swap_cli_and_restart(backend_communicator).await;
+    drop(cli_update_guard);
+
+    if update_result.is_ok() {
+        mark_cli_up_to_date();
+    }
+    update_result
+}
+
+/// Download & verify the new Edge CLI while the device keeps running, then stop the device only
+/// to swap the binary. If the device does not start again, the previous Edge CLI is restored.
+async fn swap_cli_and_restart(
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let staged_filepath = download_verified_edge_cli(backend_communicator)
+        .await
+        .map_err(|err| format!("Automatic Edge CLI update failed. {}", err))?;
+
+    let bin_path_str = format!(
+        "{}{}",
+        backend_communicator.data_dir,
+        get_edge_binary_name(backend_communicator)
+    );
+    // The installed binary is archived under this id when the new one is installed.
+    let previous_cli_id = hash_file(Path::new(&bin_path_str), backend_communicator)
+        .ok()
+        .map(|checksum| get_archive_id(&checksum));
+
+    log_info!(
+        "Stopping device to update the Edge CLI.".to_string(),
+        backend_communicator,
+    );
+    if !device_stop_from_frontend(false, backend_communicator).await {
+        let _ = fs::remove_file(&staged_filepath);
+        let err_message = "Edge CLI update cancelled, the device could not be stopped.".to_string();
+        log_error!(err_message.clone(), backend_communicator);
+        return Err(err_message);
+    }
+
+    let install_result = install_staged_edge_cli(&staged_filepath, backend_communicator).await;
+
+    // Restart regardless of the update result, the node should keep earning.
+    log_info!(
+        "Starting device after Edge CLI update.".to_string(),
+        backend_communicator,
+    );
+    if device_start_from_frontend(false, backend_communicator).await {
+        return install_result.map(|_| "Edge CLI updated automatically.".to_string());
+    }
+
+    let previous_cli_id = match (install_result, previous_cli_id) {
+        (Ok(_), Some(previous_cli_id)) => previous_cli_id,
+        _ => {
+            let err_message =
+                "Device could not be started again after the Edge CLI update.".to_string();
+            log_error!(err_message.clone(), backend_communicator);
+            return Err(err_message);
+        }
+    };
+
+    log_error!(
+        "Device did not start with the new Edge CLI, rolling back to the previous one.".to_string(),
+        backend_communicator,
+    );
+    rollback_edge_cli(previous_cli_id, backend_communicator).await?;
+    let err_message = if device_start_from_frontend(false, backend_communicator).await {
+        "Device did not start with the new Edge CLI. Rolled back to the previous Edge CLI."
+    } else {
+        "Device could not be started with the new nor with the previous Edge CLI."
+    };
+    log_error!(err_message.to_string(), backend_communicator);
+    Err(err_message.to_string())
+}
+
+/// Periodically check for Edge CLI updates and apply the update policy.
+/// The interval is re-read from config each round, 0 pauses checking.
+pub fn start_cli_update_checker(backend_communicator: BackendCommunicator) {
+    spawn_background_task(async move {
+        tokio::time::sleep(Duration::from_secs(STARTUP_DELAY_SECONDS)).await;
+        loop {
+            let update_check_interval_hours =
+                get_cli_config(&backend_communicator).update_check_interval_hours;
+            if update_check_interval_hours == 0 {
+                tokio::time::sleep(Duration::from_secs(3600)).await;
+                continue;
+            }
+
+            match check_for_cli_update(&backend_communicator).await {
+                Ok(cli_update_status) => {
+                    if let Err(err) =
+                        apply_cli_update_policy(&cli_update_status, &backend_communicator).await
+                    {
+                        log_error!(err, &backend_communicator);
+                    }
+                }
+                Err(err) => log_warn!(
+                    format!("Skipped Edge CLI update check. {}", err),
+                    &backend_communicator,
+                ),
+            }
+
+            tokio::time::sleep(Duration::from_secs(update_check_interval_hours * 3600)).await;
+        }
+    });
+}
@@ -1,7 +1,13 @@
 use crate::check_requirements::check_specifications::{get_os_info, get_os_simple_strings};
-use crate::{utility::log_and_emit, BackendCommunicator};
+use crate::logging::{log_error, log_info, log_warn};
+use crate::BackendCommunicator;
 
-use super::{get_config, ConfigStruct};
+use super::{
+    get_config, CliConfig, ConfigStruct, ContainerRuntimeConfig, DownloadConfig, LoggingConfig,
+    Network, NetworkDefinition, NotificationConfig, RequirementsConfig, ShutdownConfig,
+    StakeMonitorConfig, StartupConfig, StatusApiConfig,
+};
+use std::collections::BTreeMap;
 
 pub fn get_config_path_as_str(backend_communicator: &BackendCommunicator) -> String {
     let config_path = format!("{}config.txt", backend_communicator.data_dir.clone()); // eg. appdata/config.txt
@@ -19,7 +25,7 @@ pub fn get_node_address(backend_communicator: &BackendCommunicator) -> String {
 
             if node_address == no_node_found {
                 let err = "Node not found in config.".to_string();
-                log_and_emit(err, backend_communicator);
+                log_error!(err, backend_communicator);
                 no_node_found
             } else {
                 node_address
@@ -27,7 +33,7 @@ pub fn get_node_address(backend_communicator: &BackendCommunicator) -> String {
         }
         Err(err) => {
             let err_message = format!("Could not load node address config. Error: {}", err);
-            log_and_emit(err_message, backend_communicator);
+            log_error!(err_message, backend_communicator);
             no_node_found
         }
     }
@@ -44,7 +50,7 @@ pub fn get_initialization_status(backend_communicator: &BackendCommunicator) ->
         Ok(ok_config) => config = ok_config,
         Err(err) => {
             let err_message = format!("Could not load initialization status. Err {}", err);
-            log_and_emit(err_message, backend_communicator);
+            log_error!(err_message, backend_communicator);
             return failed_to_get_code;
         }
     };
@@ -68,7 +74,7 @@ pub fn get_autostart_status(backend_communicator: &BackendCommunicator) -> bool
                 "Could not load autostart status. Assuming false. Err {}",
                 err
             );
-            log_and_emit(err_message, backend_communicator);
+            log_error!(err_message, backend_communicator);
             false
         }
     }
@@ -87,7 +93,7 @@ pub fn get_launch_minimized_status(backend_communicator: &BackendCommunicator) -
                 "Could not load minimized status. Assuming false. Err {}",
                 err
             );
-            log_and_emit(err_message, backend_communicator);
+            log_error!(err_message, backend_communicator);
             false
         }
     }
@@ -103,7 +109,7 @@ pub fn get_wallet_address(backend_communicator: &BackendCommunicator) -> String
         }
         Err(err) => {
             let err_message = format!("Could not load wallet address. Assuming false. Err {}", err);
-            log_and_emit(err_message, backend_communicator);
+            log_error!(err_message, backend_communicator);
             "CouldNotLoadWalletAddressFromConfig".to_string()
         }
     }
@@ -119,7 +125,7 @@ pub fn get_last_node_payment(backend_communicator: &BackendCommunicator) -> u64
         }
         Err(err) => {
             let err_message = format!("Could not load last node payment. Err {}", err);
-            log_and_emit(err_message, backend_communicator);
+            log_error!(err_message, backend_communicator);
             0
         }
     }
@@ -135,14 +141,14 @@ pub fn get_stake_id(backend_communicator: &BackendCommunicator) -> String {
         }
         Err(err) => {
             let err_message = format!("Could not load stake id. Err {}", err);
-            log_and_emit(err_message, backend_communicator);
+            log_error!(err_message, backend_communicator);
             "Not Set".to_string()
         }
     }
 }
 
-/// Returns network
-pub fn get_network(backend_communicator: &BackendCommunicator) -> String {
+/// Returns network. Falls back to mainnet on error.
+pub fn get_network(backend_communicator: &BackendCommunicator) -> Network {
     let config;
     match get_config(backend_communicator) {
         Ok(ok_config) => {
@@ -151,65 +157,269 @@ pub fn get_network(backend_communicator: &BackendCommunicator) -> String {
         }
         Err(err) => {
             let err_message = format!("Could not load network. Err {}", err);
-            log_and_emit(err_message, backend_communicator);
-            "Not Set".to_string()
+            log_error!(err_message, backend_communicator);
+            Network::default()
         }
     }
 }
 
-/// Get edge binary name based on network value
-pub fn get_edge_binary_name(backend_communicator: &BackendCommunicator) -> String {
+/// Returns notification settings. Falls back to defaults (no channels) on error.
+pub fn get_notification_config(backend_communicator: &BackendCommunicator) -> NotificationConfig {
+    let config;
+    match get_config(backend_communicator) {
+        Ok(ok_config) => {
+            config = ok_config;
+            config.notifications
+        }
+        Err(err) => {
+            let err_message = format!("Could not load notification settings. Err {}", err);
+            log_error!(err_message, backend_communicator);
+            NotificationConfig::default()
+        }
+    }
+}
+
+/// Returns status API settings. Falls back to defaults (disabled) on error.
+pub fn get_status_api_config(backend_communicator: &BackendCommunicator) -> StatusApiConfig {
+    let config;
+    match get_config(backend_communicator) {
+        Ok(ok_config) => {
+            config = ok_config;
+            config.status_api
+        }
+        Err(err) => {
+            let err_message = format!("Could not load status API settings. Err {}", err);
+            log_error!(err_message, backend_communicator);
+            StatusApiConfig::default()
+        }
+    }
+}
+
+/// Returns logging settings. Falls back to defaults (info, text) on error.
+pub fn get_logging_config(backend_communicator: &BackendCommunicator) -> LoggingConfig {
+    let config;
+    match get_config(backend_communicator) {
+        Ok(ok_config) => {
+            config = ok_config;
+            config.logging
+        }
+        Err(err) => {
+            let err_message = format!("Could not load logging settings. Err {}", err);
+            log_error!(err_message, backend_communicator);
+            LoggingConfig::default()
+        }
+    }
+}
+
+/// Returns stake monitor settings. Falls back to defaults (every 30 minutes) on error.
+pub fn get_stake_monitor_config(backend_communicator: &BackendCommunicator) -> StakeMonitorConfig {
+    let config;
+    match get_config(backend_communicator) {
+        Ok(ok_config) => {
+            config = ok_config;
+            config.stake_monitor
+        }
+        Err(err) => {
+            let err_message = format!("Could not load stake monitor settings. Err {}", err);
+            log_error!(err_message, backend_communicator);
+            StakeMonitorConfig::default()
+        }
+    }
+}
+
+pub fn get_requirements_config(backend_communicator: &BackendCommunicator) -> RequirementsConfig {
+    let config;
+    match get_config(backend_communicator) {
+        Ok(ok_config) => {
+            config = ok_config;
+            config.requirements
+        }
+        Err(err) => {
+            let err_message = format!("Could not load requirement settings. Err {}", err);
+            log_error!(err_message, backend_communicator);
+            RequirementsConfig::default()
+        }
+    }
+}
+
+/// Returns the container runtime settings. Falls back to automatic detection on error.
+pub fn get_container_runtime_config(
+    backend_communicator: &BackendCommunicator,
+) -> ContainerRuntimeConfig {
+    let config;
+    match get_config(backend_communicator) {
+        Ok(ok_config) => {
+            config = ok_config;
+            config.container_runtime
+        }
+        Err(err) => {
+            let err_message = format!("Could not load container runtime settings. Err {}", err);
+            log_error!(err_message, backend_communicator);
+            ContainerRuntimeConfig::default()
+        }
+    }
+}
+
+/// Returns the autostart waiting & retry settings. Falls back to defaults on error.
+pub fn get_startup_config(backend_communicator: &BackendCommunicator) -> StartupConfig {
+    let config;
+    match get_config(backend_communicator) {
+        Ok(ok_config) => {
+            config = ok_config;
+            config.startup
+        }
+        Err(err) => {
+            let err_message = format!("Could not load startup settings. Err {}", err);
+            log_error!(err_message, backend_communicator);
+            StartupConfig::default()
+        }
+    }
+}
+
+/// Returns what happens to the device on quit. Falls back to defaults (keep running) on error.
+pub fn get_shutdown_config(backend_communicator: &BackendCommunicator) -> ShutdownConfig {
+    let config;
+    match get_config(backend_communicator) {
+        Ok(ok_config) => {
+            config = ok_config;
+            config.shutdown
+        }
+        Err(err) => {
+            let err_message = format!("Could not load shutdown settings. Err {}", err);
+            log_error!(err_message, backend_communicator);
+            ShutdownConfig::default()
+        }
+    }
+}
+
+/// Returns Edge CLI version settings. Falls back to defaults (latest) on error.
+pub fn get_cli_config(backend_communicator: &BackendCommunicator) -> CliConfig {
+    let config;
+    match get_config(backend_communicator) {
+        Ok(ok_config) => {
+            config = ok_config;
+            config.cli
+        }
+        Err(err) => {
+            let err_message = format!("Could not load CLI settings. Err {}", err);
+            log_error!(err_message, backend_communicator);
+            CliConfig::default()
+        }
+    }
+}
+
+/// Returns the pinned Edge CLI version for the current network, or "latest" if not pinned.
+pub fn get_pinned_cli_version(backend_communicator: &BackendCommunicator) -> String {
     let network = get_network(backend_communicator);
+    let cli_config = get_cli_config(backend_communicator);
+    match cli_config.pinned_versions.get(network.as_str()) {
+        Some(version) if !version.is_empty() => version.clone(),
+        _ => String::from("latest"),
+    }
+}
 
-    let mainnet = String::from("mainnet");
-    let mainnet_bin_windows = String::from("edge.exe");
-    let mainnet_bin_macos = String::from("edge");
-    let mainnet_bin_linux = String::from("edge");
+/// Returns the checksum of the Edge CLI pinned for the current network, if a binary of unknown
+/// version was rolled back to. Updates are not installed over it.
+pub fn get_pinned_cli_checksum(backend_communicator: &BackendCommunicator) -> Option<String> {
+    let network = get_network(backend_communicator);
+    let cli_config = get_cli_config(backend_communicator);
+    match cli_config.pinned_checksums.get(network.as_str()) {
+        Some(checksum) if !checksum.is_empty() => Some(checksum.clone()),
+        _ => None,
+    }
+}
 
-    let testnet = String::from("testnet");
-    let testnet_bin_windows = String::from("edgetest.exe");
-    let testnet_bin_macos = String::from("edgetest");
-    let testnet_bin_linux = String::from("edgetest");
+/// Returns all known networks: mainnet, testnet and those defined in `[networks]`.
+pub fn get_network_definitions(
+    backend_communicator: &BackendCommunicator,
+) -> BTreeMap<String, NetworkDefinition> {
+    let mut network_definitions = BTreeMap::new();
+    for network in [Network::Mainnet, Network::Testnet] {
+        if let Some(network_definition) = NetworkDefinition::builtin(&network) {
+            network_definitions.insert(network.to_string(), network_definition);
+        }
+    }
+
+    match get_config(backend_communicator) {
+        Ok(config) => {
+            for (name, network_definition) in config.networks {
+                match name.parse::<Network>() {
+                    Ok(network) => {
+                        network_definitions.insert(network.to_string(), network_definition);
+                    }
+                    Err(err) => log_warn!(
+                        format!("Ignored network definition. Err {}", err),
+                        backend_communicator,
+                    ),
+                }
+            }
+        }
+        Err(err) => {
+            let err_message = format!("Could not load network definitions. Err {}", err);
+            log_error!(err_message, backend_communicator);
+        }
+    }
+    network_definitions
+}
+
+/// Returns the endpoints and binary of a network. Errors if the network is not defined.
+pub fn get_network_definition(
+    network: &Network,
+    backend_communicator: &BackendCommunicator,
+) -> Result<NetworkDefinition, String> {
+    match get_network_definitions(backend_communicator).remove(network.as_str()) {
+        Some(network_definition) => Ok(network_definition),
+        None => {
+            let err_message = format!(
+                "Network {} is not defined. Add it to [networks] in the config.",
+                network
+            );
+            log_error!(err_message.clone(), backend_communicator);
+            Err(err_message)
+        }
+    }
+}
+
+/// Get edge binary name based on network value
+pub fn get_edge_binary_name(backend_communicator: &BackendCommunicator) -> String {
+    let network = get_network(backend_communicator);
 
     let os_info = get_os_info(backend_communicator);
     let cli_os_name = os_info.cli_os_name;
     // Optional OS's
     let os_options = get_os_simple_strings();
 
-    if network == mainnet {
-        let bin_name: String;
-        if cli_os_name == os_options.windows {
-            bin_name = mainnet_bin_windows;
-        } else if cli_os_name == os_options.macos {
-            bin_name = mainnet_bin_macos;
-        } else {
-            bin_name = mainnet_bin_linux;
-        }
-
-        let ok_message = format!(
-            "Derived binary name {} based on network {}",
-            bin_name, network
-        );
-        log_and_emit(ok_message, backend_communicator);
-        bin_name
-    } else if network == testnet {
-        let bin_name;
-        if cli_os_name == os_options.windows {
-            bin_name = testnet_bin_windows;
-        } else if cli_os_name == os_options.macos {
-            bin_name = testnet_bin_macos;
-        } else {
-            bin_name = testnet_bin_linux;
-        }
-        let ok_message = format!(
-            "Derived binary name {} based on network {}",
-            bin_name, network
-        );
-        log_and_emit(ok_message, backend_communicator);
-        bin_name
+    // An undefined network gets a binary of its own name, which can never be downloaded.
+    let bin_name_without_extension = match get_network_definition(&network, backend_communicator) {
+        Ok(network_definition) => network_definition.binary_name,
+        Err(_) => network.to_string(),
+    };
+    let bin_name = if cli_os_name == os_options.windows {
+        format!("{}.exe", bin_name_without_extension)
     } else {
-        let err_message = format!("Unable to derive binary name based on network {}", network);
-        log_and_emit(err_message.clone(), backend_communicator);
-        err_message
+        bin_name_without_extension
+    };
+
+    let ok_message = format!(
+        "Derived binary name {} based on network {}",
+        bin_name, network
+    );
+    log_info!(ok_message, backend_communicator);
+    bin_name
+}
+
+/// Returns download settings. Falls back to defaults (files.edge.network) on error.
+pub fn get_download_config(backend_communicator: &BackendCommunicator) -> DownloadConfig {
+    let config;
+    match get_config(backend_communicator) {
+        Ok(ok_config) => {
+            config = ok_config;
+            config.download
+        }
+        Err(err) => {
+            let err_message = format!("Could not load download settings. Err {}", err);
+            log_error!(err_message, backend_communicator);
+            DownloadConfig::default()
+        }
     }
 }
@@ -1,8 +1,9 @@
-use std::{fs, path::Path};
+use std::{collections::BTreeMap, fmt, fs, path::Path, str::FromStr};
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
-use crate::{utility::log_and_emit, BackendCommunicator};
+use crate::logging::{log_error, log_info, log_warn, reload_logging_config, LogLevel};
+use crate::BackendCommunicator;
 
 use self::getters::get_config_path_as_str;
 
@@ -16,11 +17,499 @@ pub struct ConfigStruct {
     pub launch_minimized: bool, // Does the program start minimized?
     pub last_node_payment: u64, // When was the last node earnings payment to the user?
     pub wallet_address: String, // What is the wallet address from which the device was assigned?
-    pub network: String,   // On which Edge network is the device, mainnet or testnet?
-    pub address: String,   // What is the device XE address?
+    #[serde(deserialize_with = "deserialize_network")]
+    pub network: Network, // On which Edge network is the device, mainnet, testnet or one of [networks]?
+    pub address: String,     // What is the device XE address?
     pub private_key: String, // What is the private key of the XE address?
-    pub public_key: String, // What is the public key of the XE address?
-    pub stake_id: String,  // What is the stake ID of the device?
+    pub public_key: String,  // What is the public key of the XE address?
+    pub stake_id: String,    // What is the stake ID of the device?
+    #[serde(default)]
+    pub notifications: NotificationConfig, // Where and when are node events sent?
+    #[serde(default)]
+    pub status_api: StatusApiConfig, // Local HTTP status & metrics endpoint settings.
+    #[serde(default)]
+    pub cli: CliConfig, // Which Edge CLI version is used and how many previous ones are kept?
+    #[serde(default)]
+    pub download: DownloadConfig, // Where is the Edge CLI downloaded from?
+    #[serde(default)]
+    pub network_identities: BTreeMap<String, DeviceIdentity>, // Network -> device of the networks which are not active.
+    #[serde(default)]
+    pub logging: LoggingConfig, // Log level, format, rotation & retention of log.txt.
+    #[serde(default)]
+    pub stake_monitor: StakeMonitorConfig, // How often is the stake checked on the index?
+    #[serde(default)]
+    pub networks: BTreeMap<String, NetworkDefinition>, // Network -> endpoints, overrides or adds to mainnet & testnet.
+    #[serde(default)]
+    pub requirements: RequirementsConfig, // Which additional system checks run before the device starts?
+    #[serde(default)]
+    pub container_runtime: ContainerRuntimeConfig, // Docker, Podman or a custom executable for the device container.
+    #[serde(default)]
+    pub startup: StartupConfig, // How long does autostart wait for Docker and retry starting the device?
+    #[serde(default)]
+    pub shutdown: ShutdownConfig, // Is the device stopped when the program quits?
+}
+
+impl ConfigStruct {
+    /// Copy of the config with private keys and notification credentials masked, eg. for a
+    /// support bundle.
+    pub fn without_secrets(&self) -> ConfigStruct {
+        let masked = |secret: &mut String| {
+            if !secret.is_empty() && secret != "Unset" {
+                *secret = "[REDACTED]".to_string();
+            }
+        };
+
+        let mut config = self.clone();
+        masked(&mut config.private_key);
+        for device_identity in config.network_identities.values_mut() {
+            masked(&mut device_identity.private_key);
+        }
+        for notification_channel in config.notifications.channels.iter_mut() {
+            match notification_channel {
+                NotificationChannel::Webhook { url } => masked(url),
+                NotificationChannel::Discord { webhook_url } => masked(webhook_url),
+                NotificationChannel::Telegram { bot_token, .. } => masked(bot_token),
+                NotificationChannel::Email { smtp_password, .. } => masked(smtp_password),
+            }
+        }
+        config
+    }
+}
+
+/// Edge network the device runs on, stored in the config as 'mainnet', 'testnet' or the name of
+/// a network defined in `[networks]`.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(into = "String", try_from = "String")]
+pub enum Network {
+    #[default]
+    Mainnet,
+    Testnet,
+    Custom(String), // A private or staging network, eg. staging
+}
+
+impl Network {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Network::Mainnet => "mainnet",
+            Network::Testnet => "testnet",
+            Network::Custom(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Network {
+    type Err = String;
+
+    /// Network names are used in paths, urls and Docker volume names, so only a-z, 0-9, - and _.
+    fn from_str(network: &str) -> Result<Self, Self::Err> {
+        let name = network.trim().to_lowercase();
+        match name.as_str() {
+            "mainnet" => Ok(Network::Mainnet),
+            "testnet" => Ok(Network::Testnet),
+            _ if !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+            {
+                Ok(Network::Custom(name))
+            }
+            _ => Err(format!(
+                "Invalid network name {}. Only letters, digits, - and _ are allowed.",
+                network
+            )),
+        }
+    }
+}
+
+impl From<Network> for String {
+    fn from(network: Network) -> Self {
+        network.to_string()
+    }
+}
+
+impl TryFrom<String> for Network {
+    type Error = String;
+
+    fn try_from(network: String) -> Result<Self, Self::Error> {
+        Network::from_str(&network)
+    }
+}
+
+/// Endpoints and binary of an Edge network.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct NetworkDefinition {
+    pub index_url: String,    // eg. https://index.xe.network
+    pub wallet_url: String,   // eg. https://wallet.xe.network
+    pub cli_base_url: String, // Empty uses the base_url of [download].
+    pub binary_name: String,  // eg. edge, .exe is added on Windows.
+}
+
+impl Default for NetworkDefinition {
+    fn default() -> Self {
+        NetworkDefinition {
+            index_url: String::new(),
+            wallet_url: String::new(),
+            cli_base_url: String::new(),
+            binary_name: "edge".to_string(),
+        }
+    }
+}
+
+impl NetworkDefinition {
+    /// Definitions of the public Edge networks, used when they are not in `[networks]`.
+    pub fn builtin(network: &Network) -> Option<NetworkDefinition> {
+        match network {
+            Network::Mainnet => Some(NetworkDefinition {
+                index_url: "https://index.xe.network".to_string(),
+                wallet_url: "https://wallet.xe.network".to_string(),
+                cli_base_url: String::new(),
+                binary_name: "edge".to_string(),
+            }),
+            Network::Testnet => Some(NetworkDefinition {
+                index_url: "https://index.test.network".to_string(),
+                wallet_url: "https://wallet.test.network".to_string(),
+                cli_base_url: String::new(),
+                binary_name: "edgetest".to_string(),
+            }),
+            Network::Custom(_) => None,
+        }
+    }
+}
+
+/// An invalid network name in the config falls back to mainnet, instead of resetting the whole config.
+fn deserialize_network<'de, D>(deserializer: D) -> Result<Network, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let network = String::deserialize(deserializer)?;
+    Ok(Network::from_str(&network).unwrap_or_default())
+}
+
+/// Device data kept per network, so switching back restores the previous device.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct DeviceIdentity {
+    pub initialized: bool,
+    pub last_node_payment: u64,
+    pub wallet_address: String,
+    pub address: String,
+    pub private_key: String,
+    pub public_key: String,
+    pub stake_id: String,
+}
+
+impl Default for DeviceIdentity {
+    fn default() -> Self {
+        DeviceIdentity {
+            initialized: false,
+            last_node_payment: 0,
+            wallet_address: "Unset".to_string(),
+            address: "Unset".to_string(),
+            private_key: "Unset".to_string(),
+            public_key: "Unset".to_string(),
+            stake_id: "Unset".to_string(),
+        }
+    }
+}
+
+/// Format of the lines in log.txt.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Text, // eg. 2023-05-01T12:00:00.000Z INFO  docker: Closed docker container
+    Json, // One JSON object per line.
+}
+
+/// Level, format, rotation & retention of log.txt.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct LoggingConfig {
+    pub level: LogLevel, // Entries below this level are not written to log.txt.
+    pub targets: BTreeMap<String, LogLevel>, // Module -> level, eg. docker = 'debug'. Overrides level.
+    pub format: LogFormat,
+    pub max_file_size_mb: u64, // Rotate log.txt at this size. 0 disables.
+    pub rotate_daily: bool,    // Rotate log.txt when the day changes?
+    pub max_files: usize,      // Number of rotated logs kept, eg. log.txt.1
+    pub max_age_days: u64,     // Rotated logs older than this are removed. 0 disables.
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        LoggingConfig {
+            level: LogLevel::Info,
+            targets: BTreeMap::new(),
+            format: LogFormat::Text,
+            max_file_size_mb: 10,
+            rotate_daily: true,
+            max_files: 7,
+            max_age_days: 30,
+        }
+    }
+}
+
+/// Periodic check whether the stake still backs the device.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct StakeMonitorConfig {
+    pub check_interval_minutes: u64, // 0 disables.
+    pub stop_device: bool, // Stop the device when the stake is unlocking, released or reassigned?
+}
+
+impl Default for StakeMonitorConfig {
+    fn default() -> Self {
+        StakeMonitorConfig {
+            check_interval_minutes: 30,
+            stop_device: true,
+        }
+    }
+}
+
+/// Waiting & retries when the node is started automatically at launch.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct StartupConfig {
+    pub docker_timeout_minutes: u64, // Docker Desktop can take minutes to start after login.
+    pub max_retry_interval_seconds: u64, // Retries back off from 5 seconds up to this interval.
+    pub start_attempts: u32,         // How often is starting the device tried once Docker is ready?
+}
+
+impl Default for StartupConfig {
+    fn default() -> Self {
+        StartupConfig {
+            docker_timeout_minutes: 30,
+            max_retry_interval_seconds: 60,
+            start_attempts: 5,
+        }
+    }
+}
+
+/// What happens to the device when the program quits.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ShutdownConfig {
+    pub stop_device_on_quit: bool, // By default the device keeps running without the GUI.
+    pub stop_timeout_seconds: u64, // Quit anyway if stopping the device takes longer.
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        ShutdownConfig {
+            stop_device_on_quit: false,
+            stop_timeout_seconds: 120,
+        }
+    }
+}
+
+/// Additional system requirement checks, run before Edge CLI commands which start the device.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct RequirementsConfig {
+    pub check_memory: bool,
+    pub check_disk: bool,
+    pub check_ports: bool,
+    pub check_virtualization: bool,
+    pub check_clock: bool,
+    pub check_index_connectivity: bool, // Off by default, an unreachable index would block starting the device.
+    pub min_free_memory_mb: u64,
+    pub min_free_disk_gb: u64, // For the data directory and the Docker data directory.
+    pub required_ports: Vec<u16>, // Ports which must not be used by other programs, eg. 80 & 443 for gateways.
+    pub max_clock_skew_seconds: i64, // Compared with the time of the index.
+    pub min_docker_version: String, // Older Docker engines fail the Docker check.
+    pub min_podman_version: String, // Older Podman versions fail the Docker check.
+}
+
+impl Default for RequirementsConfig {
+    fn default() -> Self {
+        RequirementsConfig {
+            check_memory: true,
+            check_disk: true,
+            check_ports: true,
+            check_virtualization: true,
+            check_clock: true,
+            check_index_connectivity: false,
+            min_free_memory_mb: 1024,
+            min_free_disk_gb: 10,
+            required_ports: vec![],
+            max_clock_skew_seconds: 60,
+            min_docker_version: "20.10.0".to_string(),
+            min_podman_version: "4.0.0".to_string(),
+        }
+    }
+}
+
+/// Container runtime which runs the Edge device container.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ContainerRuntimeKind {
+    Auto,   // Docker if installed, otherwise Podman.
+    Docker, // The docker executable.
+    Podman, // The podman executable.
+    Custom, // The executable of `executable`, eg. a full path or nerdctl.
+}
+
+/// Which container runtime is used and how it is reached.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct ContainerRuntimeConfig {
+    pub runtime: ContainerRuntimeKind,
+    pub executable: String, // Path or name of the executable. Empty uses docker or podman from the PATH.
+    pub socket: String, // eg. unix:///run/user/1000/podman/podman.sock. Empty uses the default of the runtime.
+}
+
+impl Default for ContainerRuntimeConfig {
+    fn default() -> Self {
+        ContainerRuntimeConfig {
+            runtime: ContainerRuntimeKind::Auto,
+            executable: "".to_string(),
+            socket: "".to_string(),
+        }
+    }
+}
+
+/// Source of Edge CLI downloads, for hosts behind a proxy or without internet access.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct DownloadConfig {
+    pub base_url: String, // https://, http://, file:// or a local directory with the same layout as files.edge.network/cli
+    pub proxy: String,    // eg. http://proxy.local:3128. Empty uses the system proxy settings.
+}
+
+impl Default for DownloadConfig {
+    fn default() -> Self {
+        DownloadConfig {
+            base_url: "https://files.edge.network/cli".to_string(),
+            proxy: String::new(),
+        }
+    }
+}
+
+/// Edge CLI version pinning and retention of previously installed binaries.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CliConfig {
+    pub pinned_versions: BTreeMap<String, String>, // Network -> version eg. mainnet = '1.2.3'. Missing means latest.
+    pub pinned_checksums: BTreeMap<String, String>, // Network -> SHA256 of a rolled back binary of unknown version.
+    pub keep_previous_binaries: usize, // Number of replaced binaries kept per network for rollback.
+    pub update_policy: CliUpdatePolicy, // What happens when a newer Edge CLI is found?
+    pub update_check_interval_hours: u64, // How often to check for a newer Edge CLI. 0 disables.
+    pub require_signature: bool, // Refuse to install an Edge CLI without a valid minisign signature?
+    pub trusted_public_keys: Vec<String>, // Minisign public keys (base64) trusted in addition to the embedded Edge release key.
+}
+
+impl Default for CliConfig {
+    fn default() -> Self {
+        CliConfig {
+            pinned_versions: BTreeMap::new(),
+            pinned_checksums: BTreeMap::new(),
+            keep_previous_binaries: 3,
+            update_policy: CliUpdatePolicy::NotifyOnly,
+            update_check_interval_hours: 24,
+            require_signature: false,
+            trusted_public_keys: vec![],
+        }
+    }
+}
+
+/// Action taken by the background update checker when a newer Edge CLI is available.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CliUpdatePolicy {
+    NotifyOnly,           // Only notify, the user updates via the GUI.
+    AutoUpdateWhenIdle,   // Update when the device container is not running.
+    AutoUpdateAndRestart, // Stop the device, update and start the device again.
+}
+
+/// Settings of the optional local HTTP status API. Changes apply after restarting the program.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct StatusApiConfig {
+    pub enabled: bool,        // Serve /health, /status and /metrics?
+    pub bind_address: String, // Only change from localhost if the port is firewalled.
+    pub port: u16,
+}
+
+impl Default for StatusApiConfig {
+    fn default() -> Self {
+        StatusApiConfig {
+            enabled: false,
+            bind_address: "127.0.0.1".to_string(),
+            port: 9615,
+        }
+    }
+}
+
+/// A destination for node event notifications.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotificationChannel {
+    Webhook {
+        url: String, // Receives a JSON POST for every event.
+    },
+    Discord {
+        webhook_url: String, // Discord channel webhook url.
+    },
+    Telegram {
+        bot_token: String,
+        chat_id: String,
+        #[serde(default = "default_telegram_api_url")]
+        api_url: String, // Override to point at a local stand-in.
+    },
+    Email {
+        smtp_host: String,
+        smtp_port: u16,
+        #[serde(default = "default_smtp_tls")]
+        smtp_tls: bool, // Disable for plaintext local relays only.
+        smtp_username: String,
+        smtp_password: String,
+        from: String,
+        to: String,
+    },
+}
+
+fn default_telegram_api_url() -> String {
+    "https://api.telegram.org".to_string()
+}
+
+fn default_smtp_tls() -> bool {
+    true
+}
+
+/// Per-event toggles, channels and rate limiting for node event notifications.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct NotificationConfig {
+    pub channels: Vec<NotificationChannel>, // Empty means desktop notifications only.
+    pub node_offline: bool,                 // Notify when the index sees the node offline.
+    pub node_online: bool,                  // Notify when the node comes back online.
+    pub earnings_received: bool,            // Notify on node earnings payouts.
+    pub lottery_won: bool,                  // Notify on lottery winnings.
+    pub cli_update_available: bool,         // Notify when a newer Edge CLI is available.
+    pub requirement_check_failed: bool,     // Notify when system requirement checks fail.
+    pub stake_changed: bool,                // Notify when the stake no longer backs the device.
+    pub rate_limit_seconds: u64, // Minimum time between two notifications of the same event.
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        NotificationConfig {
+            channels: vec![],
+            node_offline: true,
+            node_online: true,
+            earnings_received: true,
+            lottery_won: true,
+            cli_update_available: true,
+            requirement_check_failed: true,
+            stake_changed: true,
+            rate_limit_seconds: 3600,
+        }
+    }
 }
 
 /// Create the default config file locally
@@ -34,18 +523,31 @@ pub fn create_default_config(backend_communicator: &BackendCommunicator) -> Resu
         last_node_payment: 0,
         wallet_address: "Unset".to_string(),
         address: "Unset".to_string(),
-        network: "mainnet".to_string(),
+        network: Network::Mainnet,
         private_key: "Unset".to_string(),
         public_key: "Unset".to_string(),
         stake_id: "Unset".to_string(),
+        notifications: NotificationConfig::default(),
+        status_api: StatusApiConfig::default(),
+        cli: CliConfig::default(),
+        download: DownloadConfig::default(),
+        network_identities: BTreeMap::new(),
+        logging: LoggingConfig::default(),
+        stake_monitor: StakeMonitorConfig::default(),
+        networks: BTreeMap::new(),
+        requirements: RequirementsConfig::default(),
+        container_runtime: ContainerRuntimeConfig::default(),
+        startup: StartupConfig::default(),
+        shutdown: ShutdownConfig::default(),
     };
     match confy::store_path(config_path.clone(), default_config) {
         Ok(_) => {
-            log_and_emit(
+            reload_logging_config();
+            log_info!(
                 format!("Created initial config file at location: {}", config_path),
                 backend_communicator,
             );
-            log_and_emit(
+            log_info!(
                 "Awaiting initial command...".to_string(),
                 backend_communicator,
             );
@@ -68,11 +570,11 @@ pub fn create_config_if_not_exists(
         match create_default_config(backend_communicator) {
             Ok(_) => {
                 let ok_message = "Created default config.".to_string();
-                log_and_emit(ok_message.clone(), backend_communicator);
+                log_info!(ok_message.clone(), backend_communicator);
                 return Ok(ok_message);
             }
             Err(err_string) => {
-                log_and_emit(err_string.clone(), backend_communicator);
+                log_error!(err_string.clone(), backend_communicator);
                 return Err(err_string);
             }
         }
@@ -83,6 +585,23 @@ pub fn create_config_if_not_exists(
     Ok(ok_message)
 }
 
+/// Write the config file through to disk, eg. before the program exits.
+pub fn flush_config(backend_communicator: &BackendCommunicator) -> Result<(), String> {
+    let config_path_as_str = get_config_path_as_str(backend_communicator);
+    // Windows only flushes files opened for writing.
+    match fs::OpenOptions::new()
+        .append(true)
+        .open(&config_path_as_str)
+        .and_then(|config_file| config_file.sync_all())
+    {
+        Ok(_) => Ok(()),
+        Err(err) => Err(format!(
+            "Unable to flush config at path {}. Err: {}",
+            config_path_as_str, err
+        )),
+    }
+}
+
 /// Load config file
 pub fn get_config(backend_communicator: &BackendCommunicator) -> Result<ConfigStruct, String> {
     let config_path_as_str = get_config_path_as_str(backend_communicator);
@@ -97,27 +616,27 @@ pub fn get_config(backend_communicator: &BackendCommunicator) -> Result<ConfigSt
     match confy::load_path(config_path) {
         Ok(ok_config) => Ok(ok_config),
         Err(_) => {
-            log_and_emit(
+            log_warn!(
                 format!(
                     "Unable to load config at path {}. Assumed corrupted.",
                     config_path.display()
                 ),
                 backend_communicator,
             );
-            log_and_emit(
+            log_warn!(
                 format!(
                     "Attempting to restore corrupted config to default state. Path: {}",
                     config_path.display()
                 ),
                 backend_communicator,
             );
-            log_and_emit(
+            log_info!(
                 format!("Removing corrupted file at path {}", config_path.display()),
                 backend_communicator,
             );
             match fs::remove_file(config_path) {
                 Ok(_) => {
-                    log_and_emit(
+                    log_info!(
                         format!(
                             "Removed corrupted config at path : {}",
                             config_path.display()
@@ -132,12 +651,12 @@ pub fn get_config(backend_communicator: &BackendCommunicator) -> Result<ConfigSt
                     let error_message =
                         "Could not load config, but restored to default value.".to_string();
 
-                    log_and_emit(error_message.clone(), backend_communicator);
+                    log_error!(error_message.clone(), backend_communicator);
                     Err(error_message)
                 }
                 Err(_) => {
                     let error_message = "Unable to remove corrupted config file.".to_string();
-                    log_and_emit(error_message.clone(), backend_communicator);
+                    log_error!(error_message.clone(), backend_communicator);
                     Err(error_message)
                 }
             }
@@ -1,6 +1,10 @@
-use crate::{utility::log_and_emit, BackendCommunicator};
+use crate::logging::{log_error, log_info, reload_logging_config};
+use crate::BackendCommunicator;
 
-use super::{get_config, getters::get_config_path_as_str};
+use super::{
+    get_config, getters::get_config_path_as_str, DeviceIdentity, DownloadConfig, LoggingConfig,
+    Network, NotificationConfig, StatusApiConfig,
+};
 
 /// Write initialized value to config
 pub fn set_device_initialization_status(
@@ -14,7 +18,7 @@ pub fn set_device_initialization_status(
             let mut changed_config = ok_config;
             changed_config.initialized = device_initialization_status;
 
-            log_and_emit(
+            log_info!(
                 format!(
                     "Changed config initialization status to: {}",
                     device_initialization_status
@@ -33,7 +37,7 @@ pub fn set_device_initialization_status(
 
 /// Save device data into the config file.
 pub fn set_device_data(
-    network: &String,
+    network: &Network,
     address: &String,
     private_key: &String,
     public_key: &String,
@@ -45,14 +49,16 @@ pub fn set_device_data(
         Ok(ok_config) => {
             let mut changed_config = ok_config;
             changed_config.address = address.to_string();
-            changed_config.network = network.to_string();
+            changed_config.network = network.clone();
             changed_config.private_key = private_key.to_string();
             changed_config.public_key = public_key.to_string();
 
             match confy::store_path(config_path, changed_config) {
                 Ok(_) => {
+                    // The new private key must be masked in the log.
+                    reload_logging_config();
                     let ok_message = "Stored device data in config.".to_string();
-                    log_and_emit(ok_message.clone(), backend_communicator);
+                    log_info!(ok_message.clone(), backend_communicator);
                     Ok(ok_message)
                 }
                 Err(_) => Err("Unable to store config file at location".to_string()),
@@ -73,7 +79,7 @@ pub fn set_autostart_status(
             let mut changed_config = ok_config;
             changed_config.is_auto_start_enabled = autostart_status;
 
-            log_and_emit(
+            log_info!(
                 format!(
                     "Changed autostart state to: {}",
                     changed_config.is_auto_start_enabled
@@ -85,7 +91,7 @@ pub fn set_autostart_status(
                 Ok(_) => Ok(()),
                 Err(_) => {
                     let err_msg = "Unable to store config file at location".to_string();
-                    log_and_emit(err_msg.clone(), backend_communicator);
+                    log_error!(err_msg.clone(), backend_communicator);
                     Err(err_msg)
                 }
             }
@@ -94,27 +100,55 @@ pub fn set_autostart_status(
     }
 }
 
-pub fn set_network(
-    network: String,
+/// Switch the active network, keeping the device of the current network for switching back.
+/// Returns the device of the target network, which is unset if it was never set up.
+pub fn switch_device_identity(
+    network: &Network,
     backend_communicator: &BackendCommunicator,
-) -> Result<(), std::string::String> {
+) -> Result<DeviceIdentity, String> {
     let config_path = get_config_path_as_str(backend_communicator);
 
     match get_config(backend_communicator) {
         Ok(ok_config) => {
             let mut changed_config = ok_config;
-            changed_config.network = network;
-
-            log_and_emit(
-                format!("Set network to: {}", changed_config.network),
-                backend_communicator,
-            );
+            let current_identity = DeviceIdentity {
+                initialized: changed_config.initialized,
+                last_node_payment: changed_config.last_node_payment,
+                wallet_address: changed_config.wallet_address.clone(),
+                address: changed_config.address.clone(),
+                private_key: changed_config.private_key.clone(),
+                public_key: changed_config.public_key.clone(),
+                stake_id: changed_config.stake_id.clone(),
+            };
+            changed_config
+                .network_identities
+                .insert(changed_config.network.to_string(), current_identity);
+
+            let target_identity = changed_config
+                .network_identities
+                .remove(network.as_str())
+                .unwrap_or_default();
+            changed_config.network = network.clone();
+            changed_config.initialized = target_identity.initialized;
+            changed_config.last_node_payment = target_identity.last_node_payment;
+            changed_config.wallet_address = target_identity.wallet_address.clone();
+            changed_config.address = target_identity.address.clone();
+            changed_config.private_key = target_identity.private_key.clone();
+            changed_config.public_key = target_identity.public_key.clone();
+            changed_config.stake_id = target_identity.stake_id.clone();
 
             match confy::store_path(config_path, changed_config) {
-                Ok(_) => Ok(()),
+                Ok(_) => {
+                    reload_logging_config();
+                    log_info!(
+                        format!("Switched device data to network: {}", network),
+                        backend_communicator,
+                    );
+                    Ok(target_identity)
+                }
                 Err(_) => {
                     let err_msg = "Unable to store config file at location".to_string();
-                    log_and_emit(err_msg.clone(), backend_communicator);
+                    log_error!(err_msg.clone(), backend_communicator);
                     Err(err_msg)
                 }
             }
@@ -134,7 +168,7 @@ pub fn set_launch_minimized_status(
             let mut changed_config = ok_config;
             changed_config.launch_minimized = launch_minimized;
 
-            log_and_emit(
+            log_info!(
                 format!(
                     "The program will launch minimized: {}",
                     changed_config.launch_minimized
@@ -146,7 +180,7 @@ pub fn set_launch_minimized_status(
                 Ok(_) => Ok(()),
                 Err(_) => {
                     let err_msg = "Unable to store config file at location".to_string();
-                    log_and_emit(err_msg.clone(), backend_communicator);
+                    log_error!(err_msg.clone(), backend_communicator);
                     Err(err_msg)
                 }
             }
@@ -166,7 +200,7 @@ pub fn set_wallet_address(
             let mut changed_config = ok_config;
             changed_config.wallet_address = wallet_address;
 
-            log_and_emit(
+            log_info!(
                 format!(
                     "Set wallet address in config: {}",
                     changed_config.wallet_address
@@ -178,7 +212,7 @@ pub fn set_wallet_address(
                 Ok(_) => Ok(()),
                 Err(_) => {
                     let err_msg = "Unable to store config file at location".to_string();
-                    log_and_emit(err_msg.clone(), backend_communicator);
+                    log_error!(err_msg.clone(), backend_communicator);
                     Err(err_msg)
                 }
             }
@@ -198,7 +232,7 @@ pub fn set_last_node_payment(
             let mut changed_config = ok_config;
             changed_config.last_node_payment = last_node_payment;
 
-            log_and_emit(
+            log_info!(
                 format!(
                     "Set last node payment timestamp in config: {}",
                     changed_config.last_node_payment
@@ -210,7 +244,7 @@ pub fn set_last_node_payment(
                 Ok(_) => Ok(()),
                 Err(_) => {
                     let err_msg = "Unable to store config file at location".to_string();
-                    log_and_emit(err_msg.clone(), backend_communicator);
+                    log_error!(err_msg.clone(), backend_communicator);
                     Err(err_msg)
                 }
             }
@@ -230,7 +264,7 @@ pub fn set_stake_id(
             let mut changed_config = ok_config;
             changed_config.stake_id = stake_id;
 
-            log_and_emit(
+            log_info!(
                 format!("Set stake ID in config: {}", changed_config.stake_id),
                 backend_communicator,
             );
@@ -239,7 +273,219 @@ pub fn set_stake_id(
                 Ok(_) => Ok(()),
                 Err(_) => {
                     let err_msg = "Unable to store config file at location".to_string();
-                    log_and_emit(err_msg.clone(), backend_communicator);
+                    log_error!(err_msg.clone(), backend_communicator);
+                    Err(err_msg)
+                }
+            }
+        }
+        Err(err) => Err(err),
+    }
+}
+
+pub fn set_notification_config(
+    notification_config: NotificationConfig,
+    backend_communicator: &BackendCommunicator,
+) -> Result<(), std::string::String> {
+    let config_path = get_config_path_as_str(backend_communicator);
+
+    match get_config(backend_communicator) {
+        Ok(ok_config) => {
+            let mut changed_config = ok_config;
+            changed_config.notifications = notification_config;
+
+            log_info!(
+                format!(
+                    "Set notification settings in config. Channels: {}",
+                    changed_config.notifications.channels.len()
+                ),
+                backend_communicator,
+            );
+
+            match confy::store_path(config_path, changed_config) {
+                Ok(_) => {
+                    // Notification credentials must be masked in the log.
+                    reload_logging_config();
+                    Ok(())
+                }
+                Err(_) => {
+                    let err_msg = "Unable to store config file at location".to_string();
+                    log_error!(err_msg.clone(), backend_communicator);
+                    Err(err_msg)
+                }
+            }
+        }
+        Err(err) => Err(err),
+    }
+}
+
+pub fn set_status_api_config(
+    status_api_config: StatusApiConfig,
+    backend_communicator: &BackendCommunicator,
+) -> Result<(), std::string::String> {
+    let config_path = get_config_path_as_str(backend_communicator);
+
+    match get_config(backend_communicator) {
+        Ok(ok_config) => {
+            let mut changed_config = ok_config;
+            changed_config.status_api = status_api_config;
+
+            log_info!(
+                format!(
+                    "Set status API in config. Enabled: {}, address: {}:{}. Restart the program to apply.",
+                    changed_config.status_api.enabled,
+                    changed_config.status_api.bind_address,
+                    changed_config.status_api.port
+                ),
+                backend_communicator,
+            );
+
+            match confy::store_path(config_path, changed_config) {
+                Ok(_) => Ok(()),
+                Err(_) => {
+                    let err_msg = "Unable to store config file at location".to_string();
+                    log_error!(err_msg.clone(), backend_communicator);
+                    Err(err_msg)
+                }
+            }
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Pin the Edge CLI version of a network. "latest" or an empty version removes the pin.
+/// Replaces a pin by checksum.
+pub fn set_pinned_cli_version(
+    network: String,
+    version: String,
+    backend_communicator: &BackendCommunicator,
+) -> Result<(), std::string::String> {
+    let config_path = get_config_path_as_str(backend_communicator);
+
+    match get_config(backend_communicator) {
+        Ok(ok_config) => {
+            let mut changed_config = ok_config;
+            changed_config.cli.pinned_checksums.remove(&network);
+            if version.is_empty() || version == "latest" {
+                changed_config.cli.pinned_versions.remove(&network);
+                log_info!(
+                    format!("Edge CLI for {} will use the latest version.", network),
+                    backend_communicator,
+                );
+            } else {
+                log_info!(
+                    format!("Pinned Edge CLI for {} to version {}.", network, version),
+                    backend_communicator,
+                );
+                changed_config.cli.pinned_versions.insert(network, version);
+            }
+
+            match confy::store_path(config_path, changed_config) {
+                Ok(_) => Ok(()),
+                Err(_) => {
+                    let err_msg = "Unable to store config file at location".to_string();
+                    log_error!(err_msg.clone(), backend_communicator);
+                    Err(err_msg)
+                }
+            }
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Pin the Edge CLI of a network to a binary of unknown version by its checksum.
+/// Removes the version pin, the binary is not downloaded again.
+pub fn set_pinned_cli_checksum(
+    network: String,
+    checksum: String,
+    backend_communicator: &BackendCommunicator,
+) -> Result<(), std::string::String> {
+    let config_path = get_config_path_as_str(backend_communicator);
+
+    match get_config(backend_communicator) {
+        Ok(ok_config) => {
+            let mut changed_config = ok_config;
+            log_info!(
+                format!("Pinned Edge CLI for {} to checksum {}.", network, checksum),
+                backend_communicator,
+            );
+            changed_config.cli.pinned_versions.remove(&network);
+            changed_config
+                .cli
+                .pinned_checksums
+                .insert(network, checksum);
+
+            match confy::store_path(config_path, changed_config) {
+                Ok(_) => Ok(()),
+                Err(_) => {
+                    let err_msg = "Unable to store config file at location".to_string();
+                    log_error!(err_msg.clone(), backend_communicator);
+                    Err(err_msg)
+                }
+            }
+        }
+        Err(err) => Err(err),
+    }
+}
+
+pub fn set_download_config(
+    download_config: DownloadConfig,
+    backend_communicator: &BackendCommunicator,
+) -> Result<(), std::string::String> {
+    let config_path = get_config_path_as_str(backend_communicator);
+
+    match get_config(backend_communicator) {
+        Ok(ok_config) => {
+            let mut changed_config = ok_config;
+            changed_config.download = download_config;
+
+            log_info!(
+                format!(
+                    "Set download source in config to {}.",
+                    changed_config.download.base_url
+                ),
+                backend_communicator,
+            );
+
+            match confy::store_path(config_path, changed_config) {
+                Ok(_) => Ok(()),
+                Err(_) => {
+                    let err_msg = "Unable to store config file at location".to_string();
+                    log_error!(err_msg.clone(), backend_communicator);
+                    Err(err_msg)
+                }
+            }
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Store logging settings, they apply to the next log entry.
+pub fn set_logging_config(
+    logging_config: LoggingConfig,
+    backend_communicator: &BackendCommunicator,
+) -> Result<(), std::string::String> {
+    let config_path = get_config_path_as_str(backend_communicator);
+
+    match get_config(backend_communicator) {
+        Ok(ok_config) => {
+            let mut changed_config = ok_config;
+            changed_config.logging = logging_config;
+
+            match confy::store_path(config_path, changed_config.clone()) {
+                Ok(_) => {
+                    reload_logging_config();
+                    log_info!(
+                        format!(
+                            "Set log level in config to {}.",
+                            changed_config.logging.level
+                        ),
+                        backend_communicator,
+                    );
+                    Ok(())
+                }
+                Err(_) => {
+                    let err_msg = "Unable to store config file at location".to_string();
+                    log_error!(err_msg.clone(), backend_communicator);
                     Err(err_msg)
                 }
             }
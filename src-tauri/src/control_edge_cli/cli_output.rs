@@ -0,0 +1,282 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::cli_versions::parse_cli_version;
+
+/// Known failures of Edge CLI commands, serialized as `{ "kind": ..., "message": ... }`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+pub enum EdgeCliError {
+    NotInstalled(String),         // No Edge CLI binary installed via the GUI.
+    RequirementsNotMet(String),   // A system requirement check failed.
+    DockerUnavailable(String),    // Docker is not installed, not running or not accessible.
+    StakeNotFound(String),        // The stake does not exist or is not assigned to this device.
+    DeviceNotRunning(String),     // The device container is not running.
+    DeviceAlreadyRunning(String), // The device container is already running.
+    CommandFailed(String),        // The CLI ran, but reported an unrecognized error.
+    ProcessFailed(String),        // The CLI could not be run at all.
+}
+
+impl fmt::Display for EdgeCliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            EdgeCliError::NotInstalled(message)
+            | EdgeCliError::RequirementsNotMet(message)
+            | EdgeCliError::DockerUnavailable(message)
+            | EdgeCliError::StakeNotFound(message)
+            | EdgeCliError::DeviceNotRunning(message)
+            | EdgeCliError::DeviceAlreadyRunning(message)
+            | EdgeCliError::CommandFailed(message)
+            | EdgeCliError::ProcessFailed(message) => message,
+        };
+        write!(f, "{}", message)
+    }
+}
+
+/// Map the stderr of a failed CLI command to a known error.
+pub fn parse_cli_error(stderr: &str, exit_code: i32) -> EdgeCliError {
+    let stderr_lowercase = stderr.to_lowercase();
+    let message = stderr.trim().to_string();
+
+    if stderr_lowercase.contains("docker") {
+        EdgeCliError::DockerUnavailable(message)
+    } else if stderr_lowercase.contains("stake")
+        && (stderr_lowercase.contains("not found")
+            || stderr_lowercase.contains("invalid")
+            || stderr_lowercase.contains("not assigned"))
+    {
+        EdgeCliError::StakeNotFound(message)
+    } else if stderr_lowercase.contains("not running")
+        || stderr_lowercase.contains("no such container")
+    {
+        EdgeCliError::DeviceNotRunning(message)
+    } else if stderr_lowercase.contains("already running") {
+        EdgeCliError::DeviceAlreadyRunning(message)
+    } else if exit_code == 1 {
+        EdgeCliError::CommandFailed(format!("CLI installed but ran with error: {}", message))
+    } else {
+        EdgeCliError::CommandFailed(format!(
+            "Edge exit code {} not recognized. Stderr: {}",
+            exit_code, message
+        ))
+    }
+}
+
+/// Output of `device info`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceInfo {
+    pub address: Option<String>,          // Device XE address
+    pub network: Option<String>,          // eg. mainnet
+    pub stake: Option<String>,            // Stake ID the device is assigned to
+    pub container_id: Option<String>,     // Docker container ID, if running
+    pub fields: BTreeMap<String, String>, // All `key: value` lines, keys in snake_case
+    pub raw: String,
+}
+
+/// Output of `device status`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceStatus {
+    pub running: bool,
+    pub status: String, // eg. running
+    pub container_id: Option<String>,
+    pub fields: BTreeMap<String, String>,
+    pub raw: String,
+}
+
+/// Output of `device start`, `device stop` and `device restart`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceCommandResult {
+    pub command: String, // eg. start
+    pub container_id: Option<String>,
+    pub raw: String,
+}
+
+/// Output of `--version`.
+#[derive(Debug, Clone, Serialize)]
+pub struct EdgeCliVersion {
+    pub version: Option<String>, // eg. 1.2.3
+    pub raw: String,
+}
+
+/// Collect `key: value` lines, eg. `Device Address: xe_123` becomes `device_address = xe_123`.
+fn parse_fields(stdout: &str) -> BTreeMap<String, String> {
+    let mut fields = BTreeMap::new();
+    for line in stdout.lines() {
+        if let Some((key, value)) = line.split_once(':') {
+            let key = key
+                .trim()
+                .to_lowercase()
+                .split_whitespace()
+                .collect::<Vec<&str>>()
+                .join("_");
+            let value = value.trim();
+            if !key.is_empty() && !value.is_empty() {
+                fields.insert(key, value.to_string());
+            }
+        }
+    }
+    fields
+}
+
+/// Returns the value of the first key which exists.
+fn find_field(fields: &BTreeMap<String, String>, keys: &[&str]) -> Option<String> {
+    keys.iter().find_map(|key| fields.get(*key).cloned())
+}
+
+/// Returns the first XE address in the output.
+fn find_xe_address(stdout: &str) -> Option<String> {
+    stdout
+        .split(|c: char| c.is_whitespace() || c == '"' || c == ',')
+        .find(|token| token.starts_with("xe_") && token.len() == 43)
+        .map(|token| token.to_string())
+}
+
+/// Returns the first token which looks like a Docker container ID (12 or 64 hex characters).
+fn find_container_id(stdout: &str) -> Option<String> {
+    stdout
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .find(|token| {
+            (token.len() == 12 || token.len() == 64) && token.chars().all(|c| c.is_ascii_hexdigit())
+        })
+        .map(|token| token.to_string())
+}
+
+pub fn parse_device_info(stdout: &str) -> DeviceInfo {
+    let fields = parse_fields(stdout);
+    DeviceInfo {
+        address: find_field(&fields, &["address", "device_address", "device"])
+            .filter(|address| address.starts_with("xe_"))
+            .or_else(|| find_xe_address(stdout)),
+        network: find_field(&fields, &["network"]),
+        stake: find_field(&fields, &["stake", "stake_id", "stake_hash"]),
+        container_id: find_field(&fields, &["container", "container_id"])
+            .or_else(|| find_container_id(stdout)),
+        fields,
+        raw: stdout.to_string(),
+    }
+}
+
+pub fn parse_device_status(stdout: &str) -> DeviceStatus {
+    let fields = parse_fields(stdout);
+    let status = find_field(&fields, &["status", "state", "device_status"])
+        .unwrap_or_else(|| stdout.trim().to_string());
+    let status_lowercase = status.to_lowercase();
+    let running = (status_lowercase.contains("running")
+        && !status_lowercase.contains("not running"))
+        || status_lowercase == "up"
+        || status_lowercase.starts_with("up "); // Docker style, eg. Up 3 hours
+
+    DeviceStatus {
+        running,
+        status,
+        container_id: find_field(&fields, &["container", "container_id"])
+            .or_else(|| find_container_id(stdout)),
+        fields,
+        raw: stdout.to_string(),
+    }
+}
+
+pub fn parse_device_command_result(command: &str, stdout: &str) -> DeviceCommandResult {
+    DeviceCommandResult {
+        command: command.to_string(),
+        container_id: find_container_id(stdout),
+        raw: stdout.to_string(),
+    }
+}
+
+pub fn parse_edge_cli_version(stdout: &str) -> EdgeCliVersion {
+    EdgeCliVersion {
+        version: parse_cli_version(stdout),
+        raw: stdout.trim().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEVICE_ADDRESS: &str = "xe_3F129e5Be5f0E3d3B53F7a1b2c3d4e5f6A7b8C9d";
+
+    #[test]
+    fn parses_the_edge_cli_version() {
+        assert_eq!(
+            parse_edge_cli_version("edge v1.2.3\n").version.as_deref(),
+            Some("1.2.3")
+        );
+        assert_eq!(
+            parse_edge_cli_version("Edge CLI 2.0.0-rc.1 (linux/arm64)")
+                .version
+                .as_deref(),
+            Some("2.0.0-rc.1")
+        );
+        assert_eq!(
+            parse_cli_version("edge version 1.10.0, built 2024-01-05").as_deref(),
+            Some("1.10.0")
+        );
+        assert_eq!(parse_cli_version("edge development build"), None);
+        assert_eq!(parse_cli_version(""), None);
+        assert_eq!(parse_edge_cli_version("  edge v1.2.3  ").raw, "edge v1.2.3");
+    }
+
+    #[test]
+    fn maps_stderr_to_known_errors() {
+        assert!(matches!(
+            parse_cli_error("Cannot connect to the Docker daemon", 1),
+            EdgeCliError::DockerUnavailable(_)
+        ));
+        assert!(matches!(
+            parse_cli_error("Error: stake not found", 1),
+            EdgeCliError::StakeNotFound(_)
+        ));
+        assert!(matches!(
+            parse_cli_error("Error: No such container: edge-device", 1),
+            EdgeCliError::DeviceNotRunning(_)
+        ));
+        assert!(matches!(
+            parse_cli_error("device is already running", 1),
+            EdgeCliError::DeviceAlreadyRunning(_)
+        ));
+        assert_eq!(
+            parse_cli_error(" unexpected ", 1).to_string(),
+            "CLI installed but ran with error: unexpected"
+        );
+        assert_eq!(
+            parse_cli_error("unexpected", 2).to_string(),
+            "Edge exit code 2 not recognized. Stderr: unexpected"
+        );
+    }
+
+    #[test]
+    fn parses_device_info() {
+        let stdout = format!(
+            "Device Address: {}\nNetwork: mainnet\nStake ID: 0123abcd\nContainer: 3f4e5d6c7b8a\n",
+            DEVICE_ADDRESS
+        );
+        let device_info = parse_device_info(&stdout);
+        assert_eq!(device_info.address.as_deref(), Some(DEVICE_ADDRESS));
+        assert_eq!(device_info.network.as_deref(), Some("mainnet"));
+        assert_eq!(device_info.stake.as_deref(), Some("0123abcd"));
+        assert_eq!(device_info.container_id.as_deref(), Some("3f4e5d6c7b8a"));
+        assert_eq!(
+            device_info.fields.get("device_address").map(String::as_str),
+            Some(DEVICE_ADDRESS)
+        );
+
+        // Without labels the address is found in the output.
+        let device_info = parse_device_info(&format!("{{\"address\": \"{}\"}}", DEVICE_ADDRESS));
+        assert_eq!(device_info.address.as_deref(), Some(DEVICE_ADDRESS));
+    }
+
+    #[test]
+    fn parses_device_status() {
+        let device_status = parse_device_status("Status: running\nContainer: 3f4e5d6c7b8a");
+        assert!(device_status.running);
+        assert_eq!(device_status.status, "running");
+        assert_eq!(device_status.container_id.as_deref(), Some("3f4e5d6c7b8a"));
+
+        assert!(parse_device_status("Up 3 hours").running);
+        assert!(!parse_device_status("Status: not running").running);
+        assert!(!parse_device_status("exited").running);
+    }
+}
@@ -1,11 +1,27 @@
 use crate::check_requirements;
+use crate::check_requirements::RequirementChecks;
 use crate::config::getters::{get_edge_binary_name, get_stake_id};
-use crate::utility::log_and_emit;
+use crate::docker::container_runtime::get_edge_cli_envs;
+use crate::docker::{get_edge_device_container_image, pull_docker_image};
+use crate::logging::{log_debug, log_error, log_info};
+use crate::node_status;
+use crate::process_runner::{run_process, run_process_with_env};
+use crate::tray;
 use crate::BackendCommunicator;
-use std::process::Command;
+use serde::Serialize;
+use std::path::Path;
+use std::time::Duration;
 
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
+use self::cli_output::{
+    parse_cli_error, parse_device_command_result, parse_device_info, parse_device_status,
+    DeviceCommandResult, DeviceInfo, DeviceStatus, EdgeCliError,
+};
+
+pub mod cli_output;
+
+/// `device start` may pull the device image first, which can take a while on slow connections.
+const EDGE_CLI_COMMAND_TIMEOUT: Duration = Duration::from_secs(900);
+const EDGE_CLI_VERSION_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// Run a command in the Edge Cli
 /// You can choose whether the edge binary must be the latest version to run the command.
@@ -13,45 +29,54 @@ async fn command_edge_cli(
     cli_command: String,
     checklatestbinary: bool,
     backend_communicator: &BackendCommunicator,
-) -> Result<String, String> {
+) -> Result<String, EdgeCliError> {
     let arglist: Vec<&str> = cli_command.split(' ').collect();
     let output;
 
     // Requirements must first be met before commands can be run.
+    let starts_device = matches!(arglist.as_slice(), ["device", "start" | "restart", ..]);
+    let requirement_checks = RequirementChecks::before_edge_cli_command(
+        checklatestbinary,
+        starts_device,
+        backend_communicator,
+    );
     let check_requirements_future =
-        check_requirements::main(true, true, true, checklatestbinary, backend_communicator).await;
+        check_requirements::main(&requirement_checks, backend_communicator).await;
     match check_requirements_future {
         Ok(_) => {}
         Err(err) => {
             let error_message = format!("You need to pass all system checks before running an Edge CLI command. Your system check results: {}", err);
-            log_and_emit(error_message.clone(), backend_communicator);
-            return Err(error_message);
+            log_error!(error_message.clone(), backend_communicator);
+            return Err(EdgeCliError::RequirementsNotMet(error_message));
         }
     }
 
     let bin_name = get_edge_binary_name(backend_communicator);
     let bin_path = format!("{}{}", backend_communicator.data_dir.clone(), bin_name);
+    if !Path::new(&bin_path).exists() {
+        return Err(EdgeCliError::NotInstalled(String::from(
+            "Edge CLI not yet installed via GUI.",
+        )));
+    }
 
-    log_and_emit(
+    log_debug!(
         format!("Invoking command in Edge CLI = {}", cli_command),
         backend_communicator,
     );
-    #[cfg(target_os = "windows")]
-    const WINDOWS_CREATE_NO_WINDOW: u32 = 0x08000000;
-
-    #[cfg(target_os = "windows")]
-    let command = Command::new(bin_path)
-        .args(arglist)
-        .creation_flags(WINDOWS_CREATE_NO_WINDOW)
-        .output();
-
-    #[cfg(not(target_os = "windows"))]
-    let command = Command::new(bin_path).args(arglist).output();
+    // The Edge CLI reaches the container runtime through the Docker API, eg. the Podman socket.
+    let command = run_process_with_env(
+        &bin_path,
+        &arglist,
+        &get_edge_cli_envs(backend_communicator),
+        EDGE_CLI_COMMAND_TIMEOUT,
+        backend_communicator,
+    )
+    .await;
 
     match command {
         Ok(command_completed_result) => {
             output = command_completed_result;
-            log_and_emit(
+            log_info!(
                 format!(
                     "Edge CLI command `{}` completed.",
                     cli_command.clone(),
@@ -66,65 +91,253 @@ async fn command_edge_cli(
                 cli_command.clone(),
                 command_not_completed
             );
-            log_and_emit(error_message.clone(), backend_communicator);
-            return Err(error_message);
+            log_error!(error_message.clone(), backend_communicator);
+            return Err(EdgeCliError::ProcessFailed(error_message));
         }
     }
 
-    // Convert stdout strings
-
-    let stdout_output_str: String;
-    match String::from_utf8(output.stdout.to_vec()) {
-        Ok(ok_converted_str) => stdout_output_str = ok_converted_str,
-        Err(_) => {
-            let err_message = "Unable to convert stdout.".to_string();
-            log_and_emit(err_message.clone(), backend_communicator);
-            return Err(err_message);
-        }
-    };
-
-    let stderr_output_str: String;
-    match String::from_utf8(output.stderr.to_vec()) {
-        Ok(ok_converted_str) => stderr_output_str = ok_converted_str,
-        Err(_) => {
-            stderr_output_str = "Unable to convert stderr.".to_string();
-            log_and_emit(stderr_output_str.clone(), backend_communicator);
-            return Err(stderr_output_str);
-        }
-    };
+    let stdout_output_str: String = output.stdout;
+    let stderr_output_str: String = output.stderr;
 
     let exit_code: i32;
     let cli_found_successful_command = 0;
-    let cli_found_failed_command = 1;
-    match output.status.code() {
+    match output.exit_code {
         Some(code) => {
             exit_code = code;
-            log_and_emit(
+            log_debug!(
                 format!("Edge CLI exit code = {}", exit_code),
                 backend_communicator,
             );
 
             if exit_code == cli_found_successful_command {
                 let log_message = format!("Stdout: {}", stdout_output_str);
-                log_and_emit(log_message, backend_communicator);
+                log_info!(log_message, backend_communicator);
                 Ok(stdout_output_str)
-            } else if exit_code == cli_found_failed_command {
-                let err_message =
-                    format!("CLI installed but ran with error: {}", stderr_output_str);
-                log_and_emit(err_message.clone(), backend_communicator);
-                return Err(err_message);
             } else {
-                let err_message = format!("Edge exit code {} not recognized.", exit_code);
-                log_and_emit(err_message.clone(), backend_communicator);
-                return Err(err_message);
+                let edge_cli_error = parse_cli_error(&stderr_output_str, exit_code);
+                log_error!(edge_cli_error.to_string(), backend_communicator);
+                Err(edge_cli_error)
             }
         }
         None => {
             let err_message = "Edge CLI running status could not be checked.".to_string();
-            log_and_emit(err_message.clone(), backend_communicator);
-            Err(err_message)
+            log_error!(err_message.clone(), backend_communicator);
+            Err(EdgeCliError::ProcessFailed(err_message))
+        }
+    }
+}
+
+/// Returns the info of the Edge device, eg. address & network.
+pub async fn get_device_info(
+    backend_communicator: &BackendCommunicator,
+) -> Result<DeviceInfo, EdgeCliError> {
+    let stdout_str =
+        command_edge_cli("device info".to_string(), false, backend_communicator).await?;
+    Ok(parse_device_info(&stdout_str))
+}
+
+/// Returns whether the Edge device is running.
+pub async fn get_device_status(
+    backend_communicator: &BackendCommunicator,
+) -> Result<DeviceStatus, EdgeCliError> {
+    let stdout_str =
+        command_edge_cli("device status".to_string(), false, backend_communicator).await?;
+    Ok(parse_device_status(&stdout_str))
+}
+
+/// Run `device start`, `device stop` or `device restart` for the configured stake.
+pub async fn run_device_command(
+    command: &str,
+    checklatestbinary: bool,
+    backend_communicator: &BackendCommunicator,
+) -> Result<DeviceCommandResult, EdgeCliError> {
+    if !["start", "stop", "restart"].contains(&command) {
+        return Err(EdgeCliError::CommandFailed(format!(
+            "Unknown device command {}.",
+            command
+        )));
+    }
+    let stake_id: String = get_stake_id(backend_communicator);
+    let cli_command = format!("device {} --stake={}", command, stake_id);
+    let command_result =
+        command_edge_cli(cli_command, checklatestbinary, backend_communicator).await;
+    tray::request_tray_refresh();
+    let stdout_str = command_result?;
+    if command != "stop" {
+        node_status::record_device_start();
+    }
+    Ok(parse_device_command_result(command, &stdout_str))
+}
+
+/// A single step of a device operation such as restart.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceOperationStep {
+    pub name: String, // eg. stop
+    pub success: bool,
+    pub message: String,
+}
+
+/// Combined result of a multi-step device operation such as restart or update.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceOperationResult {
+    pub operation: String, // restart, update or network switch
+    pub success: bool,     // true if all steps succeeded
+    pub steps: Vec<DeviceOperationStep>,
+}
+
+impl DeviceOperationResult {
+    pub(crate) fn new(operation: &str) -> Self {
+        DeviceOperationResult {
+            operation: operation.to_string(),
+            success: true,
+            steps: vec![],
         }
     }
+
+    /// Record a step, returns whether it succeeded.
+    pub(crate) fn record_step(
+        &mut self,
+        name: &str,
+        step_result: Result<String, String>,
+        backend_communicator: &BackendCommunicator,
+    ) -> bool {
+        let (success, message) = match step_result {
+            Ok(ok_message) => (true, ok_message),
+            Err(err_message) => (false, err_message),
+        };
+        if success {
+            log_info!(
+                format!("Device {} step {}: done.", self.operation, name),
+                backend_communicator,
+            );
+        } else {
+            log_error!(
+                format!(
+                    "Device {} step {}: failed. {}",
+                    self.operation, name, message
+                ),
+                backend_communicator,
+            );
+        }
+        self.success &= success;
+        self.steps.push(DeviceOperationStep {
+            name: name.to_string(),
+            success,
+            message,
+        });
+        success
+    }
+
+    /// Log and emit the combined result.
+    pub(crate) fn finish(self, backend_communicator: &BackendCommunicator) -> Self {
+        if self.success {
+            log_info!(
+                format!("Device {} completed successfully.", self.operation),
+                backend_communicator,
+            );
+        } else {
+            log_error!(
+                format!(
+                    "Device {} failed. See the log for the failed step.",
+                    self.operation
+                ),
+                backend_communicator,
+            );
+        }
+        self
+    }
+}
+
+/// Stop the device, a device which is not running counts as stopped.
+pub(crate) async fn stop_step(
+    checklatestbinary: bool,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    match run_device_command("stop", checklatestbinary, backend_communicator).await {
+        Ok(_) => Ok("Device stopped.".to_string()),
+        Err(EdgeCliError::DeviceNotRunning(_)) => Ok("Device was not running.".to_string()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+pub(crate) async fn start_step(
+    checklatestbinary: bool,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    match run_device_command("start", checklatestbinary, backend_communicator).await {
+        Ok(_) => Ok("Device started.".to_string()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Restart the Edge device by stopping and starting it.
+/// Stopping removes the device container, so starting creates a new one.
+pub async fn device_restart(
+    checklatestbinary: bool,
+    backend_communicator: &BackendCommunicator,
+) -> DeviceOperationResult {
+    let mut device_operation_result = DeviceOperationResult::new("restart");
+
+    log_info!(
+        "Device restart step 1/2: Stopping device.".to_string(),
+        backend_communicator,
+    );
+    let stop_result = stop_step(checklatestbinary, backend_communicator).await;
+    if !device_operation_result.record_step("stop", stop_result, backend_communicator) {
+        return device_operation_result.finish(backend_communicator);
+    }
+
+    log_info!(
+        "Device restart step 2/2: Starting device.".to_string(),
+        backend_communicator,
+    );
+    let start_result = start_step(checklatestbinary, backend_communicator).await;
+    device_operation_result.record_step("start", start_result, backend_communicator);
+
+    device_operation_result.finish(backend_communicator)
+}
+
+/// Pull the latest device image and restart the device on it.
+/// The device keeps running on the current image if the pull fails.
+pub async fn device_update(
+    checklatestbinary: bool,
+    backend_communicator: &BackendCommunicator,
+) -> DeviceOperationResult {
+    let mut device_operation_result = DeviceOperationResult::new("update");
+
+    log_info!(
+        "Device update step 1/3: Pulling latest device image.".to_string(),
+        backend_communicator,
+    );
+    let pull_result = match get_edge_device_container_image(backend_communicator).await {
+        Ok(Some(image)) => pull_docker_image(&image, backend_communicator).await,
+        Ok(None) => Ok(
+            "No device container found, starting the device downloads the latest image."
+                .to_string(),
+        ),
+        Err(err) => Err(err),
+    };
+    if !device_operation_result.record_step("pull", pull_result, backend_communicator) {
+        return device_operation_result.finish(backend_communicator);
+    }
+
+    log_info!(
+        "Device update step 2/3: Stopping device.".to_string(),
+        backend_communicator,
+    );
+    let stop_result = stop_step(checklatestbinary, backend_communicator).await;
+    if !device_operation_result.record_step("stop", stop_result, backend_communicator) {
+        return device_operation_result.finish(backend_communicator);
+    }
+
+    log_info!(
+        "Device update step 3/3: Starting device on the latest image.".to_string(),
+        backend_communicator,
+    );
+    let start_result = start_step(checklatestbinary, backend_communicator).await;
+    device_operation_result.record_step("start", start_result, backend_communicator);
+
+    device_operation_result.finish(backend_communicator)
 }
 
 /// Stop Edge device
@@ -132,14 +345,12 @@ pub async fn device_stop_from_frontend(
     checklatestbinary: bool,
     backend_communicator: &BackendCommunicator,
 ) -> bool {
-    let stake_id: String = get_stake_id(backend_communicator);
-    let cli_command = format!("device stop --stake={}", stake_id);
-    let command_edge_cli_future =
-        command_edge_cli(cli_command, checklatestbinary, backend_communicator).await;
-    match command_edge_cli_future {
+    let run_device_command_future =
+        run_device_command("stop", checklatestbinary, backend_communicator).await;
+    match run_device_command_future {
         Ok(_) => {
             let ok_message = "Device stopped successfully.".to_string();
-            log_and_emit(ok_message, backend_communicator);
+            log_info!(ok_message, backend_communicator);
             true
         }
         Err(_) => false,
@@ -151,16 +362,56 @@ pub async fn device_start_from_frontend(
     checklatestbinary: bool,
     backend_communicator: &BackendCommunicator,
 ) -> bool {
-    let stake_id: String = get_stake_id(backend_communicator);
-    let cli_command = format!("device start --stake={}", stake_id);
-    let command_edge_cli_future =
-        command_edge_cli(cli_command, checklatestbinary, backend_communicator).await;
-    match command_edge_cli_future {
-        Ok(stdout_str) => {
-            let ok_message = format!("Device successfully started! Ok msg: {}", stdout_str);
-            log_and_emit(ok_message, backend_communicator);
+    let run_device_command_future =
+        run_device_command("start", checklatestbinary, backend_communicator).await;
+    match run_device_command_future {
+        Ok(device_command_result) => {
+            let ok_message = format!(
+                "Device successfully started! Ok msg: {}",
+                device_command_result.raw
+            );
+            log_info!(ok_message, backend_communicator);
             true
         }
         Err(_) => false,
     }
 }
+
+/// Returns the version reported by the installed Edge CLI, eg. 1.2.3.
+/// Unlike other CLI commands, this does not run the requirement checks first.
+pub async fn get_edge_cli_version(
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, EdgeCliError> {
+    let bin_name = get_edge_binary_name(backend_communicator);
+    let bin_path = format!("{}{}", backend_communicator.data_dir.clone(), bin_name);
+    if !Path::new(&bin_path).exists() {
+        return Err(EdgeCliError::NotInstalled(String::from(
+            "Edge CLI not yet installed via GUI.",
+        )));
+    }
+
+    let command = run_process(
+        &bin_path,
+        &["--version"],
+        EDGE_CLI_VERSION_TIMEOUT,
+        backend_communicator,
+    )
+    .await;
+
+    match command {
+        Ok(output) if output.exit_code == Some(0) => Ok(output.stdout.trim().to_string()),
+        Ok(output) => {
+            let err_message = format!(
+                "Edge CLI version could not be read. Exit code: {:?}",
+                output.exit_code
+            );
+            log_error!(err_message.clone(), backend_communicator);
+            Err(EdgeCliError::CommandFailed(err_message))
+        }
+        Err(err) => {
+            let err_message = format!("Unable to run Edge CLI version command. Error: {}", err);
+            log_error!(err_message.clone(), backend_communicator);
+            Err(EdgeCliError::ProcessFailed(err_message))
+        }
+    }
+}
@@ -7,13 +7,15 @@ use std::collections::HashMap;
 use std::fs::{self, OpenOptions};
 use std::io::Write;
 
+use crate::check_requirements::check_edge::get_edge_staking_url;
+use crate::check_requirements::RequirementChecks;
 use crate::config::getters::get_network;
 use crate::config::setters::set_device_data;
 use crate::docker::{
     copy_data_to_running_container, remove_temporary_container,
     start_docker_container_for_copying_data,
 };
-use crate::utility::log_and_emit;
+use crate::logging::{log_debug, log_info};
 use crate::{check_requirements, BackendCommunicator};
 
 /// Logs and emits device code progress.
@@ -27,7 +29,7 @@ fn helper_create_device_code_setup_stage_log_and_emit(
         "Creating device token. Phase {} / {}",
         *account_phase_current, account_phases_total
     );
-    log_and_emit(ok_device_token_step, backend_communicator);
+    log_info!(ok_device_token_step, backend_communicator);
 }
 
 /// Flow for creating a device token. If successful displays the next step.
@@ -39,7 +41,7 @@ pub async fn create_device_code(
     backend_communicator: &BackendCommunicator,
 ) -> Result<String, String> {
     // Prereq: Check requirements to see if Docker is running
-    match check_requirements::main(false, false, true, false, backend_communicator).await {
+    match check_requirements::main(&RequirementChecks::docker_only(), backend_communicator).await {
         Ok(_) => {}
         Err(err_str) => return Err(err_str),
     }
@@ -52,9 +54,12 @@ pub async fn create_device_code(
     );
 
     let network = get_network(backend_communicator);
-    println!(
-        "add_device_from_frontend function called. address {}, private_key {}, public_key {}",
-        address, private_key, public_key
+    log_debug!(
+        format!(
+            "Creating device token for address {}, public key {}",
+            address, public_key
+        ),
+        backend_communicator,
     );
 
     // TODO: Check if input is correct.
@@ -83,7 +88,7 @@ pub async fn create_device_code(
     );
 
     let fields_hashmap: HashMap<String, String> = HashMap::from([
-        ("network".to_string(), network),
+        ("network".to_string(), network.to_string()),
         ("address".to_string(), address.clone()),
         ("privateKey".to_string(), private_key.clone()),
         ("publicKey".to_string(), public_key),
@@ -104,16 +109,16 @@ pub async fn create_device_code(
     );
 
     // Copy files to Docker
-    match start_docker_container_for_copying_data(backend_communicator) {
+    match start_docker_container_for_copying_data(backend_communicator).await {
         Ok(_) => {}
         Err(err_str) => return Err(err_str),
     }
 
     for filepath in filepaths {
-        match copy_data_to_running_container(filepath.clone(), backend_communicator) {
+        match copy_data_to_running_container(filepath.clone(), backend_communicator).await {
             Ok(_) => {
                 let ok_message = format!("Copied file {} to edge-device-data", filepath.clone());
-                log_and_emit(ok_message.clone(), backend_communicator);
+                log_info!(ok_message.clone(), backend_communicator);
             }
             Err(err_str) => return Err(err_str),
         }
@@ -125,7 +130,7 @@ pub async fn create_device_code(
         backend_communicator,
     );
 
-    match remove_temporary_container(backend_communicator) {
+    match remove_temporary_container(backend_communicator).await {
         Ok(_) => {}
         Err(err_str) => {
             return Err(err_str);
@@ -166,35 +171,7 @@ pub async fn create_device_code(
     let url_safe_device_code =
         general_purpose::URL_SAFE_NO_PAD.encode(device_json_string.as_bytes());
 
-    let network = get_network(backend_communicator);
-    let mainnet = String::from("mainnet");
-    let mainnet_wallet_url = String::from("https://wallet.xe.network/staking");
-    let testnet = String::from("testnet");
-    let testnet_wallet_url = String::from("https://wallet.test.network/staking");
-
-    let wallet_url;
-    if network == mainnet {
-        wallet_url = mainnet_wallet_url;
-        let ok_message = format!(
-            "Derived staking wallet url {} based on network in config: {}",
-            wallet_url, network
-        );
-        log_and_emit(ok_message, backend_communicator);
-    } else if network == testnet {
-        wallet_url = testnet_wallet_url;
-        let ok_message = format!(
-            "Derived staking wallet url {} based on network in config: {}",
-            wallet_url, network
-        );
-        log_and_emit(ok_message, backend_communicator);
-    } else {
-        let err_message = format!(
-            "Could not derive wallet url based on network in config: {}",
-            network
-        );
-        log_and_emit(err_message.clone(), backend_communicator);
-        return Err(err_message);
-    }
+    let wallet_url = get_edge_staking_url(backend_communicator)?;
 
     // Phase 7: Show next step
     helper_create_device_code_setup_stage_log_and_emit(
@@ -207,7 +184,7 @@ pub async fn create_device_code(
         wallet_url, url_safe_device_code
     );
     let ok_msg = String::from("Your device token was created.");
-    log_and_emit(ok_msg, backend_communicator);
+    log_info!(ok_msg, backend_communicator);
     Ok(next_step)
 }
 
@@ -243,7 +220,7 @@ fn helper_delete_device_file(
     match fs::remove_file(file_path.clone()) {
         Ok(_) => {
             let ok_msg = format!("File {} was successfully cleaned up.", file_path);
-            log_and_emit(ok_msg.clone(), backend_communicator);
+            log_info!(ok_msg.clone(), backend_communicator);
             Ok(ok_msg)
         }
         Err(_) => Err(format!("Unable to delete file {}", file_path)),
@@ -0,0 +1,242 @@
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::config::getters::get_container_runtime_config;
+use crate::config::{ContainerRuntimeConfig, ContainerRuntimeKind};
+use crate::logging::{log_error, log_info};
+use crate::process_runner::{run_process, ProcessOutput};
+use crate::BackendCommunicator;
+
+const RUNTIME_DETECTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Command line interface of a container runtime. Podman accepts the Docker commands used here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ContainerRuntimeCli {
+    #[default]
+    Docker,
+    Podman,
+}
+
+impl fmt::Display for ContainerRuntimeCli {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContainerRuntimeCli::Docker => write!(f, "Docker"),
+            ContainerRuntimeCli::Podman => write!(f, "Podman"),
+        }
+    }
+}
+
+/// The container runtime used for all container operations.
+#[derive(Debug, Clone, Serialize)]
+pub struct ContainerRuntime {
+    pub cli: ContainerRuntimeCli,
+    pub executable: String,     // eg. docker, podman or /usr/local/bin/docker
+    pub socket: Option<String>, // Passed to the executable, None for the default of the runtime.
+    pub api_socket: Option<String>, // Docker API socket for the Edge CLI, eg. the Podman socket.
+}
+
+/// Detected runtime and the settings it was detected with, a changed config detects again.
+struct DetectedContainerRuntime {
+    container_runtime_config: ContainerRuntimeConfig,
+    container_runtime: ContainerRuntime,
+}
+
+static DETECTED_CONTAINER_RUNTIME: Mutex<Option<DetectedContainerRuntime>> = Mutex::new(None);
+
+fn detected_container_runtime() -> MutexGuard<'static, Option<DetectedContainerRuntime>> {
+    match DETECTED_CONTAINER_RUNTIME.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    if value.trim().is_empty() {
+        None
+    } else {
+        Some(value.trim().to_string())
+    }
+}
+
+/// Runtimes to try for the settings, in order.
+fn get_runtime_candidates(
+    container_runtime_config: &ContainerRuntimeConfig,
+) -> Vec<ContainerRuntime> {
+    let socket = non_empty(&container_runtime_config.socket);
+    let candidate = |cli: ContainerRuntimeCli, default_executable: &str| ContainerRuntime {
+        cli,
+        executable: non_empty(&container_runtime_config.executable)
+            .unwrap_or_else(|| default_executable.to_string()),
+        socket: socket.clone(),
+        api_socket: socket.clone(),
+    };
+
+    match container_runtime_config.runtime {
+        ContainerRuntimeKind::Auto if non_empty(&container_runtime_config.executable).is_none() => {
+            vec![
+                candidate(ContainerRuntimeCli::Docker, "docker"),
+                candidate(ContainerRuntimeCli::Podman, "podman"),
+            ]
+        }
+        ContainerRuntimeKind::Auto | ContainerRuntimeKind::Docker => {
+            vec![candidate(ContainerRuntimeCli::Docker, "docker")]
+        }
+        ContainerRuntimeKind::Podman => vec![candidate(ContainerRuntimeCli::Podman, "podman")],
+        ContainerRuntimeKind::Custom => {
+            let executable = non_empty(&container_runtime_config.executable)
+                .unwrap_or_else(|| "docker".to_string());
+            let cli = if executable.to_lowercase().contains("podman") {
+                ContainerRuntimeCli::Podman
+            } else {
+                ContainerRuntimeCli::Docker
+            };
+            vec![candidate(cli, &executable)]
+        }
+    }
+}
+
+/// The Podman socket, if the Podman service provides one on this host. The Edge CLI talks to it
+/// through the Docker API.
+async fn get_podman_api_socket(
+    executable: &str,
+    backend_communicator: &BackendCommunicator,
+) -> Option<String> {
+    let args = ["info", "--format", "{{.Host.RemoteSocket.Path}}"];
+    let output = run_process(
+        executable,
+        &args,
+        RUNTIME_DETECTION_TIMEOUT,
+        backend_communicator,
+    )
+    .await
+    .ok()?;
+    let socket_path = output.stdout.trim();
+    let socket_path = socket_path
+        .strip_prefix("unix://")
+        .or_else(|| socket_path.strip_prefix("unix:"))
+        .unwrap_or(socket_path);
+    if output.exit_code == Some(0) && !socket_path.is_empty() && Path::new(socket_path).exists() {
+        Some(format!("unix://{}", socket_path))
+    } else {
+        None
+    }
+}
+
+/// Find the configured container runtime. With `runtime = 'auto'`, Docker is preferred over
+/// Podman. A `docker` executable which is the Podman Docker emulation is used as Podman.
+pub(crate) async fn detect_container_runtime(
+    backend_communicator: &BackendCommunicator,
+) -> Result<ContainerRuntime, String> {
+    let container_runtime_config = get_container_runtime_config(backend_communicator);
+    if let Some(detected) = detected_container_runtime().as_ref() {
+        if detected.container_runtime_config == container_runtime_config {
+            return Ok(detected.container_runtime.clone());
+        }
+    }
+
+    let mut errors: Vec<String> = vec![];
+    for mut candidate in get_runtime_candidates(&container_runtime_config) {
+        let version_output = match run_process(
+            &candidate.executable,
+            &["--version"],
+            RUNTIME_DETECTION_TIMEOUT,
+            backend_communicator,
+        )
+        .await
+        {
+            Ok(output) if output.exit_code == Some(0) => output.stdout,
+            Ok(output) => {
+                errors.push(format!(
+                    "{} --version exited with code {:?}.",
+                    candidate.executable, output.exit_code
+                ));
+                continue;
+            }
+            Err(err) => {
+                errors.push(err);
+                continue;
+            }
+        };
+
+        if version_output.to_lowercase().contains("podman") {
+            candidate.cli = ContainerRuntimeCli::Podman;
+        }
+        if candidate.cli == ContainerRuntimeCli::Podman && candidate.api_socket.is_none() {
+            candidate.api_socket =
+                get_podman_api_socket(&candidate.executable, backend_communicator).await;
+        }
+
+        log_info!(
+            format!(
+                "Using container runtime {} ({}).",
+                candidate.cli,
+                version_output.trim()
+            ),
+            backend_communicator,
+        );
+        *detected_container_runtime() = Some(DetectedContainerRuntime {
+            container_runtime_config,
+            container_runtime: candidate.clone(),
+        });
+        return Ok(candidate);
+    }
+
+    let err_message = format!(
+        "No container runtime found. Is Docker installed & did you restart your computer? Docker installation link: https://www.docker.com/products/docker-desktop/ Error = {}",
+        errors.join(" ")
+    );
+    log_error!(err_message.clone(), backend_communicator);
+    Err(err_message)
+}
+
+/// The detected container runtime, or the configured one if it was not detected yet.
+pub(crate) fn get_container_runtime(
+    backend_communicator: &BackendCommunicator,
+) -> ContainerRuntime {
+    let container_runtime_config = get_container_runtime_config(backend_communicator);
+    if let Some(detected) = detected_container_runtime().as_ref() {
+        if detected.container_runtime_config == container_runtime_config {
+            return detected.container_runtime.clone();
+        }
+    }
+    get_runtime_candidates(&container_runtime_config).remove(0)
+}
+
+/// Run a command with the container runtime, eg. `["ps", "--all"]`.
+pub(crate) async fn run_container_command(
+    args: &[&str],
+    timeout: Duration,
+    backend_communicator: &BackendCommunicator,
+) -> Result<ProcessOutput, String> {
+    let container_runtime = get_container_runtime(backend_communicator);
+    let mut runtime_args: Vec<&str> = vec![];
+    if let Some(socket) = &container_runtime.socket {
+        match container_runtime.cli {
+            ContainerRuntimeCli::Docker => runtime_args.extend(["--host", socket]),
+            ContainerRuntimeCli::Podman => runtime_args.extend(["--url", socket]),
+        }
+    }
+    runtime_args.extend(args);
+
+    run_process(
+        &container_runtime.executable,
+        &runtime_args,
+        timeout,
+        backend_communicator,
+    )
+    .await
+}
+
+/// Environment for Edge CLI commands, which reach the container runtime through the Docker API.
+pub(crate) fn get_edge_cli_envs(
+    backend_communicator: &BackendCommunicator,
+) -> Vec<(String, String)> {
+    match get_container_runtime(backend_communicator).api_socket {
+        Some(api_socket) => vec![("DOCKER_HOST".to_string(), api_socket)],
+        None => vec![],
+    }
+}
@@ -0,0 +1,444 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use super::container_runtime::ContainerRuntimeCli;
+
+/// Severity of a Docker finding. Errors fail the Docker requirement check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingSeverity {
+    Error,
+    Warning,
+}
+
+/// A problem with the Docker installation and how to solve it.
+#[derive(Debug, Clone, Serialize)]
+pub struct DockerFinding {
+    pub severity: FindingSeverity,
+    pub message: String,
+}
+
+/// Parsed `docker info` or `podman info`.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct DockerInfo {
+    pub runtime: ContainerRuntimeCli,
+    pub client_version: Option<String>,   // eg. 24.0.7
+    pub server_version: Option<String>,   // Version of the Docker engine.
+    pub storage_driver: Option<String>,   // eg. overlay2
+    pub cgroup_version: Option<String>,   // 1 or 2
+    pub os_type: Option<String>,          // linux or windows, the containers the engine runs.
+    pub operating_system: Option<String>, // eg. Docker Desktop or Ubuntu 22.04.3 LTS
+    pub rootless: bool,
+    pub server_errors: Vec<String>, // Errors reported by docker info, eg. permission denied.
+    pub findings: Vec<DockerFinding>,
+}
+
+impl DockerInfo {
+    pub fn has_errors(&self) -> bool {
+        self.findings
+            .iter()
+            .any(|finding| finding.severity == FindingSeverity::Error)
+    }
+
+    /// Findings of a severity, joined into one message.
+    pub fn findings_message(&self, severity: FindingSeverity) -> String {
+        self.findings
+            .iter()
+            .filter(|finding| finding.severity == severity)
+            .map(|finding| finding.message.as_str())
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+
+    /// Result of the Docker requirement check: failed on errors, passed with the warnings.
+    pub fn to_check_result(&self) -> Result<String, String> {
+        if self.has_errors() {
+            return Err(self.findings_message(FindingSeverity::Error));
+        }
+        let mut ok_message = match &self.server_version {
+            Some(server_version) => {
+                format!("{} {} installed & ready.", self.runtime, server_version)
+            }
+            None => format!("{} installed & ready.", self.runtime),
+        };
+        let warnings = self.findings_message(FindingSeverity::Warning);
+        if !warnings.is_empty() {
+            ok_message = format!("{} {}", ok_message, warnings);
+        }
+        Ok(ok_message)
+    }
+}
+
+fn get_str(value: &Value, key: &str) -> Option<String> {
+    match value.get(key) {
+        Some(Value::String(string)) if !string.trim().is_empty() => Some(string.trim().to_string()),
+        _ => None,
+    }
+}
+
+/// Parse the output of `podman info --format "{{json .}}"`.
+fn parse_podman_info(podman_info: &Value) -> DockerInfo {
+    let host = podman_info.get("host").unwrap_or(&Value::Null);
+    DockerInfo {
+        runtime: ContainerRuntimeCli::Podman,
+        client_version: None,
+        server_version: podman_info
+            .get("version")
+            .and_then(|version| get_str(version, "Version")),
+        storage_driver: podman_info
+            .get("store")
+            .and_then(|store| get_str(store, "graphDriverName")),
+        // eg. v2
+        cgroup_version: get_str(host, "cgroupVersion")
+            .map(|cgroup_version| cgroup_version.trim_start_matches('v').to_string()),
+        os_type: get_str(host, "os"),
+        operating_system: host
+            .get("distribution")
+            .and_then(|distribution| get_str(distribution, "distribution")),
+        rootless: host
+            .get("security")
+            .and_then(|security| security.get("rootless"))
+            .and_then(|rootless| rootless.as_bool())
+            .unwrap_or(false),
+        server_errors: vec![],
+        findings: vec![],
+    }
+}
+
+/// Parse the output of `docker info --format "{{json .}}"`, or the same command of Podman.
+pub fn parse_docker_info(docker_info_json: &str) -> Result<DockerInfo, String> {
+    let docker_info: Value = serde_json::from_str(docker_info_json.trim())
+        .map_err(|err| format!("Could not parse docker info. Err: {}", err))?;
+    if docker_info.get("host").is_some() {
+        return Ok(parse_podman_info(&docker_info));
+    }
+
+    let security_options: Vec<String> = match docker_info.get("SecurityOptions") {
+        Some(Value::Array(security_options)) => security_options
+            .iter()
+            .filter_map(|security_option| security_option.as_str())
+            .map(|security_option| security_option.to_string())
+            .collect(),
+        _ => vec![],
+    };
+    let server_errors: Vec<String> = match docker_info.get("ServerErrors") {
+        Some(Value::Array(server_errors)) => server_errors
+            .iter()
+            .filter_map(|server_error| server_error.as_str())
+            .map(|server_error| server_error.to_string())
+            .collect(),
+        _ => vec![],
+    };
+
+    Ok(DockerInfo {
+        runtime: ContainerRuntimeCli::Docker,
+        // ClientInfo is included since Docker 23.
+        client_version: docker_info
+            .get("ClientInfo")
+            .and_then(|client_info| get_str(client_info, "Version")),
+        server_version: get_str(&docker_info, "ServerVersion"),
+        storage_driver: get_str(&docker_info, "Driver"),
+        cgroup_version: get_str(&docker_info, "CgroupVersion"),
+        os_type: get_str(&docker_info, "OSType"),
+        operating_system: get_str(&docker_info, "OperatingSystem"),
+        rootless: security_options
+            .iter()
+            .any(|security_option| security_option.contains("name=rootless")),
+        server_errors,
+        findings: vec![],
+    })
+}
+
+/// Numeric parts of a version, eg. 20.10.21+dfsg1 -> [20, 10, 21]. None if it has none.
+fn parse_version_numbers(version: &str) -> Option<Vec<u64>> {
+    let version_numbers: Vec<u64> = version
+        .trim()
+        .trim_start_matches('v')
+        .split('.')
+        .map_while(|part| {
+            let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
+            digits.parse::<u64>().ok()
+        })
+        .collect();
+    if version_numbers.is_empty() {
+        None
+    } else {
+        Some(version_numbers)
+    }
+}
+
+/// Whether `version` is older than `min_version`. None if either can not be parsed.
+fn is_older_version(version: &str, min_version: &str) -> Option<bool> {
+    let mut version_numbers = parse_version_numbers(version)?;
+    let mut min_version_numbers = parse_version_numbers(min_version)?;
+    let length = version_numbers.len().max(min_version_numbers.len());
+    version_numbers.resize(length, 0);
+    min_version_numbers.resize(length, 0);
+    Some(version_numbers < min_version_numbers)
+}
+
+/// Actionable findings for a parsed `docker info`. `min_version` is the minimum version of the runtime.
+pub fn get_docker_findings(docker_info: &DockerInfo, min_version: &str) -> Vec<DockerFinding> {
+    let mut findings: Vec<DockerFinding> = vec![];
+    let mut add_finding = |severity: FindingSeverity, message: String| {
+        findings.push(DockerFinding { severity, message })
+    };
+
+    for server_error in &docker_info.server_errors {
+        if server_error.to_lowercase().contains("permission denied") {
+            add_finding(
+                FindingSeverity::Error,
+                "Docker denied access. Add your user to the docker group (sudo usermod -aG docker $USER) and log in again.".to_string(),
+            );
+        } else {
+            add_finding(
+                FindingSeverity::Error,
+                format!("Docker reported an error: {}", server_error),
+            );
+        }
+    }
+
+    if docker_info.os_type.as_deref() == Some("windows") {
+        add_finding(
+            FindingSeverity::Error,
+            "Docker Desktop is in Windows-container mode. Right-click the Docker icon in the taskbar and choose \"Switch to Linux containers...\".".to_string(),
+        );
+    }
+
+    if let Some(server_version) = &docker_info.server_version {
+        match is_older_version(server_version, min_version) {
+            Some(true) => add_finding(
+                FindingSeverity::Error,
+                format!(
+                    "{runtime} {} is older than the minimum supported version {}. Update {runtime}.",
+                    server_version,
+                    min_version,
+                    runtime = docker_info.runtime
+                ),
+            ),
+            Some(false) => {}
+            None => add_finding(
+                FindingSeverity::Warning,
+                format!(
+                    "{} version {} could not be compared with the minimum supported version {}.",
+                    docker_info.runtime, server_version, min_version
+                ),
+            ),
+        }
+    }
+    if let Some(client_version) = &docker_info.client_version {
+        if is_older_version(client_version, min_version) == Some(true) {
+            add_finding(
+                FindingSeverity::Warning,
+                format!(
+                    "{runtime} client {} is older than the minimum supported version {}. Update {runtime}.",
+                    client_version,
+                    min_version,
+                    runtime = docker_info.runtime
+                ),
+            );
+        }
+    }
+
+    if docker_info.rootless {
+        add_finding(
+            FindingSeverity::Warning,
+            format!("{runtime} runs in rootless mode. Ports below 1024 can not be published and resource limits may not apply. Use the system-wide {runtime} service if the device does not start.", runtime = docker_info.runtime),
+        );
+    }
+
+    // Podman names its default driver overlay.
+    match docker_info
+        .storage_driver
+        .as_deref()
+        .filter(|_| docker_info.runtime == ContainerRuntimeCli::Docker)
+    {
+        Some("vfs") => add_finding(
+            FindingSeverity::Warning,
+            "Docker uses the vfs storage driver, which is slow and uses a lot of disk space. Configure the overlay2 storage driver.".to_string(),
+        ),
+        Some(storage_driver @ ("devicemapper" | "aufs" | "overlay")) => add_finding(
+            FindingSeverity::Warning,
+            format!(
+                "Docker uses the deprecated {} storage driver. Configure the overlay2 storage driver.",
+                storage_driver
+            ),
+        ),
+        _ => {}
+    }
+
+    if docker_info.cgroup_version.as_deref() == Some("1") {
+        add_finding(
+            FindingSeverity::Warning,
+            format!("{runtime} uses cgroup v1, which is deprecated. Switch the system to cgroup v2 if you update {runtime}.", runtime = docker_info.runtime),
+        );
+    }
+
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCKER_INFO: &str = r#"{
+        "ServerVersion": "24.0.7-ce",
+        "Driver": "overlay2",
+        "CgroupVersion": "2",
+        "OSType": "linux",
+        "OperatingSystem": "openSUSE Tumbleweed",
+        "SecurityOptions": ["name=seccomp,profile=builtin", "name=cgroupns"],
+        "ClientInfo": {"Version": "24.0.7-ce"},
+        "ServerErrors": null
+    }"#;
+
+    const DOCKER_DESKTOP_WINDOWS_INFO: &str = r#"{
+        "ServerVersion": "24.0.6",
+        "Driver": "windowsfilter",
+        "OSType": "windows",
+        "OperatingSystem": "Microsoft Windows Server Version 22H2",
+        "ClientInfo": {"Version": "24.0.6"}
+    }"#;
+
+    const PODMAN_INFO: &str = r#"{
+        "host": {
+            "cgroupVersion": "v2",
+            "os": "linux",
+            "distribution": {"distribution": "fedora", "version": "39"},
+            "security": {"rootless": true}
+        },
+        "store": {"graphDriverName": "overlay"},
+        "version": {"Version": "4.9.3-dev"}
+    }"#;
+
+    fn finding_messages(findings: &[DockerFinding], severity: FindingSeverity) -> Vec<&str> {
+        findings
+            .iter()
+            .filter(|finding| finding.severity == severity)
+            .map(|finding| finding.message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn parses_docker_info() {
+        let docker_info = parse_docker_info(DOCKER_INFO).unwrap();
+        assert_eq!(docker_info.runtime, ContainerRuntimeCli::Docker);
+        assert_eq!(docker_info.server_version.as_deref(), Some("24.0.7-ce"));
+        assert_eq!(docker_info.client_version.as_deref(), Some("24.0.7-ce"));
+        assert_eq!(docker_info.storage_driver.as_deref(), Some("overlay2"));
+        assert_eq!(docker_info.cgroup_version.as_deref(), Some("2"));
+        assert_eq!(docker_info.os_type.as_deref(), Some("linux"));
+        assert!(!docker_info.rootless);
+        assert!(docker_info.server_errors.is_empty());
+    }
+
+    #[test]
+    fn parses_podman_info() {
+        let podman_info = parse_docker_info(PODMAN_INFO).unwrap();
+        assert_eq!(podman_info.runtime, ContainerRuntimeCli::Podman);
+        assert_eq!(podman_info.server_version.as_deref(), Some("4.9.3-dev"));
+        assert_eq!(podman_info.client_version, None);
+        assert_eq!(podman_info.storage_driver.as_deref(), Some("overlay"));
+        assert_eq!(podman_info.cgroup_version.as_deref(), Some("2"));
+        assert_eq!(podman_info.operating_system.as_deref(), Some("fedora"));
+        assert!(podman_info.rootless);
+    }
+
+    #[test]
+    fn rejects_output_which_is_not_json() {
+        let err = parse_docker_info("Cannot connect to the Docker daemon").unwrap_err();
+        assert!(err.starts_with("Could not parse docker info."), "{}", err);
+    }
+
+    #[test]
+    fn compares_versions_with_suffixes() {
+        assert_eq!(is_older_version("24.0.7-ce", "20.10.0"), Some(false));
+        assert_eq!(is_older_version("20.10.21+dfsg1", "20.10.0"), Some(false));
+        assert_eq!(is_older_version("19.03.15", "20.10.0"), Some(true));
+        assert_eq!(is_older_version("4.9.3-dev", "4.0.0"), Some(false));
+        assert_eq!(is_older_version("3.4.4-dev", "4.0.0"), Some(true));
+        assert_eq!(is_older_version("v20.10", "20.10.0"), Some(false));
+        assert_eq!(is_older_version("25.0.0-beta.1", "25.0.0"), Some(false));
+        assert_eq!(is_older_version("dev", "20.10.0"), None);
+        assert_eq!(is_older_version("24.0.7", ""), None);
+    }
+
+    #[test]
+    fn current_docker_has_no_findings() {
+        let docker_info = parse_docker_info(DOCKER_INFO).unwrap();
+        let findings = get_docker_findings(&docker_info, "20.10.0");
+        assert!(findings.is_empty(), "{:?}", findings);
+        assert_eq!(
+            docker_info.to_check_result(),
+            Ok("Docker 24.0.7-ce installed & ready.".to_string())
+        );
+    }
+
+    #[test]
+    fn windows_containers_fail_the_check() {
+        let mut docker_info = parse_docker_info(DOCKER_DESKTOP_WINDOWS_INFO).unwrap();
+        docker_info.findings = get_docker_findings(&docker_info, "20.10.0");
+        let errors = finding_messages(&docker_info.findings, FindingSeverity::Error);
+        assert_eq!(errors.len(), 1);
+        assert!(
+            errors[0].contains("Switch to Linux containers"),
+            "{}",
+            errors[0]
+        );
+        assert!(docker_info.has_errors());
+        assert!(docker_info.to_check_result().is_err());
+    }
+
+    #[test]
+    fn old_docker_fails_and_unknown_version_warns() {
+        let mut docker_info = parse_docker_info(DOCKER_INFO).unwrap();
+        docker_info.server_version = Some("19.03.15".to_string());
+        docker_info.client_version = Some("19.03.15".to_string());
+        let findings = get_docker_findings(&docker_info, "20.10.0");
+        assert_eq!(
+            finding_messages(&findings, FindingSeverity::Error),
+            vec!["Docker 19.03.15 is older than the minimum supported version 20.10.0. Update Docker."]
+        );
+        assert_eq!(
+            finding_messages(&findings, FindingSeverity::Warning),
+            vec!["Docker client 19.03.15 is older than the minimum supported version 20.10.0. Update Docker."]
+        );
+
+        docker_info.server_version = Some("dev".to_string());
+        docker_info.client_version = None;
+        let findings = get_docker_findings(&docker_info, "20.10.0");
+        assert!(finding_messages(&findings, FindingSeverity::Error).is_empty());
+        assert_eq!(
+            finding_messages(&findings, FindingSeverity::Warning).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn permission_denied_tells_how_to_fix_it() {
+        let docker_info = parse_docker_info(
+            r#"{"ServerErrors": ["permission denied while trying to connect to the Docker daemon socket"]}"#,
+        )
+        .unwrap();
+        let errors = finding_messages(
+            &get_docker_findings(&docker_info, "20.10.0"),
+            FindingSeverity::Error,
+        )
+        .join(" ");
+        assert!(errors.contains("usermod -aG docker"), "{}", errors);
+    }
+
+    #[test]
+    fn podman_findings_skip_the_docker_storage_driver() {
+        let podman_info = parse_docker_info(PODMAN_INFO).unwrap();
+        let findings = get_docker_findings(&podman_info, "4.0.0");
+        assert!(finding_messages(&findings, FindingSeverity::Error).is_empty());
+        let warnings = finding_messages(&findings, FindingSeverity::Warning);
+        assert_eq!(warnings.len(), 1);
+        assert!(
+            warnings[0].starts_with("Podman runs in rootless mode."),
+            "{}",
+            warnings[0]
+        );
+    }
+}
@@ -1,228 +1,317 @@
-use crate::utility;
-use crate::utility::log_and_emit;
+use crate::config::getters::get_requirements_config;
+use crate::logging::{log_debug, log_error, log_info, log_warn};
+use crate::process_runner::ProcessOutput;
 use crate::BackendCommunicator;
-use std::process::{Command, Output};
+use std::time::Duration;
 
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
+use self::container_runtime::{
+    detect_container_runtime, get_container_runtime, run_container_command, ContainerRuntimeCli,
+};
+use self::docker_info::{get_docker_findings, parse_docker_info, DockerInfo, FindingSeverity};
 
-pub(crate) fn remove_temporary_container(
-    backend_communicator: &BackendCommunicator,
-) -> Result<String, String> {
-    let args = ["rm", "temp_container_for_copying_edge_device_data"];
+pub mod container_runtime;
+pub mod docker_info;
+
+/// Docker commands should finish quickly, a hanging Docker daemon must not block the program.
+const DOCKER_COMMAND_TIMEOUT: Duration = Duration::from_secs(120);
+/// Pulling an image can take a while on slow connections.
+const DOCKER_PULL_TIMEOUT: Duration = Duration::from_secs(1800);
 
-    #[cfg(target_os = "windows")]
-    const WINDOWS_CREATE_NO_WINDOW: u32 = 0x08000000;
+/// Image of the temporary containers for volume operations. Fully qualified, Podman does not
+/// resolve short image names without a registry configuration.
+const ALPINE_IMAGE: &str = "docker.io/library/alpine";
 
-    #[cfg(target_os = "windows")]
-    let command = Command::new("docker")
-        .args(args)
-        .creation_flags(WINDOWS_CREATE_NO_WINDOW)
-        .output();
+/// Volume mounted by the Edge CLI into the device container, it holds the device data.
+pub(crate) const EDGE_DEVICE_DATA_VOLUME: &str = "edge-device-data";
 
-    #[cfg(not(target_os = "windows"))]
-    let command = Command::new("docker").args(args).output();
+/// Container used to copy the local device data into the device data volume.
+const TEMPORARY_CONTAINER_NAME: &str = "temp_container_for_copying_edge_device_data";
+
+/// Run a Docker command for the temporary container. `action` describes it for the error message,
+/// eg. copy data to the volume.
+async fn run_temporary_container_command(
+    args: &[&str],
+    action: &str,
+    ok_message: &str,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let command = run_container_command(args, DOCKER_COMMAND_TIMEOUT, backend_communicator).await;
 
-    let output: Output;
     match command {
-        Ok(command_completed_result) => output = command_completed_result,
-        Err(command_not_completed) => {
+        Ok(output) if output.exit_code == Some(0) => {
+            log_info!(ok_message.to_string(), backend_communicator);
+            Ok(ok_message.to_string())
+        }
+        Ok(output) => {
             let err_message = format!(
-                "Could not run docker close container command. Error: {}",
-                command_not_completed
+                "Unable to {}. Docker exit code: {:?}. Stderr: {}",
+                action,
+                output.exit_code,
+                output.stderr.trim()
             );
-            log_and_emit(err_message.clone(), backend_communicator);
-            return Err(err_message);
+            log_error!(err_message.clone(), backend_communicator);
+            Err(err_message)
         }
-    };
-
-    let exit_code;
-    let success_exit_code = 0;
-
-    match output.status.code() {
-        Some(code) => {
-            exit_code = code;
-            log_and_emit(
-                format!("Docker Exit code = {}", exit_code),
-                backend_communicator,
+        Err(command_not_completed) => {
+            let err_message = format!(
+                "Could not run the command to {}. Error: {}",
+                action, command_not_completed
             );
-            if exit_code == success_exit_code {
-                let ok_message = "Closed docker container".to_string();
-                log_and_emit(ok_message.clone(), backend_communicator);
-                Ok(ok_message)
-            } else {
-                let err_message = format!(
-                    "Did not recognize error code for closing container: {}",
-                    exit_code
-                );
-                log_and_emit(err_message.clone(), backend_communicator);
-                Err(err_message)
-            }
-        }
-        None => {
-            let error_message = "Could not find error code for Docker output.".to_string();
-            log_and_emit(error_message.clone(), backend_communicator);
-            Err(error_message)
+            log_error!(err_message.clone(), backend_communicator);
+            Err(err_message)
         }
     }
 }
 
+pub(crate) async fn remove_temporary_container(
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    run_temporary_container_command(
+        &["rm", TEMPORARY_CONTAINER_NAME],
+        "close the temporary container",
+        "Closed docker container",
+        backend_communicator,
+    )
+    .await
+}
+
 /// Copy the local edge-device-data files to the running container
-pub(crate) fn copy_data_to_running_container(
+pub(crate) async fn copy_data_to_running_container(
     file_to_copy_path: String,
     backend_communicator: &BackendCommunicator,
 ) -> Result<String, String> {
-    let args = [
-        "cp",
-        &file_to_copy_path,
-        "temp_container_for_copying_edge_device_data:/data",
-    ];
+    let container_data_path = format!("{}:/data", TEMPORARY_CONTAINER_NAME);
+    run_temporary_container_command(
+        &["cp", &file_to_copy_path, &container_data_path],
+        "copy data to the volume",
+        "Copied file to edge device data volume.",
+        backend_communicator,
+    )
+    .await
+}
 
-    #[cfg(target_os = "windows")]
-    const WINDOWS_CREATE_NO_WINDOW: u32 = 0x08000000;
+/// Start a temporary docker container for the purpose of copying data
+pub(crate) async fn start_docker_container_for_copying_data(
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let device_data_mount = format!("{}:/data", EDGE_DEVICE_DATA_VOLUME);
+    run_temporary_container_command(
+        &[
+            "container",
+            "create",
+            "--name",
+            TEMPORARY_CONTAINER_NAME,
+            "-v",
+            &device_data_mount,
+            ALPINE_IMAGE,
+        ],
+        "start the temporary container",
+        "Created temporary container for copying data.",
+        backend_communicator,
+    )
+    .await
+}
 
-    #[cfg(target_os = "windows")]
-    let command = Command::new("docker")
-        .args(args)
-        .creation_flags(WINDOWS_CREATE_NO_WINDOW)
-        .output();
+/// Name filter matching the container started by the Edge CLI `device start` command.
+const EDGE_DEVICE_CONTAINER_NAME_FILTER: &str = "name=edge-device";
 
-    #[cfg(not(target_os = "windows"))]
-    let command = Command::new("docker").args(args).output();
+/// Returns the state of the Edge device container eg. running or exited. "not found" if there is none.
+pub(crate) async fn get_edge_device_container_state(
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let args = [
+        "ps",
+        "--all",
+        "--filter",
+        EDGE_DEVICE_CONTAINER_NAME_FILTER,
+        "--format",
+        "{{.State}}",
+    ];
 
-    let output: Output;
+    let command = run_container_command(&args, DOCKER_COMMAND_TIMEOUT, backend_communicator).await;
+
+    let output: ProcessOutput;
     match command {
         Ok(command_completed_result) => output = command_completed_result,
         Err(command_not_completed) => {
             let err_message = format!(
-                "Could not run copy file command. Error: {}",
+                "Could not run docker container state command. Error: {}",
                 command_not_completed
             );
-            log_and_emit(err_message.clone(), backend_communicator);
+            log_error!(err_message.clone(), backend_communicator);
             return Err(err_message);
         }
     };
 
-    let exit_code;
-    let success_exit_code = 0;
+    if output.exit_code != Some(0) {
+        let err_message = format!(
+            "Could not read Edge device container state. Docker exit code: {:?}",
+            output.exit_code
+        );
+        log_error!(err_message.clone(), backend_communicator);
+        return Err(err_message);
+    }
 
-    match output.status.code() {
-        Some(code) => {
-            exit_code = code;
-            log_and_emit(
-                format!("Docker Exit code = {}", exit_code),
-                backend_communicator,
-            );
-            if exit_code == success_exit_code {
-                let ok_message = "Copied file to edge device data volume.".to_string();
-                log_and_emit(ok_message.clone(), backend_communicator);
-                Ok(ok_message)
-            } else {
-                let err_message = format!(
-                    "Did not recognize error code for copying data to volume: {}",
-                    exit_code
-                );
-                log_and_emit(err_message.clone(), backend_communicator);
-                Err(err_message)
-            }
-        }
-        None => {
-            let error_message = "Could not find error code for Docker output.".to_string();
-            log_and_emit(error_message.clone(), backend_communicator);
-            Err(error_message)
-        }
+    match output.stdout.lines().next() {
+        Some(state) if !state.trim().is_empty() => Ok(state.trim().to_string()),
+        _ => Ok("not found".to_string()),
     }
 }
 
-/// Start a temporary docker container for the purpose of copying data
-pub(crate) fn start_docker_container_for_copying_data(
+/// Returns the image of the Edge device container eg. registry.edge.network/edge/device:latest.
+/// None if there is no Edge device container.
+pub(crate) async fn get_edge_device_container_image(
     backend_communicator: &BackendCommunicator,
-) -> Result<String, String> {
+) -> Result<Option<String>, String> {
     let args = [
-        "container",
-        "create",
-        "--name",
-        "temp_container_for_copying_edge_device_data",
-        "-v",
-        "edge-device-data:/data",
-        "alpine",
+        "ps",
+        "--all",
+        "--filter",
+        EDGE_DEVICE_CONTAINER_NAME_FILTER,
+        "--format",
+        "{{.Image}}",
     ];
 
-    #[cfg(target_os = "windows")]
-    const WINDOWS_CREATE_NO_WINDOW: u32 = 0x08000000;
+    let command = run_container_command(&args, DOCKER_COMMAND_TIMEOUT, backend_communicator).await;
 
-    #[cfg(target_os = "windows")]
-    let command = Command::new("docker")
-        .args(args)
-        .creation_flags(WINDOWS_CREATE_NO_WINDOW)
-        .output();
-
-    #[cfg(not(target_os = "windows"))]
-    let command = Command::new("docker").args(args).output();
-
-    let output: Output;
+    let output: ProcessOutput;
     match command {
         Ok(command_completed_result) => output = command_completed_result,
         Err(command_not_completed) => {
             let err_message = format!(
-                "Could not create temporary container. Error: {}",
+                "Could not run docker container image command. Error: {}",
                 command_not_completed
             );
-            log_and_emit(err_message.clone(), backend_communicator);
+            log_error!(err_message.clone(), backend_communicator);
             return Err(err_message);
         }
     };
 
-    let exit_code;
-    let success_exit_code = 0;
+    if output.exit_code != Some(0) {
+        let err_message = format!(
+            "Could not read Edge device container image. Docker exit code: {:?}",
+            output.exit_code
+        );
+        log_error!(err_message.clone(), backend_communicator);
+        return Err(err_message);
+    }
 
-    match output.status.code() {
-        Some(code) => {
-            exit_code = code;
-            log_and_emit(
-                format!("Docker Exit code = {}", exit_code),
-                backend_communicator,
-            );
-            if exit_code == success_exit_code {
-                let ok_message = "Created temporary container for copying data.".to_string();
-                log_and_emit(ok_message.clone(), backend_communicator);
-                Ok(ok_message)
-            } else {
-                let err_message = format!(
-                    "Did not recognize error code for starting temporary container: {}",
-                    exit_code
-                );
-                log_and_emit(err_message.clone(), backend_communicator);
-                Err(err_message)
+    match output.stdout.lines().next() {
+        Some(image) if !image.trim().is_empty() => Ok(Some(image.trim().to_string())),
+        _ => Ok(None),
+    }
+}
+
+/// Returns the last lines of the Edge device container logs, stdout and stderr combined.
+pub(crate) async fn get_edge_device_container_logs(
+    tail: usize,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let args = [
+        "ps",
+        "--all",
+        "--filter",
+        EDGE_DEVICE_CONTAINER_NAME_FILTER,
+        "--format",
+        "{{.Names}}",
+    ];
+    let container_name =
+        match run_container_command(&args, DOCKER_COMMAND_TIMEOUT, backend_communicator).await {
+            Ok(output) if output.exit_code == Some(0) => match output.stdout.lines().next() {
+                Some(container_name) if !container_name.trim().is_empty() => {
+                    container_name.trim().to_string()
+                }
+                _ => return Err("No Edge device container found.".to_string()),
+            },
+            Ok(output) => {
+                return Err(format!(
+                    "Could not find Edge device container. Docker exit code: {:?}",
+                    output.exit_code
+                ))
             }
+            Err(err) => {
+                return Err(format!(
+                    "Could not run docker container name command. Error: {}",
+                    err
+                ))
+            }
+        };
+
+    let tail_str = tail.to_string();
+    let args = ["logs", "--timestamps", "--tail", &tail_str, &container_name];
+    match run_container_command(&args, DOCKER_COMMAND_TIMEOUT, backend_communicator).await {
+        Ok(output) if output.exit_code == Some(0) => {
+            Ok(format!("{}{}", output.stdout, output.stderr))
         }
-        None => {
-            let error_message = "Could not find error code for Docker output.".to_string();
-            log_and_emit(error_message.clone(), backend_communicator);
-            Err(error_message)
-        }
+        Ok(output) => Err(format!(
+            "Could not read logs of container {}. Docker exit code: {:?}. {}",
+            container_name, output.exit_code, output.stderr
+        )),
+        Err(err) => Err(format!("Could not run docker logs command. Error: {}", err)),
     }
 }
 
-/// Os-independent docker status check based on https://docs.docker.com/config/daemon/troubleshoot/#check-whether-docker-is-running
-pub(crate) fn get_docker_status(
+/// Pull the latest version of an image.
+pub(crate) async fn pull_docker_image(
+    image: &str,
     backend_communicator: &BackendCommunicator,
 ) -> Result<String, String> {
-    // OS-independent info method implemented
+    let command =
+        run_container_command(&["pull", image], DOCKER_PULL_TIMEOUT, backend_communicator).await;
+
+    match command {
+        Ok(output) if output.exit_code == Some(0) => {
+            let ok_message = format!("Pulled latest image {}.", image);
+            log_info!(ok_message.clone(), backend_communicator);
+            Ok(ok_message)
+        }
+        Ok(output) => {
+            let err_message = format!(
+                "Could not pull image {}. Docker exit code: {:?}. Error: {}",
+                image,
+                output.exit_code,
+                output.stderr.trim()
+            );
+            log_error!(err_message.clone(), backend_communicator);
+            Err(err_message)
+        }
+        Err(command_not_completed) => {
+            let err_message = format!(
+                "Could not run docker pull command. Error: {}",
+                command_not_completed
+            );
+            log_error!(err_message.clone(), backend_communicator);
+            Err(err_message)
+        }
+    }
+}
 
-    #[cfg(target_os = "windows")]
-    const WINDOWS_CREATE_NO_WINDOW: u32 = 0x08000000;
+/// Returns the version of the Docker client, for Docker versions without ClientInfo in docker info.
+async fn get_docker_client_version(backend_communicator: &BackendCommunicator) -> Option<String> {
+    let args = ["version", "--format", "{{.Client.Version}}"];
+    match run_container_command(&args, DOCKER_COMMAND_TIMEOUT, backend_communicator).await {
+        Ok(output) if !output.stdout.trim().is_empty() => Some(output.stdout.trim().to_string()),
+        _ => None,
+    }
+}
 
-    #[cfg(target_os = "windows")]
-    let command = Command::new("docker")
-        .arg("info")
-        .creation_flags(WINDOWS_CREATE_NO_WINDOW)
-        .output();
+/// Os-independent docker status check based on https://docs.docker.com/config/daemon/troubleshoot/#check-whether-docker-is-running
+/// Detects the container runtime first, Podman is checked the same way.
+/// Returns the parsed docker info with findings, eg. a too old version or Windows-container mode.
+pub(crate) async fn get_docker_status(
+    backend_communicator: &BackendCommunicator,
+) -> Result<DockerInfo, String> {
+    // OS-independent info method implemented
+    let container_runtime = detect_container_runtime(backend_communicator).await?;
 
-    #[cfg(not(target_os = "windows"))]
-    let command = Command::new("docker").arg("info").output();
+    let command = run_container_command(
+        &["info", "--format", "{{json .}}"],
+        DOCKER_COMMAND_TIMEOUT,
+        backend_communicator,
+    )
+    .await;
 
-    let output: Output;
+    let output: ProcessOutput;
     match command {
         Ok(command_completed_result) => output = command_completed_result,
         Err(command_not_completed) => {
@@ -230,45 +319,218 @@ pub(crate) fn get_docker_status(
                 "Is Docker installed & did you restart your computer? Docker installation link: https://www.docker.com/products/docker-desktop/ Error = {}",
                 command_not_completed
             );
-            utility::log_and_emit(errormessage.clone(), backend_communicator);
+            log_error!(errormessage.clone(), backend_communicator);
             return Err(errormessage);
         }
     };
 
+    let requirements_config = get_requirements_config(backend_communicator);
+    let min_version = match container_runtime.cli {
+        ContainerRuntimeCli::Docker => requirements_config.min_docker_version,
+        ContainerRuntimeCli::Podman => requirements_config.min_podman_version,
+    };
     let exit_code: i32;
     let docker_installed_and_running_code = 0;
     let docker_installed_not_running_code = 1;
-    match output.status.code() {
+    match output.exit_code {
         Some(code) => {
             exit_code = code;
-            log_and_emit(
+            log_debug!(
                 format!("Docker Exit code = {}", exit_code),
                 backend_communicator,
             );
 
             if exit_code == docker_installed_and_running_code {
-                let ok_string = String::from("Docker installed & ready.");
-                log_and_emit(ok_string.clone(), backend_communicator);
-                Ok(ok_string)
+                let mut docker_info = match parse_docker_info(&output.stdout) {
+                    Ok(docker_info) => docker_info,
+                    Err(err) => {
+                        // Docker runs, only the details are unknown.
+                        log_warn!(err, backend_communicator);
+                        DockerInfo {
+                            runtime: container_runtime.cli,
+                            ..Default::default()
+                        }
+                    }
+                };
+                if docker_info.client_version.is_none() {
+                    docker_info.client_version =
+                        get_docker_client_version(backend_communicator).await;
+                }
+                docker_info.findings = get_docker_findings(&docker_info, &min_version);
+                log_info!(
+                    format!(
+                        "{} {} ({} containers, {} storage driver) found.",
+                        docker_info.runtime,
+                        docker_info
+                            .server_version
+                            .as_deref()
+                            .unwrap_or("unknown version"),
+                        docker_info.os_type.as_deref().unwrap_or("unknown"),
+                        docker_info.storage_driver.as_deref().unwrap_or("unknown")
+                    ),
+                    backend_communicator,
+                );
+                Ok(docker_info)
+            } else if container_runtime.cli == ContainerRuntimeCli::Podman {
+                let err_string = format!("Podman installed but not running/ready. If you use a Podman machine, start it with `podman machine start`. Error: {}", output.stderr.trim());
+                log_error!(err_string.clone(), backend_communicator);
+                Err(err_string)
             } else if exit_code == docker_installed_not_running_code {
+                // Docker lists why it can not reach the daemon, eg. permission denied.
+                if let Ok(mut docker_info) = parse_docker_info(&output.stdout) {
+                    docker_info.findings = get_docker_findings(&docker_info, &min_version);
+                    if docker_info.has_errors() {
+                        let err_string = docker_info.findings_message(FindingSeverity::Error);
+                        log_error!(err_string.clone(), backend_communicator);
+                        return Err(err_string);
+                    }
+                }
                 let err_string = "Docker installed but not running/ready. Docker loading time can be several minutes.".to_string();
-                log_and_emit(err_string.clone(), backend_communicator);
+                log_error!(err_string.clone(), backend_communicator);
                 return Err(err_string);
             } else {
                 let err_string = "Docker exit code not recognized".to_string();
-                log_and_emit(err_string.clone(), backend_communicator);
+                log_error!(err_string.clone(), backend_communicator);
                 return Err(err_string);
             }
         }
         None => {
             let err_string = "Docker running status could not be checked".to_string();
-            log_and_emit(err_string.clone(), backend_communicator);
+            log_error!(err_string.clone(), backend_communicator);
             Err(err_string)
         }
     }
+}
+
+/// Returns whether a Docker volume exists.
+pub(crate) async fn docker_volume_exists(
+    volume: &str,
+    backend_communicator: &BackendCommunicator,
+) -> Result<bool, String> {
+    let args = ["volume", "inspect", volume];
+
+    match run_container_command(&args, DOCKER_COMMAND_TIMEOUT, backend_communicator).await {
+        Ok(output) => Ok(output.exit_code == Some(0)),
+        Err(command_not_completed) => {
+            let err_message = format!(
+                "Could not run docker volume inspect command. Error: {}",
+                command_not_completed
+            );
+            log_error!(err_message.clone(), backend_communicator);
+            Err(err_message)
+        }
+    }
+}
+
+/// Run a shell script in a temporary alpine container with the given volume mounts.
+async fn run_volume_script(
+    mounts: &[String],
+    script: &str,
+    backend_communicator: &BackendCommunicator,
+) -> Result<(), String> {
+    let mut args: Vec<&str> = vec!["run", "--rm"];
+    for mount in mounts {
+        args.push("-v");
+        args.push(mount);
+    }
+    args.extend([ALPINE_IMAGE, "sh", "-c", script]);
+
+    let command = run_container_command(&args, DOCKER_COMMAND_TIMEOUT, backend_communicator).await;
+
+    match command {
+        Ok(output) if output.exit_code == Some(0) => Ok(()),
+        Ok(output) => {
+            let err_message = format!(
+                "Volume operation failed. Docker exit code: {:?}. Stderr: {}",
+                output.exit_code,
+                output.stderr.trim()
+            );
+            log_error!(err_message.clone(), backend_communicator);
+            Err(err_message)
+        }
+        Err(command_not_completed) => {
+            let err_message = format!(
+                "Could not run volume operation. Error: {}",
+                command_not_completed
+            );
+            log_error!(err_message.clone(), backend_communicator);
+            Err(err_message)
+        }
+    }
+}
+
+/// Replace the content of the target volume with the content of the source volume.
+/// The target volume is created if it does not exist.
+pub(crate) async fn copy_docker_volume(
+    source_volume: &str,
+    target_volume: &str,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let mounts = [
+        format!("{}:/from", source_volume),
+        format!("{}:/to", target_volume),
+    ];
+    run_volume_script(
+        &mounts,
+        "rm -rf /to/* /to/.[!.]* && cp -a /from/. /to/",
+        backend_communicator,
+    )
+    .await?;
+
+    let ok_message = format!("Copied volume {} to {}.", source_volume, target_volume);
+    log_info!(ok_message.clone(), backend_communicator);
+    Ok(ok_message)
+}
+
+/// Remove all files from a volume.
+pub(crate) async fn clear_docker_volume(
+    volume: &str,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let mounts = [format!("{}:/data", volume)];
+    run_volume_script(&mounts, "rm -rf /data/* /data/.[!.]*", backend_communicator).await?;
+
+    let ok_message = format!("Cleared volume {}.", volume);
+    log_info!(ok_message.clone(), backend_communicator);
+    Ok(ok_message)
+}
 
-    // output Docker log.
+/// Output of a Docker command for diagnostics, eg. `docker version`.
+/// Includes stderr, where Docker reports problems such as a daemon which is not running.
+pub(crate) async fn get_docker_command_output(
+    args: &[&str],
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    match run_container_command(args, DOCKER_COMMAND_TIMEOUT, backend_communicator).await {
+        Ok(output) => Ok(format!(
+            "Exit code: {:?}\n{}{}",
+            output.exit_code, output.stdout, output.stderr
+        )),
+        Err(err) => Err(format!(
+            "Could not run docker {}. Error: {}",
+            args.join(" "),
+            err
+        )),
+    }
+}
 
-    // io::stdout().write_all(&output.stdout).unwrap();
-    // io::stderr().write_all(&output.stderr).unwrap();
+/// Returns the directory in which Docker stores images & containers, eg. /var/lib/docker.
+pub(crate) async fn get_docker_root_dir(
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let root_dir_format = match get_container_runtime(backend_communicator).cli {
+        ContainerRuntimeCli::Docker => "{{.DockerRootDir}}",
+        ContainerRuntimeCli::Podman => "{{.Store.GraphRoot}}",
+    };
+    let args = ["info", "--format", root_dir_format];
+    match run_container_command(&args, DOCKER_COMMAND_TIMEOUT, backend_communicator).await {
+        Ok(output) if output.exit_code == Some(0) && !output.stdout.trim().is_empty() => {
+            Ok(output.stdout.trim().to_string())
+        }
+        Ok(output) => Err(format!(
+            "Could not read Docker data directory. Docker exit code: {:?}",
+            output.exit_code
+        )),
+        Err(err) => Err(format!("Could not run docker info command. Error: {}", err)),
+    }
 }
@@ -0,0 +1,236 @@
+use std::sync::OnceLock;
+use tauri::AppHandle;
+
+use crate::control_edge_cli::{device_restart, get_edge_cli_version, start_step, stop_step};
+use crate::docker::get_edge_device_container_state;
+use crate::logging::{log_error, log_info, log_warn};
+use crate::node_status::get_node_status;
+use crate::notifications::show_desktop_notification;
+use crate::shutdown::spawn_background_task;
+use crate::tray::request_tray_refresh;
+use crate::BackendCommunicator;
+
+/// Whether the arguments of the first launch asked to hide (true) or show (false) the window.
+static FIRST_LAUNCH_MINIMIZED: OnceLock<Option<bool>> = OnceLock::new();
+
+/// Command passed to a second launch of the program, run by the instance which is already running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LaunchCommand {
+    Start,     // --start
+    Stop,      // --stop
+    Restart,   // --restart
+    Status,    // --status, shown as desktop notification.
+    Show,      // --show
+    Minimized, // --minimized, hides the window to the tray.
+}
+
+impl LaunchCommand {
+    fn from_arg(arg: &str) -> Option<Self> {
+        match arg.trim().to_lowercase().as_str() {
+            "--start" => Some(LaunchCommand::Start),
+            "--stop" => Some(LaunchCommand::Stop),
+            "--restart" => Some(LaunchCommand::Restart),
+            "--status" => Some(LaunchCommand::Status),
+            "--show" => Some(LaunchCommand::Show),
+            "--minimized" | "--hide" => Some(LaunchCommand::Minimized),
+            _ => None,
+        }
+    }
+}
+
+/// Commands & unknown arguments of a launch. The first argument is the program itself.
+fn parse_launch_args(argv: &[String]) -> (Vec<LaunchCommand>, Vec<String>) {
+    let mut launch_commands: Vec<LaunchCommand> = vec![];
+    let mut unknown_args: Vec<String> = vec![];
+    for arg in argv.iter().skip(1) {
+        match LaunchCommand::from_arg(arg) {
+            Some(launch_command) => {
+                if !launch_commands.contains(&launch_command) {
+                    launch_commands.push(launch_command);
+                }
+            }
+            // eg. -psn_0_12345 which macOS passes to apps started from Finder.
+            None if arg.starts_with("-psn_") => {}
+            None => unknown_args.push(arg.clone()),
+        }
+    }
+    (launch_commands, unknown_args)
+}
+
+fn show_window(backend_communicator: &BackendCommunicator) {
+    let window = &backend_communicator.front_end_window;
+    let _ = window.show();
+    let _ = window.unminimize();
+    let _ = window.set_focus();
+}
+
+fn hide_window(backend_communicator: &BackendCommunicator) {
+    if let Err(err) = backend_communicator.front_end_window.hide() {
+        log_error!(
+            format!("Unable to hide the window. Err: {}", err),
+            backend_communicator,
+        );
+    }
+}
+
+/// Log the node status and show it as desktop notification, the launching script can not
+/// receive output from the running instance.
+async fn show_node_status(backend_communicator: &BackendCommunicator) {
+    let device_status = match get_edge_device_container_state(backend_communicator).await {
+        Ok(container_state) => format!("Device container: {}.", container_state),
+        Err(_) => "Device container: unknown, is Docker running?".to_string(),
+    };
+    let online_status = match get_node_status().online {
+        Some(true) => "Online according to the index.",
+        Some(false) => "Offline according to the index.",
+        None => "Online status not checked yet.",
+    };
+    let cli_status = match get_edge_cli_version(backend_communicator).await {
+        Ok(version) => format!("Edge CLI {}.", version),
+        Err(_) => "Edge CLI not installed.".to_string(),
+    };
+    let node_status = format!("{} {} {}", device_status, online_status, cli_status);
+    log_info!(
+        format!("Node status: {}", node_status),
+        backend_communicator,
+    );
+    show_desktop_notification("Edge Node Status", &node_status, backend_communicator);
+}
+
+async fn run_device_launch_command(
+    launch_command: LaunchCommand,
+    backend_communicator: &BackendCommunicator,
+) {
+    let (description, step_result) = match launch_command {
+        LaunchCommand::Start => ("start", start_step(false, backend_communicator).await),
+        LaunchCommand::Stop => ("stop", stop_step(false, backend_communicator).await),
+        LaunchCommand::Restart => {
+            let device_operation_result = device_restart(false, backend_communicator).await;
+            if device_operation_result.success {
+                ("restart", Ok("Device restarted.".to_string()))
+            } else {
+                (
+                    "restart",
+                    Err("See the log for the failed step.".to_string()),
+                )
+            }
+        }
+        _ => return,
+    };
+    match step_result {
+        Ok(ok_message) => log_info!(ok_message, backend_communicator),
+        Err(err) => log_error!(
+            format!(
+                "Unable to {} the device from the command line. {}",
+                description, err
+            ),
+            backend_communicator,
+        ),
+    }
+    request_tray_refresh();
+}
+
+fn warn_unknown_args(unknown_args: &[String], backend_communicator: &BackendCommunicator) {
+    if !unknown_args.is_empty() {
+        log_warn!(
+            format!(
+                "Ignored unknown launch arguments {}. Use --start, --stop, --restart, --status, --show or --minimized.",
+                unknown_args.join(" ")
+            ),
+            backend_communicator,
+        );
+    }
+}
+
+/// Run the device & status commands in order in the background.
+fn run_background_commands(
+    launch_commands: Vec<LaunchCommand>,
+    backend_communicator: BackendCommunicator,
+) {
+    let background_commands: Vec<LaunchCommand> = launch_commands
+        .into_iter()
+        .filter(|launch_command| {
+            !matches!(
+                launch_command,
+                LaunchCommand::Show | LaunchCommand::Minimized
+            )
+        })
+        .collect();
+    if background_commands.is_empty() {
+        return;
+    }
+    spawn_background_task(async move {
+        for launch_command in background_commands {
+            if launch_command == LaunchCommand::Status {
+                show_node_status(&backend_communicator).await;
+            } else {
+                run_device_launch_command(launch_command, &backend_communicator).await;
+            }
+        }
+    });
+}
+
+/// Run the arguments the program was started with, eg. `--minimized --start` at login.
+/// The window is shown or hidden by the frontend once loaded, see `get_first_launch_minimized`.
+pub fn run_first_launch(backend_communicator: &BackendCommunicator) {
+    let argv: Vec<String> = std::env::args().collect();
+    let (launch_commands, unknown_args) = parse_launch_args(&argv);
+    warn_unknown_args(&unknown_args, backend_communicator);
+    if launch_commands.is_empty() {
+        return;
+    }
+    log_info!(
+        format!("Launched with arguments {}.", argv[1..].join(" ")),
+        backend_communicator,
+    );
+
+    let first_launch_minimized =
+        launch_commands
+            .iter()
+            .rev()
+            .find_map(|launch_command| match launch_command {
+                LaunchCommand::Show => Some(false),
+                LaunchCommand::Minimized => Some(true),
+                _ => None,
+            });
+    let _ = FIRST_LAUNCH_MINIMIZED.set(first_launch_minimized);
+    run_background_commands(launch_commands, backend_communicator.clone());
+}
+
+/// Whether the first launch asked to hide the window, overriding `launch_minimized` of the config.
+/// None if it did not ask for either.
+pub fn get_first_launch_minimized() -> Option<bool> {
+    FIRST_LAUNCH_MINIMIZED.get().copied().flatten()
+}
+
+/// Run the arguments of a second launch in the running instance, eg. `--start` from a script.
+/// A second launch without commands shows the window, like opening the program normally.
+pub fn run_second_launch(app_handle: &AppHandle, argv: Vec<String>) {
+    let backend_communicator = match BackendCommunicator::from_app_handle(app_handle) {
+        Ok(backend_communicator) => backend_communicator,
+        Err(err) => {
+            eprintln!("Unable to handle second launch {:?}. Err: {}", argv, err);
+            return;
+        }
+    };
+    let (launch_commands, unknown_args) = parse_launch_args(&argv);
+    warn_unknown_args(&unknown_args, &backend_communicator);
+    if launch_commands.is_empty() {
+        show_window(&backend_communicator);
+        return;
+    }
+    log_info!(
+        format!("Received launch arguments {}.", argv[1..].join(" ")),
+        &backend_communicator,
+    );
+
+    // Window commands run right away, the others in order in the background.
+    for launch_command in launch_commands.iter() {
+        match launch_command {
+            LaunchCommand::Show => show_window(&backend_communicator),
+            LaunchCommand::Minimized => hide_window(&backend_communicator),
+            _ => {}
+        }
+    }
+    run_background_commands(launch_commands, backend_communicator);
+}
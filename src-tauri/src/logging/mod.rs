@@ -0,0 +1,652 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+use crate::config::getters::get_config_path_as_str;
+use crate::config::{ConfigStruct, LogFormat, LoggingConfig, NotificationChannel};
+use crate::BackendCommunicator;
+
+mod redact;
+
+pub(crate) use redact::redact;
+
+/// Log at debug level. Debug messages are not shown in the window.
+macro_rules! log_debug {
+    ($message:expr, $backend_communicator:expr $(,)?) => {
+        $crate::logging::log(
+            $crate::logging::LogLevel::Debug,
+            module_path!(),
+            $message,
+            $backend_communicator,
+        )
+    };
+}
+
+/// Log at info level and show the message in the window.
+macro_rules! log_info {
+    ($message:expr, $backend_communicator:expr $(,)?) => {
+        $crate::logging::log(
+            $crate::logging::LogLevel::Info,
+            module_path!(),
+            $message,
+            $backend_communicator,
+        )
+    };
+}
+
+/// Log at warn level and show the message in the window.
+macro_rules! log_warn {
+    ($message:expr, $backend_communicator:expr $(,)?) => {
+        $crate::logging::log(
+            $crate::logging::LogLevel::Warn,
+            module_path!(),
+            $message,
+            $backend_communicator,
+        )
+    };
+}
+
+/// Log at error level and show the message in the window.
+macro_rules! log_error {
+    ($message:expr, $backend_communicator:expr $(,)?) => {
+        $crate::logging::log(
+            $crate::logging::LogLevel::Error,
+            module_path!(),
+            $message,
+            $backend_communicator,
+        )
+    };
+}
+
+#[allow(unused_imports)]
+pub(crate) use {log_debug, log_error, log_info, log_warn};
+
+/// Severity of a log entry, ordered from least to most severe.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(level: &str) -> Result<Self, Self::Err> {
+        match level.trim().to_lowercase().as_str() {
+            "debug" => Ok(LogLevel::Debug),
+            "info" => Ok(LogLevel::Info),
+            "warn" | "warning" => Ok(LogLevel::Warn),
+            "error" => Ok(LogLevel::Error),
+            _ => Err(format!("Unknown log level {}.", level)),
+        }
+    }
+}
+
+/// A single line of the log, as written in JSON lines format.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LogEntry {
+    pub timestamp: String, // RFC 3339, eg. 2023-05-01T12:00:00.000Z. Empty for lines of old logs.
+    pub level: LogLevel,
+    pub target: String, // Module which logged the entry, eg. docker
+    pub message: String,
+}
+
+/// Serializes writes and rotation of the log file.
+static LOG_FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// The logging settings and secrets are needed on every log call, so they are cached.
+static LOGGING_CONFIG_CACHE: Mutex<Option<CachedLoggingConfig>> = Mutex::new(None);
+
+#[derive(Clone, Default)]
+struct CachedLoggingConfig {
+    logging_config: LoggingConfig,
+    known_secrets: Vec<String>, // Masked in every log message.
+}
+
+fn lock_log_file() -> MutexGuard<'static, ()> {
+    match LOG_FILE_LOCK.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+fn logging_config_cache() -> MutexGuard<'static, Option<CachedLoggingConfig>> {
+    match LOGGING_CONFIG_CACHE.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// Private keys and notification credentials of the config.
+fn get_known_secrets(config: &ConfigStruct) -> Vec<String> {
+    let mut known_secrets = vec![config.private_key.clone()];
+    for device_identity in config.network_identities.values() {
+        known_secrets.push(device_identity.private_key.clone());
+    }
+    for notification_channel in config.notifications.channels.iter() {
+        match notification_channel {
+            NotificationChannel::Webhook { url } => known_secrets.push(url.clone()),
+            NotificationChannel::Discord { webhook_url } => known_secrets.push(webhook_url.clone()),
+            NotificationChannel::Telegram { bot_token, .. } => {
+                known_secrets.push(bot_token.clone())
+            }
+            NotificationChannel::Email { smtp_password, .. } => {
+                known_secrets.push(smtp_password.clone())
+            }
+        }
+    }
+    known_secrets.retain(|known_secret| !known_secret.is_empty() && known_secret != "Unset");
+    known_secrets
+}
+
+/// Returns the logging settings and secrets. Reads the config file directly, as loading it via
+/// `get_config` logs itself.
+fn get_cached_logging_config(backend_communicator: &BackendCommunicator) -> CachedLoggingConfig {
+    if let Some(cached_logging_config) = logging_config_cache().as_ref() {
+        return cached_logging_config.clone();
+    }
+
+    let config_path_str = get_config_path_as_str(backend_communicator);
+    if !Path::new(&config_path_str).exists() {
+        return CachedLoggingConfig::default();
+    }
+    match confy::load_path::<ConfigStruct>(&config_path_str) {
+        Ok(config) => {
+            let cached_logging_config = CachedLoggingConfig {
+                known_secrets: get_known_secrets(&config),
+                logging_config: config.logging,
+            };
+            *logging_config_cache() = Some(cached_logging_config.clone());
+            cached_logging_config
+        }
+        Err(_) => CachedLoggingConfig::default(),
+    }
+}
+
+fn get_logging_config(backend_communicator: &BackendCommunicator) -> LoggingConfig {
+    get_cached_logging_config(backend_communicator).logging_config
+}
+
+/// Private keys and notification credentials of the config, to be masked with `redact`.
+pub(crate) fn get_cached_known_secrets(backend_communicator: &BackendCommunicator) -> Vec<String> {
+    get_cached_logging_config(backend_communicator).known_secrets
+}
+
+/// Mask private keys, device tokens and other secrets in a message.
+pub(crate) fn redact_secrets(message: &str, backend_communicator: &BackendCommunicator) -> String {
+    redact(message, &get_cached_known_secrets(backend_communicator))
+}
+
+/// Forget the cached logging settings and secrets, so changes to the config are used by the next
+/// log call.
+pub fn reload_logging_config() {
+    *logging_config_cache() = None;
+}
+
+/// Module path without the crate name, eg. edge_staking_gui::docker becomes docker.
+fn short_target(target: &str) -> &str {
+    let crate_name = env!("CARGO_CRATE_NAME");
+    match target.strip_prefix(crate_name) {
+        Some("") => "main",
+        Some(module_path) => module_path.trim_start_matches("::"),
+        None => target,
+    }
+}
+
+/// Minimum level of a target. The most specific target in the config wins.
+fn get_target_level(target: &str, logging_config: &LoggingConfig) -> LogLevel {
+    logging_config
+        .targets
+        .iter()
+        .filter(|(configured_target, _)| {
+            target == configured_target.as_str()
+                || target.starts_with(&format!("{}::", configured_target))
+        })
+        .max_by_key(|(configured_target, _)| configured_target.len())
+        .map(|(_, level)| *level)
+        .unwrap_or(logging_config.level)
+}
+
+pub fn get_log_path(backend_communicator: &BackendCommunicator) -> String {
+    let log_name = "log.txt".to_string();
+    let log_path_str = format!("{}{}", backend_communicator.data_dir.clone(), log_name);
+    log_path_str
+}
+
+/// Wait for a log entry being written and write the log file through to disk, eg. before the
+/// program exits.
+pub fn flush_log(backend_communicator: &BackendCommunicator) -> Result<(), String> {
+    let log_path_str = get_log_path(backend_communicator);
+    let _log_file_guard = lock_log_file();
+    match OpenOptions::new()
+        .append(true)
+        .open(&log_path_str)
+        .and_then(|log_file| log_file.sync_all())
+    {
+        Ok(_) => Ok(()),
+        Err(err) => Err(format!(
+            "Unable to flush log file {}. Err: {}",
+            log_path_str, err
+        )),
+    }
+}
+
+/// Path of a rotated log, eg. log.txt.1 is the most recent.
+fn get_rotated_log_path(log_path_str: &str, index: usize) -> String {
+    format!("{}.{}", log_path_str, index)
+}
+
+/// Rotate the log when it is too large or was last written on another day.
+fn rotate_if_needed(log_path_str: &str, logging_config: &LoggingConfig) {
+    let metadata = match fs::metadata(log_path_str) {
+        Ok(metadata) => metadata,
+        Err(_) => return, // No log yet.
+    };
+
+    let max_file_size_bytes = logging_config.max_file_size_mb * 1024 * 1024;
+    let is_too_large = max_file_size_bytes > 0 && metadata.len() >= max_file_size_bytes;
+    let is_from_previous_day = logging_config.rotate_daily
+        && match metadata.modified() {
+            Ok(modified) => DateTime::<Utc>::from(modified).date_naive() != Utc::now().date_naive(),
+            Err(_) => false,
+        };
+    if !is_too_large && !is_from_previous_day {
+        return;
+    }
+
+    // Shift log.txt.1 to log.txt.2 etc., the oldest falls off.
+    let _ = fs::remove_file(get_rotated_log_path(
+        log_path_str,
+        logging_config.max_files.max(1),
+    ));
+    for index in (1..logging_config.max_files).rev() {
+        let _ = fs::rename(
+            get_rotated_log_path(log_path_str, index),
+            get_rotated_log_path(log_path_str, index + 1),
+        );
+    }
+    if logging_config.max_files == 0 {
+        let _ = fs::remove_file(log_path_str);
+    } else {
+        let _ = fs::rename(log_path_str, get_rotated_log_path(log_path_str, 1));
+    }
+
+    remove_expired_logs(log_path_str, logging_config);
+}
+
+/// Remove rotated logs older than the retention period.
+fn remove_expired_logs(log_path_str: &str, logging_config: &LoggingConfig) {
+    if logging_config.max_age_days == 0 {
+        return;
+    }
+    let max_age = Duration::from_secs(logging_config.max_age_days * 24 * 3600);
+    for index in 1..=logging_config.max_files {
+        let rotated_log_path = get_rotated_log_path(log_path_str, index);
+        let is_expired = fs::metadata(&rotated_log_path)
+            .and_then(|metadata| metadata.modified())
+            .map(|modified| {
+                SystemTime::now()
+                    .duration_since(modified)
+                    .map(|age| age > max_age)
+                    .unwrap_or(false)
+            })
+            .unwrap_or(false);
+        if is_expired {
+            let _ = fs::remove_file(rotated_log_path);
+        }
+    }
+}
+
+/// Format an entry as a line of the log file.
+/// In text format, lines of multi-line messages are continued with a tab.
+fn format_log_entry(log_entry: &LogEntry, log_format: LogFormat) -> String {
+    match log_format {
+        LogFormat::Json => match serde_json::to_string(log_entry) {
+            Ok(json_line) => format!("{}\n", json_line),
+            Err(_) => String::new(),
+        },
+        LogFormat::Text => format!(
+            "{} {:5} {}: {}\n",
+            log_entry.timestamp,
+            log_entry.level.as_str(),
+            log_entry.target,
+            log_entry.message.trim_end().replace('\n', "\n\t")
+        ),
+    }
+}
+
+/// Append an entry to the log file, rotating it first if needed.
+fn write_log_entry(
+    log_entry: &LogEntry,
+    logging_config: &LoggingConfig,
+    log_path_str: &str,
+) -> Result<(), String> {
+    let _log_file_guard = lock_log_file();
+    rotate_if_needed(log_path_str, logging_config);
+
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path_str);
+    match file {
+        Ok(mut valid_file) => {
+            let log_line = format_log_entry(log_entry, logging_config.format);
+            match valid_file.write_all(log_line.as_bytes()) {
+                Ok(_) => Ok(()),
+                Err(_) => Err("Unable to write log to file.".to_string()),
+            }
+        }
+        Err(_) => Err(format!("Unable to open log file {}", log_path_str)),
+    }
+}
+
+/// Mask secrets in a message and append it to the log file, if the level of the target is
+/// enabled. Returns the masked message and the written entry.
+fn write_redacted_log(
+    level: LogLevel,
+    target: &str,
+    message: &str,
+    cached_logging_config: &CachedLoggingConfig,
+    log_path_str: &str,
+) -> (String, Option<LogEntry>) {
+    let logging_config = &cached_logging_config.logging_config;
+    let message = redact::redact(message, &cached_logging_config.known_secrets);
+    if level < get_target_level(target, logging_config) {
+        return (message, None);
+    }
+
+    let log_entry = LogEntry {
+        timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        level,
+        target: target.to_string(),
+        message: message.clone(),
+    };
+    if let Err(err) = write_log_entry(&log_entry, logging_config, log_path_str) {
+        eprintln!("{}", err);
+    }
+    (message, Some(log_entry))
+}
+
+/// Log a message of a target, usually called via the `log_*!` macros.
+/// Entries below the configured level of the target are dropped. Info and above are also shown
+/// in the window. Secrets are masked before the message is written or shown.
+pub fn log(
+    level: LogLevel,
+    target: &str,
+    message: String,
+    backend_communicator: &BackendCommunicator,
+) {
+    let (message, log_entry) = write_redacted_log(
+        level,
+        short_target(target),
+        &message,
+        &get_cached_logging_config(backend_communicator),
+        &get_log_path(backend_communicator),
+    );
+    if let Some(log_entry) = log_entry {
+        if cfg!(debug_assertions) {
+            print!("{}", format_log_entry(&log_entry, LogFormat::Text));
+        }
+    }
+
+    if level >= LogLevel::Info {
+        emit_event(message, backend_communicator);
+    }
+}
+
+/// Emit an event to the main window
+fn emit_event(message: String, backend_communicator: &BackendCommunicator) {
+    if let Err(err) = backend_communicator
+        .front_end_window
+        .emit(&backend_communicator.status_listener, message)
+    {
+        eprintln!("Unable to emit event. Err: {}", err);
+    }
+}
+
+/// Parse a line of the log file. Lines of logs from before levels existed become info entries.
+fn parse_log_line(line: &str) -> LogEntry {
+    if line.starts_with('{') {
+        if let Ok(log_entry) = serde_json::from_str::<LogEntry>(line) {
+            return log_entry;
+        }
+    }
+
+    let mut parts = line.splitn(4, ' ');
+    let timestamp = parts.next().unwrap_or_default();
+    let level = parts.next().unwrap_or_default();
+    if DateTime::parse_from_rfc3339(timestamp).is_ok() {
+        if let Ok(level) = level.parse::<LogLevel>() {
+            // The level is padded to 5 characters, so INFO is followed by an extra space.
+            let rest = line[timestamp.len() + 1..]
+                .trim_start_matches(level.as_str())
+                .trim_start();
+            if let Some((target, message)) = rest.split_once(": ") {
+                return LogEntry {
+                    timestamp: timestamp.to_string(),
+                    level,
+                    target: target.to_string(),
+                    message: message.to_string(),
+                };
+            }
+        }
+    }
+
+    LogEntry {
+        timestamp: String::new(),
+        level: LogLevel::Info,
+        target: String::new(),
+        message: line.to_string(),
+    }
+}
+
+/// Read all entries of a log file, oldest first.
+fn read_log_file(log_path_str: &str) -> Vec<LogEntry> {
+    let log_str = match fs::read_to_string(log_path_str) {
+        Ok(log_str) => log_str,
+        Err(_) => return vec![],
+    };
+
+    let mut log_entries: Vec<LogEntry> = vec![];
+    for line in log_str.lines() {
+        if let Some(continued_line) = line.strip_prefix('\t') {
+            if let Some(previous_log_entry) = log_entries.last_mut() {
+                previous_log_entry.message.push('\n');
+                previous_log_entry.message.push_str(continued_line);
+                continue;
+            }
+        }
+        if !line.trim().is_empty() {
+            log_entries.push(parse_log_line(line));
+        }
+    }
+    log_entries
+}
+
+/// Returns the most recent log entries, newest first, including rotated logs.
+/// Filters on minimum level, target (prefix) and a case-insensitive text.
+pub fn get_log_entries(
+    min_level: LogLevel,
+    target: &str,
+    contains: &str,
+    limit: usize,
+    backend_communicator: &BackendCommunicator,
+) -> Vec<LogEntry> {
+    let logging_config = get_logging_config(backend_communicator);
+    let log_path_str = get_log_path(backend_communicator);
+    let contains_lowercase = contains.to_lowercase();
+
+    let mut log_paths = vec![log_path_str.clone()];
+    for index in 1..=logging_config.max_files {
+        log_paths.push(get_rotated_log_path(&log_path_str, index));
+    }
+
+    let mut log_entries: Vec<LogEntry> = vec![];
+    for log_path in log_paths {
+        let matching_log_entries = read_log_file(&log_path)
+            .into_iter()
+            .rev()
+            .filter(|log_entry| log_entry.level >= min_level)
+            .filter(|log_entry| target.is_empty() || log_entry.target.starts_with(target))
+            .filter(|log_entry| {
+                contains_lowercase.is_empty()
+                    || log_entry
+                        .message
+                        .to_lowercase()
+                        .contains(&contains_lowercase)
+            });
+        for log_entry in matching_log_entries {
+            if log_entries.len() >= limit {
+                return log_entries;
+            }
+            log_entries.push(log_entry);
+        }
+    }
+    log_entries
+}
+
+/// The most recent log entries as text, oldest first. Secrets are masked again, as older logs
+/// were written before masking existed.
+pub(crate) fn export_recent_log(
+    limit: usize,
+    backend_communicator: &BackendCommunicator,
+) -> String {
+    let mut log_entries = get_log_entries(LogLevel::Debug, "", "", limit, backend_communicator);
+    log_entries.reverse();
+    let log_str: String = log_entries
+        .iter()
+        .map(|log_entry| format_log_entry(log_entry, LogFormat::Text))
+        .collect();
+    redact_secrets(&log_str, backend_communicator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRIVATE_KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+    const BOT_TOKEN: &str = "123456789:AAHdqTcvCH1vGWJxfSeofSAs0K5PALDsaw";
+    const DISCORD_WEBHOOK_URL: &str =
+        "https://discord.com/api/webhooks/1122334455/Xy-0aBcDeFgHiJkLmNoPqRsTuVwXyZ_12345";
+    const SMTP_PASSWORD: &str = "s3cret-smtp-pass";
+
+    fn config_with_secrets() -> ConfigStruct {
+        let mut config = ConfigStruct {
+            private_key: PRIVATE_KEY.to_string(),
+            ..Default::default()
+        };
+        config.notifications.channels = vec![
+            NotificationChannel::Discord {
+                webhook_url: DISCORD_WEBHOOK_URL.to_string(),
+            },
+            NotificationChannel::Telegram {
+                bot_token: BOT_TOKEN.to_string(),
+                chat_id: "42".to_string(),
+                api_url: "http://127.0.0.1:8081".to_string(),
+            },
+            NotificationChannel::Email {
+                smtp_host: "smtp.example.com".to_string(),
+                smtp_port: 465,
+                smtp_tls: true,
+                smtp_username: "me@example.com".to_string(),
+                smtp_password: SMTP_PASSWORD.to_string(),
+                from: "me@example.com".to_string(),
+                to: "me@example.com".to_string(),
+            },
+        ];
+        config
+    }
+
+    #[test]
+    fn known_secrets_cover_keys_and_credentials() {
+        let known_secrets = get_known_secrets(&config_with_secrets());
+        for secret in [PRIVATE_KEY, BOT_TOKEN, DISCORD_WEBHOOK_URL, SMTP_PASSWORD] {
+            assert!(known_secrets.contains(&secret.to_string()));
+        }
+        assert!(get_known_secrets(&ConfigStruct::default()).is_empty());
+    }
+
+    #[test]
+    fn log_file_only_contains_masked_secrets() {
+        let log_dir =
+            std::env::temp_dir().join(format!("edge_staking_gui_log_test_{}", std::process::id()));
+        fs::create_dir_all(&log_dir).unwrap();
+        let log_path = log_dir.join("log.txt");
+        let log_path_str = log_path.to_string_lossy().to_string();
+        let _ = fs::remove_file(&log_path);
+
+        let cached_logging_config = CachedLoggingConfig {
+            logging_config: LoggingConfig::default(),
+            known_secrets: get_known_secrets(&config_with_secrets()),
+        };
+        let message = format!(
+            "key {} telegram http://127.0.0.1:8081/bot{}/sendMessage discord {} smtp {}",
+            PRIVATE_KEY, BOT_TOKEN, DISCORD_WEBHOOK_URL, SMTP_PASSWORD
+        );
+        let (masked_message, log_entry) = write_redacted_log(
+            LogLevel::Error,
+            "notifications",
+            &message,
+            &cached_logging_config,
+            &log_path_str,
+        );
+        assert!(log_entry.is_some());
+
+        let log_content = fs::read_to_string(&log_path).unwrap();
+        let _ = fs::remove_dir_all(&log_dir);
+        for secret in [PRIVATE_KEY, BOT_TOKEN, DISCORD_WEBHOOK_URL, SMTP_PASSWORD] {
+            assert!(
+                !log_content.contains(secret),
+                "{} in {}",
+                secret,
+                log_content
+            );
+            assert!(!masked_message.contains(secret));
+        }
+        assert!(log_content.contains("ERROR notifications: key [REDACTED] telegram"));
+        assert_eq!(log_content.matches("[REDACTED]").count(), 4);
+    }
+
+    #[test]
+    fn masked_message_is_returned_below_log_level() {
+        let cached_logging_config = CachedLoggingConfig {
+            logging_config: LoggingConfig::default(),
+            known_secrets: vec![PRIVATE_KEY.to_string()],
+        };
+        let (masked_message, log_entry) = write_redacted_log(
+            LogLevel::Debug,
+            "docker",
+            &format!("key {}", PRIVATE_KEY),
+            &cached_logging_config,
+            "unused.txt",
+        );
+        assert!(log_entry.is_none());
+        assert_eq!(masked_message, "key [REDACTED]");
+    }
+}
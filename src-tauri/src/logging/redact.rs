@@ -0,0 +1,254 @@
+/// Replaces secrets in log messages.
+const REDACTED: &str = "[REDACTED]";
+/// Shorter known secrets are not masked, they would mask ordinary words.
+const MIN_SECRET_LENGTH: usize = 8;
+/// Names followed by `:` or `=` and a secret, eg. "privateKey":"<secret>" or smtp_password = <secret>.
+/// Lowercase, matched case-insensitive.
+const SECRET_NAMES: [&str; 5] = [
+    "privatekey",
+    "private_key",
+    "private key",
+    "password",
+    "bot_token",
+];
+/// Url parts followed by a secret, eg. Discord webhook and Telegram bot tokens.
+const SECRET_URL_PARTS: [&str; 2] = ["/api/webhooks/", "api.telegram.org/bot"];
+/// Device tokens are base64 encoded JSON, which starts with eyJ ({").
+const BASE64_JSON_PREFIX: &str = "eyJ";
+const MIN_BASE64_JSON_LENGTH: usize = 24;
+
+/// Mask private keys, device tokens and other secrets in a message.
+/// `known_secrets` are masked wherever they occur, eg. the private key in the config.
+pub fn redact(message: &str, known_secrets: &[String]) -> String {
+    let mut redacted = message.to_string();
+
+    let mut known_secrets: Vec<&String> = known_secrets
+        .iter()
+        .filter(|known_secret| known_secret.len() >= MIN_SECRET_LENGTH)
+        .collect();
+    // Mask longer secrets first, a secret may contain another.
+    known_secrets.sort_by_key(|known_secret| std::cmp::Reverse(known_secret.len()));
+    for known_secret in known_secrets {
+        if redacted.contains(known_secret.as_str()) {
+            redacted = redacted.replace(known_secret.as_str(), REDACTED);
+        }
+    }
+
+    let redacted = redact_after_markers(&redacted, &SECRET_NAMES, true);
+    let redacted = redact_after_markers(&redacted, &SECRET_URL_PARTS, false);
+    redact_base64_json(&redacted)
+}
+
+/// End of the secret value starting at `value_start`.
+fn find_value_end(message: &str, value_start: usize) -> usize {
+    match message[value_start..].find(|character: char| {
+        character.is_whitespace() || matches!(character, '"' | '\'' | ',' | ';' | '}' | ')')
+    }) {
+        Some(value_length) => value_start + value_length,
+        None => message.len(),
+    }
+}
+
+/// Start of the value after a secret name: skips quotes, requires `:` or `=`.
+fn find_named_value_start(message: &[u8], name_end: usize) -> Option<usize> {
+    let is_quote_or_space = |byte: &u8| matches!(byte, b'"' | b'\'' | b' ');
+    let mut index = name_end;
+    while index < message.len() && is_quote_or_space(&message[index]) {
+        index += 1;
+    }
+    if index >= message.len() || !matches!(message[index], b':' | b'=') {
+        return None;
+    }
+    index += 1;
+    while index < message.len() && is_quote_or_space(&message[index]) {
+        index += 1;
+    }
+    Some(index)
+}
+
+/// Mask the values following any of the lowercase markers.
+/// With `needs_separator`, the marker is a name and the value follows a `:` or `=`.
+fn redact_after_markers(message: &str, markers: &[&str], needs_separator: bool) -> String {
+    // ASCII lowercase keeps the byte positions of the message.
+    let lowercase_message = message.to_ascii_lowercase();
+    let mut redacted = String::with_capacity(message.len());
+    let mut position = 0;
+
+    loop {
+        let marker_end = markers
+            .iter()
+            .filter_map(|marker| {
+                lowercase_message[position..]
+                    .find(marker)
+                    .map(|marker_start| position + marker_start + marker.len())
+            })
+            .min();
+        let marker_end = match marker_end {
+            Some(marker_end) => marker_end,
+            None => break,
+        };
+
+        let value_start = if needs_separator {
+            find_named_value_start(message.as_bytes(), marker_end)
+        } else {
+            Some(marker_end)
+        };
+        match value_start {
+            Some(value_start) if find_value_end(message, value_start) > value_start => {
+                redacted.push_str(&message[position..value_start]);
+                redacted.push_str(REDACTED);
+                position = find_value_end(message, value_start);
+            }
+            _ => {
+                redacted.push_str(&message[position..marker_end]);
+                position = marker_end;
+            }
+        }
+    }
+
+    redacted.push_str(&message[position..]);
+    redacted
+}
+
+fn is_base64_character(character: char) -> bool {
+    character.is_ascii_alphanumeric() || matches!(character, '-' | '_' | '+' | '/' | '=')
+}
+
+/// Mask base64 encoded JSON, such as device tokens.
+fn redact_base64_json(message: &str) -> String {
+    let mut redacted = String::with_capacity(message.len());
+    let mut position = 0;
+
+    while let Some(prefix_offset) = message[position..].find(BASE64_JSON_PREFIX) {
+        let token_start = position + prefix_offset;
+        let token_end = message[token_start..]
+            .find(|character: char| !is_base64_character(character))
+            .map(|token_length| token_start + token_length)
+            .unwrap_or(message.len());
+        // Not the end of a longer word, eg. in a hash.
+        let starts_a_word = !message[..token_start]
+            .chars()
+            .next_back()
+            .map(|character| character.is_ascii_alphanumeric() || matches!(character, '-' | '_'))
+            .unwrap_or(false);
+
+        if starts_a_word && token_end - token_start >= MIN_BASE64_JSON_LENGTH {
+            redacted.push_str(&message[position..token_start]);
+            redacted.push_str(REDACTED);
+        } else {
+            redacted.push_str(&message[position..token_end]);
+        }
+        position = token_end;
+    }
+
+    redacted.push_str(&message[position..]);
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRIVATE_KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+    const BOT_TOKEN: &str = "123456789:AAHdqTcvCH1vGWJxfSeofSAs0K5PALDsaw";
+    const DISCORD_WEBHOOK_URL: &str =
+        "https://discord.com/api/webhooks/1122334455/Xy-0aBcDeFgHiJkLmNoPqRsTuVwXyZ_12345";
+    const SMTP_PASSWORD: &str = "s3cret-smtp-pass";
+
+    fn assert_masked(redacted: &str, secret: &str) {
+        assert!(
+            !redacted.contains(secret),
+            "secret {} not masked in {}",
+            secret,
+            redacted
+        );
+        assert!(
+            redacted.contains(REDACTED),
+            "nothing masked in {}",
+            redacted
+        );
+    }
+
+    #[test]
+    fn masks_known_private_key_anywhere() {
+        let known_secrets = vec![PRIVATE_KEY.to_string()];
+        let redacted = redact(&format!("Created device {}.", PRIVATE_KEY), &known_secrets);
+        assert_eq!(redacted, "Created device [REDACTED].");
+    }
+
+    #[test]
+    fn masks_named_private_key_without_known_secrets() {
+        for message in [
+            format!(
+                "{{\"privateKey\":\"{}\",\"address\":\"xe_1\"}}",
+                PRIVATE_KEY
+            ),
+            format!("private_key = '{}'", PRIVATE_KEY),
+            format!("Private key: {}", PRIVATE_KEY),
+        ] {
+            let redacted = redact(&message, &[]);
+            assert_masked(&redacted, PRIVATE_KEY);
+        }
+        let redacted = redact(&format!("{{\"privateKey\":\"{}\"}}", PRIVATE_KEY), &[]);
+        assert_eq!(redacted, "{\"privateKey\":\"[REDACTED]\"}");
+    }
+
+    #[test]
+    fn masks_telegram_bot_token_in_url() {
+        let message = format!(
+            "POST https://api.telegram.org/bot{}/sendMessage failed.",
+            BOT_TOKEN
+        );
+        let redacted = redact(&message, &[]);
+        assert_masked(&redacted, BOT_TOKEN);
+        assert_masked(
+            &redact(&format!("bot_token = '{}'", BOT_TOKEN), &[]),
+            BOT_TOKEN,
+        );
+    }
+
+    #[test]
+    fn masks_telegram_bot_token_of_local_stand_in() {
+        let message = format!("POST http://127.0.0.1:8081/bot{}/sendMessage", BOT_TOKEN);
+        let redacted = redact(&message, &[BOT_TOKEN.to_string()]);
+        assert_masked(&redacted, BOT_TOKEN);
+    }
+
+    #[test]
+    fn masks_discord_webhook_secret() {
+        let redacted = redact(&format!("Sending to {}", DISCORD_WEBHOOK_URL), &[]);
+        assert_masked(&redacted, "1122334455/Xy-0aBcDeFgHiJkLmNoPqRsTuVwXyZ_12345");
+        assert!(redacted.starts_with("Sending to https://discord.com/api/webhooks/"));
+    }
+
+    #[test]
+    fn masks_smtp_password() {
+        assert_masked(
+            &redact(&format!("smtp_password = '{}'", SMTP_PASSWORD), &[]),
+            SMTP_PASSWORD,
+        );
+        assert_masked(
+            &redact(
+                &format!("SMTP login as me@example.com:{} failed", SMTP_PASSWORD),
+                &[SMTP_PASSWORD.to_string()],
+            ),
+            SMTP_PASSWORD,
+        );
+    }
+
+    #[test]
+    fn masks_device_token_with_wallet_and_stake() {
+        // Base64 of {"address":"xe_wallet","stake":"stake_id","privateKey":"..."}
+        let device_token =
+            "eyJhZGRyZXNzIjoieGVfd2FsbGV0Iiwic3Rha2UiOiJzdGFrZV9pZCIsInByaXZhdGVLZXkiOiIuLi4ifQ==";
+        let redacted = redact(&format!("device token {} created", device_token), &[]);
+        assert_eq!(redacted, "device token [REDACTED] created");
+    }
+
+    #[test]
+    fn keeps_ordinary_messages() {
+        let message =
+            "Device started. Password reset not needed, hash abceyJhZGRyZXNzIjoieGVfd2FsbGV0";
+        assert_eq!(redact(message, &["short".to_string()]), message);
+    }
+}
@@ -3,33 +3,46 @@
 // Disable
 #![allow(clippy::needless_late_init)]
 
-use tauri::{CustomMenuItem, SystemTray, SystemTrayEvent, SystemTrayMenu, SystemTrayMenuItem};
+use std::collections::BTreeMap;
 
 use check_requirements::{
-    check_edge::get_edge_index_url,
+    check_edge::{get_edge_index_url, get_edge_staking_url},
     pretty_check_string::{self, pretty_err_str},
 };
 use config::{
     create_default_config,
     getters::{
-        get_autostart_status, get_config_path_as_str, get_initialization_status,
-        get_last_node_payment, get_launch_minimized_status, get_network, get_node_address,
-        get_wallet_address,
+        get_config_path_as_str, get_initialization_status, get_last_node_payment,
+        get_launch_minimized_status, get_network, get_node_address, get_wallet_address,
     },
     setters::{
-        set_autostart_status, set_device_initialization_status, set_last_node_payment,
-        set_launch_minimized_status, set_wallet_address,
+        set_device_initialization_status, set_last_node_payment, set_launch_minimized_status,
+        set_wallet_address,
     },
 };
-use tauri::{Manager, Window};
+use logging::{get_log_path, log_error, log_info, log_warn};
+use tauri::{AppHandle, Manager, Window};
 use tauri_plugin_autostart::MacosLauncher;
-use utility::{get_log_path, log_and_emit};
 
+mod autostart;
 mod check_requirements;
+mod cli_versions;
 mod config;
 mod control_edge_cli;
 mod device;
 mod docker;
+mod launch_args;
+mod logging;
+mod network_switch;
+mod node_status;
+mod notifications;
+mod process_runner;
+mod shutdown;
+mod stake;
+mod startup;
+mod status_api;
+mod support_bundle;
+mod tray;
 mod utility;
 
 #[derive(Clone, serde::Serialize)]
@@ -51,6 +64,35 @@ pub struct BackendCommunicator {
     front_end_window: Window,
 }
 
+impl BackendCommunicator {
+    /// Create a communicator for backend tasks which are not started from the frontend.
+    /// Uses the same data dir as the frontend's appLocalDataDir.
+    pub fn from_app_handle(app_handle: &AppHandle) -> Result<BackendCommunicator, String> {
+        let front_end_window = match app_handle.get_window("main") {
+            Some(window) => window,
+            None => return Err("Main window not found.".to_string()),
+        };
+        let data_dir = match app_handle.path_resolver().app_local_data_dir() {
+            Some(dir) => dir,
+            None => return Err("Unable to resolve app data directory.".to_string()),
+        };
+        if let Err(err) = std::fs::create_dir_all(&data_dir) {
+            return Err(format!(
+                "Unable to create app data directory {}. Err: {}",
+                data_dir.display(),
+                err
+            ));
+        }
+
+        Ok(BackendCommunicator {
+            status_listener: String::from(STATUSLISTENER),
+            // Trailing separator, file names are appended directly.
+            data_dir: data_dir.join("").to_string_lossy().to_string(),
+            front_end_window,
+        })
+    }
+}
+
 #[tauri::command]
 async fn add_device_from_frontend(
     address: String,
@@ -71,6 +113,78 @@ async fn add_device_from_frontend(
     }
 }
 
+/// Run the selected system requirement checks, eg. `{ "memory": true, "clock": true }`.
+/// Returns the report of the checks.
+#[tauri::command]
+async fn check_requirements_from_frontend(
+    checks: check_requirements::RequirementChecks,
+    window: Window,
+    datadir: String,
+) -> Option<node_status::RequirementReport> {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    let _ = check_requirements::main(&checks, backend_communicator).await;
+    node_status::get_node_status().last_requirement_report
+}
+
+/// Check now whether the stake still backs the device.
+#[tauri::command]
+async fn check_stake_from_frontend(
+    window: Window,
+    datadir: String,
+) -> Option<stake::monitor::StakeCheckStatus> {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    match stake::monitor::check_stake(backend_communicator).await {
+        Ok(stake_check_status) => Some(stake_check_status),
+        Err(err) => {
+            log_warn!(err, backend_communicator);
+            None
+        }
+    }
+}
+
+/// Create a diagnostic zip for support requests. `outputpath` is a zip file or a directory.
+/// Returns the path of the created zip.
+#[tauri::command]
+async fn create_support_bundle_from_frontend(
+    outputpath: String,
+    window: Window,
+    datadir: String,
+) -> Result<String, String> {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    support_bundle::create_support_bundle(&outputpath, backend_communicator).await
+}
+
+/// Restart the device: stop, then start.
+#[tauri::command]
+async fn device_restart_from_frontend(
+    checklatestbinary: bool,
+    window: Window,
+    datadir: String,
+) -> control_edge_cli::DeviceOperationResult {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    control_edge_cli::device_restart(checklatestbinary, backend_communicator).await
+}
+
 #[tauri::command]
 async fn device_start_from_frontend(
     checklatestbinary: bool,
@@ -100,15 +214,99 @@ async fn device_stop_from_frontend(
     control_edge_cli::device_stop_from_frontend(checklatestbinary, backend_communicator).await
 }
 
+/// Update the device: pull the latest device image, then restart.
 #[tauri::command]
-fn get_autostart_status_from_frontend(window: Window, datadir: String) -> bool {
+async fn device_update_from_frontend(
+    checklatestbinary: bool,
+    window: Window,
+    datadir: String,
+) -> control_edge_cli::DeviceOperationResult {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    control_edge_cli::device_update(checklatestbinary, backend_communicator).await
+}
+
+/// Look up, validate & store the stake assigned to the device address.
+#[tauri::command]
+async fn discover_stake_from_frontend(
+    window: Window,
+    datadir: String,
+) -> Result<stake::StakeInfo, stake::StakeError> {
     let backend_communicator = &BackendCommunicator {
         status_listener: String::from(STATUSLISTENER),
         data_dir: datadir,
         front_end_window: window,
     };
 
-    get_autostart_status(backend_communicator)
+    stake::discover_and_set_stake(backend_communicator).await
+}
+
+/// Returns autostart according to the config and the launch at login setting of the OS.
+#[tauri::command]
+fn get_autostart_status_from_frontend(
+    window: Window,
+    datadir: String,
+) -> autostart::AutostartStatus {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    autostart::get_autostart(backend_communicator)
+}
+
+/// Stop a running Docker or Edge CLI process.
+#[tauri::command]
+fn cancel_process_from_frontend(processid: u64, window: Window, datadir: String) -> bool {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    process_runner::cancel_process(processid, backend_communicator).is_ok()
+}
+
+/// Check for a newer Edge CLI now, bypassing the cached checksum, and apply the update policy.
+#[tauri::command]
+async fn check_cli_update_from_frontend(
+    window: Window,
+    datadir: String,
+) -> Option<cli_versions::update_checker::CliUpdateStatus> {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    match cli_versions::update_checker::check_for_cli_update(backend_communicator).await {
+        Ok(cli_update_status) => {
+            match cli_versions::update_checker::apply_cli_update_policy(
+                &cli_update_status,
+                backend_communicator,
+            )
+            .await
+            {
+                Ok(ok_message) => log_info!(ok_message, backend_communicator),
+                Err(err_message) => log_error!(err_message, backend_communicator),
+            }
+            cli_versions::update_checker::get_last_cli_update_status()
+        }
+        Err(err_message) => {
+            log_error!(err_message, backend_communicator);
+            None
+        }
+    }
+}
+
+#[tauri::command]
+fn get_cli_update_status_from_frontend() -> Option<cli_versions::update_checker::CliUpdateStatus> {
+    cli_versions::update_checker::get_last_cli_update_status()
 }
 
 #[tauri::command]
@@ -122,6 +320,29 @@ fn get_config_location_from_frontend(window: Window, datadir: String) -> String
     get_config_path_as_str(backend_communicator)
 }
 
+/// Returns the most recent log entries, newest first.
+/// Filters on minimum level (eg. warn), target (eg. docker) and text, empty strings match all.
+#[tauri::command]
+fn get_log_entries_from_frontend(
+    minlevel: String,
+    target: String,
+    contains: String,
+    limit: usize,
+    window: Window,
+    datadir: String,
+) -> Vec<logging::LogEntry> {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    let min_level = minlevel
+        .parse::<logging::LogLevel>()
+        .unwrap_or(logging::LogLevel::Debug);
+    logging::get_log_entries(min_level, &target, &contains, limit, backend_communicator)
+}
+
 #[tauri::command]
 fn get_log_location_from_frontend(window: Window, datadir: String) -> String {
     let backend_communicator = &BackendCommunicator {
@@ -133,6 +354,17 @@ fn get_log_location_from_frontend(window: Window, datadir: String) -> String {
     get_log_path(backend_communicator)
 }
 
+#[tauri::command]
+fn get_logging_config_from_frontend(window: Window, datadir: String) -> config::LoggingConfig {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    config::getters::get_logging_config(backend_communicator)
+}
+
 /// Returns true if initialization is complete, false if not.
 #[tauri::command]
 fn get_device_initialization_status_from_frontend(window: Window, datadir: String) -> bool {
@@ -148,6 +380,46 @@ fn get_device_initialization_status_from_frontend(window: Window, datadir: Strin
     initialization_status == 0
 }
 
+#[tauri::command]
+async fn get_device_info_from_frontend(
+    window: Window,
+    datadir: String,
+) -> Result<control_edge_cli::cli_output::DeviceInfo, control_edge_cli::cli_output::EdgeCliError> {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    control_edge_cli::get_device_info(backend_communicator).await
+}
+
+#[tauri::command]
+async fn get_device_status_from_frontend(
+    window: Window,
+    datadir: String,
+) -> Result<control_edge_cli::cli_output::DeviceStatus, control_edge_cli::cli_output::EdgeCliError>
+{
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    control_edge_cli::get_device_status(backend_communicator).await
+}
+
+#[tauri::command]
+fn get_download_config_from_frontend(window: Window, datadir: String) -> config::DownloadConfig {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    config::getters::get_download_config(backend_communicator)
+}
+
 #[tauri::command]
 fn get_edge_cli_download_url_from_frontend(window: Window, datadir: String) -> String {
     let backend_communicator = &BackendCommunicator {
@@ -159,6 +431,23 @@ fn get_edge_cli_download_url_from_frontend(window: Window, datadir: String) -> S
     check_requirements::check_edge::get_edge_cli_download_url_from_frontend(backend_communicator)
 }
 
+#[tauri::command]
+async fn get_edge_cli_version_from_frontend(
+    window: Window,
+    datadir: String,
+) -> Result<control_edge_cli::cli_output::EdgeCliVersion, control_edge_cli::cli_output::EdgeCliError>
+{
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    control_edge_cli::get_edge_cli_version(backend_communicator)
+        .await
+        .map(|version_output| control_edge_cli::cli_output::parse_edge_cli_version(&version_output))
+}
+
 #[tauri::command]
 fn get_index_url_from_frontend(window: Window, datadir: String) -> String {
     let backend_communicator = &BackendCommunicator {
@@ -181,6 +470,11 @@ fn get_launch_minimized_status_from_frontend(window: Window, datadir: String) ->
     get_launch_minimized_status(backend_communicator)
 }
 
+#[tauri::command]
+fn get_first_launch_minimized_from_frontend() -> Option<bool> {
+    launch_args::get_first_launch_minimized()
+}
+
 #[tauri::command]
 fn get_last_node_payment_from_frontend(window: Window, datadir: String) -> u64 {
     let backend_communicator = &BackendCommunicator {
@@ -192,6 +486,20 @@ fn get_last_node_payment_from_frontend(window: Window, datadir: String) -> u64 {
     get_last_node_payment(backend_communicator)
 }
 
+#[tauri::command]
+fn get_network_definitions_from_frontend(
+    window: Window,
+    datadir: String,
+) -> BTreeMap<String, config::NetworkDefinition> {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    config::getters::get_network_definitions(backend_communicator)
+}
+
 #[tauri::command]
 fn get_network_from_frontend(window: Window, datadir: String) -> String {
     let backend_communicator = &BackendCommunicator {
@@ -200,7 +508,7 @@ fn get_network_from_frontend(window: Window, datadir: String) -> String {
         front_end_window: window,
     };
 
-    get_network(backend_communicator)
+    get_network(backend_communicator).to_string()
 }
 
 #[tauri::command]
@@ -216,6 +524,25 @@ fn get_node_address_from_frontend(window: Window, datadir: String) -> String {
     get_node_address(backend_communicator)
 }
 
+#[tauri::command]
+fn get_notification_config_from_frontend(
+    window: Window,
+    datadir: String,
+) -> config::NotificationConfig {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    config::getters::get_notification_config(backend_communicator)
+}
+
+#[tauri::command]
+fn get_stake_check_status_from_frontend() -> Option<stake::monitor::StakeCheckStatus> {
+    stake::monitor::get_last_stake_check_status()
+}
+
 #[tauri::command]
 fn get_stake_id_from_frontend(window: Window, datadir: String) -> String {
     let backend_communicator = &BackendCommunicator {
@@ -227,6 +554,34 @@ fn get_stake_id_from_frontend(window: Window, datadir: String) -> String {
     config::getters::get_stake_id(backend_communicator)
 }
 
+#[tauri::command]
+fn get_staking_url_from_frontend(window: Window, datadir: String) -> String {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    get_edge_staking_url(backend_communicator).unwrap_or_else(|_| String::from("ERR, see log"))
+}
+
+/// Phase of starting the node at launch, None if the startup did not run yet.
+#[tauri::command]
+fn get_startup_status_from_frontend() -> Option<startup::StartupStatus> {
+    startup::get_startup_status()
+}
+
+#[tauri::command]
+fn get_status_api_config_from_frontend(window: Window, datadir: String) -> config::StatusApiConfig {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    config::getters::get_status_api_config(backend_communicator)
+}
+
 #[tauri::command]
 fn get_wallet_address_from_frontend(window: Window, datadir: String) -> String {
     let backend_communicator = &BackendCommunicator {
@@ -249,6 +604,45 @@ async fn install_edge_cli_from_frontend(window: Window, datadir: String) -> bool
     check_requirements::check_edge::get_edge_cli_binary(backend_communicator).await
 }
 
+/// Install the Edge CLI from a local binary, verified with a checksum (or checksum file path).
+#[tauri::command]
+async fn install_edge_cli_from_file_from_frontend(
+    binarypath: String,
+    checksum: String,
+    window: Window,
+    datadir: String,
+) -> bool {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    cli_versions::install_edge_cli_from_file(binarypath, checksum, backend_communicator)
+        .await
+        .is_ok()
+}
+
+/// Returns the archived Edge CLI binaries of the current network, newest first.
+#[tauri::command]
+fn list_edge_cli_versions_from_frontend(
+    window: Window,
+    datadir: String,
+) -> Vec<cli_versions::ArchivedEdgeCli> {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    cli_versions::list_archived_edge_clis(backend_communicator)
+}
+
+#[tauri::command]
+fn list_running_processes_from_frontend() -> Vec<process_runner::RunningProcess> {
+    process_runner::list_running_processes()
+}
+
 #[tauri::command]
 fn log_and_emit_from_frontend(message: String, window: Window, datadir: String) {
     // Send message from frontend to backend,
@@ -258,22 +652,101 @@ fn log_and_emit_from_frontend(message: String, window: Window, datadir: String)
         front_end_window: window,
     };
 
-    log_and_emit(message, backend_communicator);
+    logging::log(
+        logging::LogLevel::Info,
+        "frontend",
+        message,
+        backend_communicator,
+    );
 }
 
+/// Send a node event detected in the frontend (eg. earnings) to the notification channels.
+#[tauri::command]
+async fn notify_node_event_from_frontend(
+    event: notifications::NodeEvent,
+    title: String,
+    message: String,
+    window: Window,
+    datadir: String,
+) -> bool {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    notifications::notify(event, &title, &message, backend_communicator)
+        .await
+        .is_ok()
+}
+
+/// Count received earnings (in micro XE) for the status API metrics.
+#[tauri::command]
+fn record_earnings_from_frontend(amount: u64, islottery: bool) {
+    node_status::record_earnings(amount, islottery);
+}
+
+/// Switch to a previously installed Edge CLI binary by its archive id.
+#[tauri::command]
+async fn rollback_edge_cli_from_frontend(id: String, window: Window, datadir: String) -> bool {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    cli_versions::rollback_edge_cli(id, backend_communicator)
+        .await
+        .is_ok()
+}
+
+/// Run device start, stop or restart and return the parsed CLI output.
+#[tauri::command]
+async fn run_device_command_from_frontend(
+    command: String,
+    checklatestbinary: bool,
+    window: Window,
+    datadir: String,
+) -> Result<
+    control_edge_cli::cli_output::DeviceCommandResult,
+    control_edge_cli::cli_output::EdgeCliError,
+> {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    control_edge_cli::run_device_command(&command, checklatestbinary, backend_communicator).await
+}
+
+#[tauri::command]
+async fn send_test_notification_from_frontend(window: Window, datadir: String) -> bool {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    notifications::send_test_notification(backend_communicator)
+        .await
+        .is_ok()
+}
+
+/// Turn autostart on or off, in the config and as launch at login setting of the OS.
 #[tauri::command]
 fn set_autostart_status_from_frontend(
     autostartstatus: bool,
     window: Window,
     datadir: String,
-) -> bool {
+) -> autostart::AutostartStatus {
     let backend_communicator = &BackendCommunicator {
         status_listener: String::from(STATUSLISTENER),
         data_dir: datadir,
         front_end_window: window,
     };
 
-    set_autostart_status(autostartstatus, backend_communicator).is_ok()
+    autostart::set_autostart(autostartstatus, backend_communicator)
 }
 
 /// Returns true if initialization is complete, false if not.
@@ -289,7 +762,7 @@ fn set_device_fully_initialized_from_frontend(window: Window, datadir: String) -
         Ok(_) => {
             let ok_message = "Congratulations! Your device has fully started! You can now close the Staking GUI.".to_string();
             let ok_message_pretty = pretty_check_string::pretty_ok_str(&ok_message, false);
-            log_and_emit(ok_message_pretty, backend_communicator);
+            log_info!(ok_message_pretty, backend_communicator);
             true
         }
         Err(err_str) => {
@@ -297,7 +770,7 @@ fn set_device_fully_initialized_from_frontend(window: Window, datadir: String) -
                 "Congratulations, your device is working! Yet was unable to set the initialization config value to true. On next restart, just click the start node button again. If the error keeps persisting, please contact support on the Edge Discord: https://ed.ge/discord. Error: {}", err_str
             );
             let err_message_pretty = pretty_err_str(&err_message, false);
-            log_and_emit(err_message_pretty, backend_communicator);
+            log_error!(err_message_pretty, backend_communicator);
             false
         }
     }
@@ -314,19 +787,34 @@ fn set_device_not_initialized_from_frontend(window: Window, datadir: String) ->
     match set_device_initialization_status(false, backend_communicator) {
         Ok(_) => {
             let ok_message = "Reset program to setup stage.".to_string();
-            log_and_emit(ok_message, backend_communicator);
+            log_info!(ok_message, backend_communicator);
             true
         }
         Err(err) => {
             let err_message = format!(
                 "Unable to reset program to setup stage. Try pressing the button again and reloading. If the problem persists, contact support on the Edge Discord: https://ed.ge/discord. Error: {}", err
             );
-            log_and_emit(err_message, backend_communicator);
+            log_error!(err_message, backend_communicator);
             false
         }
     }
 }
 
+#[tauri::command]
+fn set_download_config_from_frontend(
+    downloadconfig: config::DownloadConfig,
+    window: Window,
+    datadir: String,
+) -> bool {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    config::setters::set_download_config(downloadconfig, backend_communicator).is_ok()
+}
+
 #[tauri::command]
 fn set_last_node_payment_from_frontend(
     lastnodepayment: u64,
@@ -339,7 +827,9 @@ fn set_last_node_payment_from_frontend(
         front_end_window: window,
     };
 
-    set_last_node_payment(lastnodepayment, backend_communicator).is_ok()
+    let set_result = set_last_node_payment(lastnodepayment, backend_communicator);
+    tray::request_tray_refresh();
+    set_result.is_ok()
 }
 
 #[tauri::command]
@@ -358,25 +848,72 @@ fn set_launch_minimized_status_from_frontend(
 }
 
 #[tauri::command]
-fn set_network_from_frontend(network: String, window: Window, datadir: String) -> bool {
+fn set_logging_config_from_frontend(
+    loggingconfig: config::LoggingConfig,
+    window: Window,
+    datadir: String,
+) -> bool {
     let backend_communicator = &BackendCommunicator {
         status_listener: String::from(STATUSLISTENER),
         data_dir: datadir,
         front_end_window: window,
     };
 
-    config::setters::set_network(network, backend_communicator).is_ok()
+    config::setters::set_logging_config(loggingconfig, backend_communicator).is_ok()
 }
 
 #[tauri::command]
-fn set_stake_id_from_frontend(stake: String, window: Window, datadir: String) -> bool {
+async fn set_node_online_status_from_frontend(online: bool, window: Window, datadir: String) {
     let backend_communicator = &BackendCommunicator {
         status_listener: String::from(STATUSLISTENER),
         data_dir: datadir,
         front_end_window: window,
     };
 
-    config::setters::set_stake_id(stake, backend_communicator).is_ok()
+    notifications::set_node_online_status(online, backend_communicator).await;
+}
+
+#[tauri::command]
+fn set_notification_config_from_frontend(
+    notificationconfig: config::NotificationConfig,
+    window: Window,
+    datadir: String,
+) -> bool {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    config::setters::set_notification_config(notificationconfig, backend_communicator).is_ok()
+}
+
+#[tauri::command]
+fn set_status_api_config_from_frontend(
+    statusapiconfig: config::StatusApiConfig,
+    window: Window,
+    datadir: String,
+) -> bool {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    config::setters::set_status_api_config(statusapiconfig, backend_communicator).is_ok()
+}
+
+/// Pin the Edge CLI version of the current network. Use "latest" to unpin.
+#[tauri::command]
+fn set_pinned_cli_version_from_frontend(version: String, window: Window, datadir: String) -> bool {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    let network = get_network(backend_communicator).to_string();
+    config::setters::set_pinned_cli_version(network, version, backend_communicator).is_ok()
 }
 
 #[tauri::command]
@@ -404,6 +941,22 @@ fn reset_config_from_frontend(window: Window, datadir: String) -> bool {
     create_default_config(backend_communicator).is_ok()
 }
 
+/// Switch to another network, keeping the device of the current network for switching back.
+#[tauri::command]
+async fn switch_network_from_frontend(
+    network: String,
+    window: Window,
+    datadir: String,
+) -> control_edge_cli::DeviceOperationResult {
+    let backend_communicator = &BackendCommunicator {
+        status_listener: String::from(STATUSLISTENER),
+        data_dir: datadir,
+        front_end_window: window,
+    };
+
+    network_switch::switch_network(&network, backend_communicator).await
+}
+
 #[tauri::command]
 async fn update_edge_cli_from_frontend(window: Window, datadir: String) -> bool {
     let backend_communicator = &BackendCommunicator {
@@ -416,14 +969,6 @@ async fn update_edge_cli_from_frontend(window: Window, datadir: String) -> bool
 }
 
 fn main() {
-    let show = CustomMenuItem::new("show".to_string(), "Show");
-    let quit = CustomMenuItem::new("quit".to_string(), "Quit");
-    let tray_menu = SystemTrayMenu::new()
-        .add_item(show)
-        .add_item(quit)
-        .add_native_item(SystemTrayMenuItem::Separator);
-    let tray = SystemTray::new().with_menu(tray_menu);
-
     // Fix PATH on MacOS & Linux https://tauri.app/v1/guides/building/macos/
     // It's an official plugin and other plugins also use .unwrap
     // TODO: Figure out how to add logging
@@ -433,73 +978,109 @@ fn main() {
     tauri::Builder::default()
         .invoke_handler(tauri::generate_handler![
             add_device_from_frontend,
+            cancel_process_from_frontend,
+            check_cli_update_from_frontend,
+            check_requirements_from_frontend,
+            check_stake_from_frontend,
+            create_support_bundle_from_frontend,
+            device_restart_from_frontend,
             device_start_from_frontend,
             device_stop_from_frontend,
+            device_update_from_frontend,
+            discover_stake_from_frontend,
             get_autostart_status_from_frontend,
+            get_cli_update_status_from_frontend,
             get_config_location_from_frontend,
+            get_device_info_from_frontend,
             get_device_initialization_status_from_frontend,
+            get_device_status_from_frontend,
+            get_download_config_from_frontend,
             get_edge_cli_download_url_from_frontend,
+            get_edge_cli_version_from_frontend,
+            get_first_launch_minimized_from_frontend,
             get_index_url_from_frontend,
             get_launch_minimized_status_from_frontend,
             get_last_node_payment_from_frontend,
+            get_log_entries_from_frontend,
             get_log_location_from_frontend,
+            get_logging_config_from_frontend,
+            get_network_definitions_from_frontend,
             get_network_from_frontend,
             get_node_address_from_frontend,
+            get_notification_config_from_frontend,
+            get_stake_check_status_from_frontend,
             get_stake_id_from_frontend,
+            get_staking_url_from_frontend,
+            get_startup_status_from_frontend,
+            get_status_api_config_from_frontend,
             get_wallet_address_from_frontend,
+            install_edge_cli_from_file_from_frontend,
             install_edge_cli_from_frontend,
+            list_edge_cli_versions_from_frontend,
+            list_running_processes_from_frontend,
             log_and_emit_from_frontend,
+            notify_node_event_from_frontend,
+            record_earnings_from_frontend,
+            rollback_edge_cli_from_frontend,
+            run_device_command_from_frontend,
+            send_test_notification_from_frontend,
             set_autostart_status_from_frontend,
             set_device_fully_initialized_from_frontend,
             set_device_not_initialized_from_frontend,
+            set_download_config_from_frontend,
             set_last_node_payment_from_frontend,
             set_launch_minimized_status_from_frontend,
-            set_network_from_frontend,
-            set_stake_id_from_frontend,
+            set_logging_config_from_frontend,
+            set_node_online_status_from_frontend,
+            set_notification_config_from_frontend,
+            set_pinned_cli_version_from_frontend,
+            set_status_api_config_from_frontend,
             set_wallet_address_from_frontend,
+            switch_network_from_frontend,
             reset_config_from_frontend,
             update_edge_cli_from_frontend,
         ])
-        .system_tray(tray)
+        .setup(|app| {
+            match BackendCommunicator::from_app_handle(&app.handle()) {
+                Ok(backend_communicator) => {
+                    match status_api::start_status_api(backend_communicator.clone()) {
+                        Ok(ok_message) => log_info!(ok_message, &backend_communicator),
+                        Err(err_message) => log_error!(err_message, &backend_communicator),
+                    }
+                    cli_versions::update_checker::start_cli_update_checker(
+                        backend_communicator.clone(),
+                    );
+                    stake::monitor::start_stake_monitor(backend_communicator.clone());
+                    autostart::reconcile_autostart(&backend_communicator);
+                    startup::start_startup_orchestrator(backend_communicator.clone());
+                    tray::start_tray_updater(backend_communicator.clone());
+                    launch_args::run_first_launch(&backend_communicator);
+                }
+                Err(err) => eprintln!("Unable to start backend services. Err: {}", err),
+            }
+            Ok(())
+        })
+        .system_tray(tray::create_system_tray())
         .on_window_event(|event| {
             if let tauri::WindowEvent::CloseRequested { api, .. } = event.event() {
                 event.window().hide().unwrap();
                 api.prevent_close();
             }
         })
-        .on_system_tray_event(|app, event| match event {
-            SystemTrayEvent::DoubleClick {
-                position: _,
-                size: _,
-                ..
-            } => {
-                println!("system tray received a double click");
-                let window = app.get_window("main").unwrap();
-                window.show().unwrap();
-                window.unminimize().unwrap();
-            }
-            SystemTrayEvent::MenuItemClick { id, .. } => match id.as_str() {
-                "show" => {
-                    let window = app.get_window("main").unwrap();
-                    window.show().unwrap();
-                    window.unminimize().unwrap();
-                }
-                "quit" => {
-                    std::process::exit(0);
-                }
-                _ => {}
-            },
-            _ => {}
-        })
+        .on_system_tray_event(tray::handle_system_tray_event)
         .plugin(tauri_plugin_autostart::init(
             MacosLauncher::LaunchAgent,
             Some(vec![]), /* arbitrary number of args to pass to your app */
         ))
         .plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
-            println!("{}, {argv:?}, {cwd}", app.package_info().name);
-
-            app.emit_all("single-instance", Payload { args: argv, cwd })
-                .unwrap();
+            let _ = app.emit_all(
+                "single-instance",
+                Payload {
+                    args: argv.clone(),
+                    cwd,
+                },
+            );
+            launch_args::run_second_launch(app, argv);
         }))
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
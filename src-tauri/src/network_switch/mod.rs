@@ -0,0 +1,243 @@
+use crate::check_requirements;
+use crate::check_requirements::check_edge::get_edge_cli_binary;
+use crate::check_requirements::RequirementChecks;
+use crate::config::getters::{get_network, get_network_definition};
+use crate::config::setters::switch_device_identity;
+use crate::config::Network;
+use crate::control_edge_cli::{start_step, stop_step, DeviceOperationResult};
+use crate::docker::{
+    clear_docker_volume, copy_docker_volume, docker_volume_exists, get_edge_device_container_state,
+    EDGE_DEVICE_DATA_VOLUME,
+};
+use crate::logging::log_info;
+use crate::BackendCommunicator;
+
+/// Volume in which the device data of a network is kept while another network is active.
+/// eg. edge-device-data-testnet
+fn get_network_device_data_volume(network: &Network) -> String {
+    format!("{}-{}", EDGE_DEVICE_DATA_VOLUME, network)
+}
+
+/// Stop the device if its container is running.
+async fn stop_running_device(backend_communicator: &BackendCommunicator) -> Result<String, String> {
+    let container_state = get_edge_device_container_state(backend_communicator).await?;
+    if container_state == "running" {
+        stop_step(false, backend_communicator).await
+    } else {
+        Ok("Device was not running.".to_string())
+    }
+}
+
+/// Keep the device data of the current network in its own volume.
+async fn backup_device_data(
+    network: &Network,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    if !docker_volume_exists(EDGE_DEVICE_DATA_VOLUME, backend_communicator).await? {
+        return Ok("No device data to keep.".to_string());
+    }
+    copy_docker_volume(
+        EDGE_DEVICE_DATA_VOLUME,
+        &get_network_device_data_volume(network),
+        backend_communicator,
+    )
+    .await
+}
+
+/// Put the kept device data of the target network back, or start with empty device data.
+async fn restore_device_data(
+    network: &Network,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let network_volume = get_network_device_data_volume(network);
+    if docker_volume_exists(&network_volume, backend_communicator).await? {
+        copy_docker_volume(
+            &network_volume,
+            EDGE_DEVICE_DATA_VOLUME,
+            backend_communicator,
+        )
+        .await
+    } else if docker_volume_exists(EDGE_DEVICE_DATA_VOLUME, backend_communicator).await? {
+        clear_docker_volume(EDGE_DEVICE_DATA_VOLUME, backend_communicator).await
+    } else {
+        Ok(format!("No device data kept for {}.", network))
+    }
+}
+
+/// Undo a network switch which failed after the config was switched: switch the config back,
+/// restore the device data of the previous network and start the device if it was running.
+/// The Edge CLI is only replaced once a download is verified, so the previous one is still there.
+async fn revert_network_switch(
+    previous_network: &Network,
+    device_was_running: bool,
+    mut device_operation_result: DeviceOperationResult,
+    backend_communicator: &BackendCommunicator,
+) -> DeviceOperationResult {
+    log_info!(
+        format!(
+            "Network switch failed, switching back to {}.",
+            previous_network
+        ),
+        backend_communicator,
+    );
+    let revert_result = match switch_device_identity(previous_network, backend_communicator) {
+        Ok(_) => restore_device_data(previous_network, backend_communicator)
+            .await
+            .map(|_| format!("Switched back to {}.", previous_network)),
+        Err(err) => Err(err),
+    };
+    if device_operation_result.record_step("revert", revert_result, backend_communicator)
+        && device_was_running
+    {
+        let start_result = start_step(false, backend_communicator).await;
+        device_operation_result.record_step("start", start_result, backend_communicator);
+    }
+    device_operation_result.finish(backend_communicator)
+}
+
+/// Switch the device to another network.
+/// Stops the device, keeps the device data & config of the current network, restores those of
+/// the target network, installs the Edge CLI of the target network and starts the device if it
+/// was set up on the target network before. If restoring the data or installing the Edge CLI
+/// fails, the device is switched back to the current network.
+pub async fn switch_network(
+    network: &str,
+    backend_communicator: &BackendCommunicator,
+) -> DeviceOperationResult {
+    let mut device_operation_result = DeviceOperationResult::new("network switch");
+    let current_network = get_network(backend_communicator);
+    let network = match network
+        .parse::<Network>()
+        .and_then(|network| get_network_definition(&network, backend_communicator).map(|_| network))
+    {
+        Ok(network) => network,
+        Err(err) => {
+            device_operation_result.record_step("validate", Err(err), backend_communicator);
+            return device_operation_result.finish(backend_communicator);
+        }
+    };
+
+    log_info!(
+        format!(
+            "Network switch step 1/7: Checking switch from {} to {}.",
+            current_network, network
+        ),
+        backend_communicator,
+    );
+    if current_network == network {
+        device_operation_result.record_step(
+            "validate",
+            Ok(format!("Already on {}, nothing to switch.", network)),
+            backend_communicator,
+        );
+        return device_operation_result.finish(backend_communicator);
+    }
+    // Docker is needed to stop the device and move its data.
+    let validate_result =
+        check_requirements::main(&RequirementChecks::docker_only(), backend_communicator).await;
+    if !device_operation_result.record_step("validate", validate_result, backend_communicator) {
+        return device_operation_result.finish(backend_communicator);
+    }
+
+    let device_was_running = matches!(
+        get_edge_device_container_state(backend_communicator)
+            .await
+            .as_deref(),
+        Ok("running")
+    );
+    log_info!(
+        "Network switch step 2/7: Stopping device.".to_string(),
+        backend_communicator,
+    );
+    let stop_result = stop_running_device(backend_communicator).await;
+    if !device_operation_result.record_step("stop", stop_result, backend_communicator) {
+        return device_operation_result.finish(backend_communicator);
+    }
+
+    log_info!(
+        format!(
+            "Network switch step 3/7: Keeping device data of {}.",
+            current_network
+        ),
+        backend_communicator,
+    );
+    let backup_result = backup_device_data(&current_network, backend_communicator).await;
+    if !device_operation_result.record_step("backup", backup_result, backend_communicator) {
+        return device_operation_result.finish(backend_communicator);
+    }
+
+    log_info!(
+        format!("Network switch step 4/7: Switching config to {}.", network),
+        backend_communicator,
+    );
+    let target_identity = match switch_device_identity(&network, backend_communicator) {
+        Ok(target_identity) => {
+            device_operation_result.record_step(
+                "config",
+                Ok(format!("Switched config to {}.", network)),
+                backend_communicator,
+            );
+            target_identity
+        }
+        Err(err) => {
+            device_operation_result.record_step("config", Err(err), backend_communicator);
+            return device_operation_result.finish(backend_communicator);
+        }
+    };
+
+    log_info!(
+        format!(
+            "Network switch step 5/7: Restoring device data of {}.",
+            network
+        ),
+        backend_communicator,
+    );
+    let restore_result = restore_device_data(&network, backend_communicator).await;
+    if !device_operation_result.record_step("restore", restore_result, backend_communicator) {
+        return revert_network_switch(
+            &current_network,
+            device_was_running,
+            device_operation_result,
+            backend_communicator,
+        )
+        .await;
+    }
+
+    log_info!(
+        format!(
+            "Network switch step 6/7: Installing Edge CLI for {}.",
+            network
+        ),
+        backend_communicator,
+    );
+    let binary_result = if get_edge_cli_binary(backend_communicator).await {
+        Ok(format!("Edge CLI for {} installed.", network))
+    } else {
+        Err(format!("Unable to install Edge CLI for {}.", network))
+    };
+    if !device_operation_result.record_step("binary", binary_result, backend_communicator) {
+        return revert_network_switch(
+            &current_network,
+            device_was_running,
+            device_operation_result,
+            backend_communicator,
+        )
+        .await;
+    }
+
+    log_info!(
+        "Network switch step 7/7: Starting device.".to_string(),
+        backend_communicator,
+    );
+    let start_result = if target_identity.initialized {
+        start_step(false, backend_communicator).await
+    } else {
+        Ok(format!(
+            "No device set up on {} yet. Complete the setup to start the device.",
+            network
+        ))
+    };
+    device_operation_result.record_step("start", start_result, backend_communicator);
+
+    device_operation_result.finish(backend_communicator)
+}
@@ -0,0 +1,74 @@
+use serde::Serialize;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::docker::docker_info::DockerInfo;
+
+/// Result of a single system requirement check, eg. Docker.
+#[derive(Debug, Serialize, Clone)]
+pub struct RequirementCheckResult {
+    pub name: String, // eg. docker, matches the metrics label
+    pub passed: bool,
+    pub message: String,
+}
+
+/// Structured result of the last `check_requirements::main` run.
+#[derive(Debug, Serialize, Clone)]
+pub struct RequirementReport {
+    pub checked_at: i64, // Unix timestamp in seconds
+    pub passed: bool,
+    pub checks: Vec<RequirementCheckResult>,
+    pub docker: Option<DockerInfo>, // Parsed docker info with findings, None if Docker was not checked or not reachable.
+}
+
+/// In-memory node status shared between the frontend commands and background services.
+/// Resets when the program restarts.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct NodeStatus {
+    pub online: Option<bool>, // Last online status according to the index, None if unknown.
+    pub device_starts_total: u64, // How often the device was (re)started by the GUI.
+    pub node_earnings_total: u64, // Node earnings seen since launch, in micro XE.
+    pub lottery_earnings_total: u64, // Lottery winnings seen since launch, in micro XE.
+    pub last_requirement_report: Option<RequirementReport>,
+}
+
+static NODE_STATUS: Mutex<Option<NodeStatus>> = Mutex::new(None);
+
+/// Run a closure with mutable access to the node status.
+fn with_node_status<T>(f: impl FnOnce(&mut NodeStatus) -> T) -> T {
+    let mut guard: MutexGuard<Option<NodeStatus>> = match NODE_STATUS.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    f(guard.get_or_insert_with(NodeStatus::default))
+}
+
+/// Returns a copy of the current node status.
+pub fn get_node_status() -> NodeStatus {
+    with_node_status(|node_status| node_status.clone())
+}
+
+/// Set the online status, returns the previous online status.
+pub fn set_online(online: bool) -> Option<bool> {
+    with_node_status(|node_status| node_status.online.replace(online))
+}
+
+/// Count a successful device (re)start.
+pub fn record_device_start() {
+    with_node_status(|node_status| node_status.device_starts_total += 1);
+}
+
+/// Add received earnings, in micro XE.
+pub fn record_earnings(amount: u64, is_lottery: bool) {
+    with_node_status(|node_status| {
+        if is_lottery {
+            node_status.lottery_earnings_total += amount;
+        } else {
+            node_status.node_earnings_total += amount;
+        }
+    });
+}
+
+/// Store the result of the latest requirement check.
+pub fn set_last_requirement_report(requirement_report: RequirementReport) {
+    with_node_status(|node_status| node_status.last_requirement_report = Some(requirement_report));
+}
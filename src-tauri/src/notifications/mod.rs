@@ -0,0 +1,504 @@
+use chrono::{DateTime, Utc};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tauri::api::notification::Notification;
+use tauri::Manager;
+
+use crate::config::getters::{get_node_address, get_notification_config};
+use crate::config::{NotificationChannel, NotificationConfig};
+use crate::logging::{log_error, log_info};
+use crate::node_status;
+use crate::tray;
+use crate::BackendCommunicator;
+
+/// Node events which can be sent to the configured notification channels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeEvent {
+    NodeOffline,
+    NodeOnline,
+    EarningsReceived,
+    LotteryWon,
+    CliUpdateAvailable,
+    RequirementCheckFailed,
+    StakeChanged,
+}
+
+impl NodeEvent {
+    /// Returns whether the event is toggled on in the notification settings.
+    fn is_enabled(&self, notification_config: &NotificationConfig) -> bool {
+        match self {
+            NodeEvent::NodeOffline => notification_config.node_offline,
+            NodeEvent::NodeOnline => notification_config.node_online,
+            NodeEvent::EarningsReceived => notification_config.earnings_received,
+            NodeEvent::LotteryWon => notification_config.lottery_won,
+            NodeEvent::CliUpdateAvailable => notification_config.cli_update_available,
+            NodeEvent::RequirementCheckFailed => notification_config.requirement_check_failed,
+            NodeEvent::StakeChanged => notification_config.stake_changed,
+        }
+    }
+}
+
+/// JSON body sent to generic webhooks.
+#[derive(Clone, Serialize)]
+struct WebhookPayload {
+    event: NodeEvent,
+    title: String,
+    message: String,
+    node_address: String,
+    timestamp: i64,
+}
+
+/// Last time a notification was sent per event, used for rate limiting.
+fn last_sent_per_event() -> &'static Mutex<HashMap<NodeEvent, DateTime<Utc>>> {
+    static LAST_SENT: OnceLock<Mutex<HashMap<NodeEvent, DateTime<Utc>>>> = OnceLock::new();
+    LAST_SENT.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Returns true if the event was sent less than `rate_limit_seconds` ago.
+fn is_rate_limited(event: NodeEvent, rate_limit_seconds: u64) -> bool {
+    let last_sent = match last_sent_per_event().lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+
+    match last_sent.get(&event) {
+        Some(last_sent_at) => {
+            let seconds_since_last = Utc::now()
+                .signed_duration_since(*last_sent_at)
+                .num_seconds();
+            seconds_since_last >= 0 && (seconds_since_last as u64) < rate_limit_seconds
+        }
+        None => false,
+    }
+}
+
+/// Start the rate limit of the event, called once it was delivered.
+fn mark_sent(event: NodeEvent) {
+    let mut last_sent = match last_sent_per_event().lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    last_sent.insert(event, Utc::now());
+}
+
+/// Send a node event to all configured channels, respecting per-event toggles and rate limiting.
+/// Returns Ok when the event was delivered, skipped or there are no channels configured.
+pub async fn notify(
+    event: NodeEvent,
+    title: &str,
+    message: &str,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let notification_config = get_notification_config(backend_communicator);
+
+    if notification_config.channels.is_empty() {
+        return Ok("No notification channels configured.".to_string());
+    }
+
+    if !event.is_enabled(&notification_config) {
+        return Ok(format!("Notifications for {:?} are disabled.", event));
+    }
+
+    if is_rate_limited(event, notification_config.rate_limit_seconds) {
+        let ok_message = format!("Skipped {:?} notification due to rate limiting.", event);
+        log_info!(ok_message.clone(), backend_communicator);
+        return Ok(ok_message);
+    }
+
+    // A failed delivery does not count towards the rate limit, so the next attempt is sent.
+    let send_result = send_to_channels(
+        event,
+        title,
+        message,
+        &notification_config.channels,
+        backend_communicator,
+    )
+    .await;
+    if send_result.is_ok() {
+        mark_sent(event);
+    }
+    send_result
+}
+
+/// Show a desktop notification, also while the window is hidden.
+pub fn show_desktop_notification(
+    title: &str,
+    body: &str,
+    backend_communicator: &BackendCommunicator,
+) {
+    let identifier = backend_communicator
+        .front_end_window
+        .app_handle()
+        .config()
+        .tauri
+        .bundle
+        .identifier
+        .clone();
+    if let Err(err) = Notification::new(identifier).title(title).body(body).show() {
+        log_error!(
+            format!("Unable to show desktop notification. Err: {}", err),
+            backend_communicator,
+        );
+    }
+}
+
+/// Send a test message to every configured channel, ignoring toggles and rate limiting.
+pub async fn send_test_notification(
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let notification_config = get_notification_config(backend_communicator);
+    if notification_config.channels.is_empty() {
+        let err_message = "No notification channels configured.".to_string();
+        log_error!(err_message.clone(), backend_communicator);
+        return Err(err_message);
+    }
+
+    send_to_channels(
+        NodeEvent::NodeOnline,
+        "Edge Staking GUI test notification",
+        "If you can read this, notifications for your Edge node are working.",
+        &notification_config.channels,
+        backend_communicator,
+    )
+    .await
+}
+
+/// Record the node online status reported by the index.
+/// Notifies on every offline report (rate limited) and when the node comes back online.
+pub async fn set_node_online_status(online: bool, backend_communicator: &BackendCommunicator) {
+    let previous_online_status = node_status::set_online(online);
+    tray::request_tray_refresh();
+
+    let notify_result = if !online {
+        notify(
+            NodeEvent::NodeOffline,
+            "Node not online.",
+            "Your Edge node is not currently online according to index. Double check if your node is started.",
+            backend_communicator,
+        )
+        .await
+    } else if previous_online_status == Some(false) {
+        notify(
+            NodeEvent::NodeOnline,
+            "Node back online.",
+            "Your Edge node is online again according to index.",
+            backend_communicator,
+        )
+        .await
+    } else {
+        return;
+    };
+
+    if let Err(err) = notify_result {
+        log_error!(err, backend_communicator);
+    }
+}
+
+/// Deliver a message to every channel. Errors of individual channels are collected.
+async fn send_to_channels(
+    event: NodeEvent,
+    title: &str,
+    message: &str,
+    channels: &[NotificationChannel],
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let client;
+    match Client::builder().timeout(Duration::from_secs(30)).build() {
+        Ok(ok_client) => client = ok_client,
+        Err(err) => {
+            let err_message = format!("Unable to create notification http client. Err: {}", err);
+            log_error!(err_message.clone(), backend_communicator);
+            return Err(err_message);
+        }
+    }
+
+    let node_address = get_node_address(backend_communicator);
+    let mut failed_channels: Vec<String> = vec![];
+    for channel in channels {
+        let send_result =
+            send_to_channel(&client, event, title, message, channel, &node_address).await;
+        match send_result {
+            Ok(_) => {
+                let ok_message = format!(
+                    "Sent {:?} notification via {} channel.",
+                    event,
+                    channel_name(channel)
+                );
+                log_info!(ok_message, backend_communicator);
+            }
+            Err(err) => {
+                let err_message = format!(
+                    "Unable to send {:?} notification via {} channel. Err: {}",
+                    event,
+                    channel_name(channel),
+                    err
+                );
+                log_error!(err_message.clone(), backend_communicator);
+                failed_channels.push(err_message);
+            }
+        }
+    }
+
+    if failed_channels.is_empty() {
+        Ok(format!("Sent {:?} notification.", event))
+    } else {
+        Err(failed_channels.join(" | "))
+    }
+}
+
+/// Deliver a message via a single channel.
+async fn send_to_channel(
+    client: &Client,
+    event: NodeEvent,
+    title: &str,
+    message: &str,
+    channel: &NotificationChannel,
+    node_address: &str,
+) -> Result<(), String> {
+    match channel {
+        NotificationChannel::Webhook { url } => {
+            let payload = WebhookPayload {
+                event,
+                title: title.to_string(),
+                message: message.to_string(),
+                node_address: node_address.to_string(),
+                timestamp: Utc::now().timestamp(),
+            };
+            post_json(client, url, &payload).await
+        }
+        NotificationChannel::Discord { webhook_url } => {
+            let payload = serde_json::json!({
+                "content": format!("**{}**\n{}", title, message),
+            });
+            post_json(client, webhook_url, &payload).await
+        }
+        NotificationChannel::Telegram {
+            bot_token,
+            chat_id,
+            api_url,
+        } => {
+            let telegram_url = format!(
+                "{}/bot{}/sendMessage",
+                api_url.trim_end_matches('/'),
+                bot_token
+            );
+            let payload = serde_json::json!({
+                "chat_id": chat_id,
+                "text": format!("{}\n{}", title, message),
+            });
+            post_json(client, &telegram_url, &payload).await
+        }
+        NotificationChannel::Email {
+            smtp_host,
+            smtp_port,
+            smtp_tls,
+            smtp_username,
+            smtp_password,
+            from,
+            to,
+        } => {
+            send_email(
+                smtp_host,
+                *smtp_port,
+                *smtp_tls,
+                smtp_username,
+                smtp_password,
+                from,
+                to,
+                title,
+                message,
+            )
+            .await
+        }
+    }
+}
+
+/// Human readable channel name for logging.
+fn channel_name(channel: &NotificationChannel) -> &'static str {
+    match channel {
+        NotificationChannel::Webhook { .. } => "webhook",
+        NotificationChannel::Discord { .. } => "Discord",
+        NotificationChannel::Telegram { .. } => "Telegram",
+        NotificationChannel::Email { .. } => "email",
+    }
+}
+
+/// POST a JSON body and check for a successful status code.
+async fn post_json<T: Serialize>(client: &Client, url: &str, payload: &T) -> Result<(), String> {
+    match client.post(url).json(payload).send().await {
+        Ok(response) => {
+            if response.status().is_success() {
+                Ok(())
+            } else {
+                Err(format!("Received http status {}", response.status()))
+            }
+        }
+        // Urls can contain tokens (eg. Telegram), keep them out of the log.
+        Err(err) => Err(format!("Request failed: {}", err.without_url())),
+    }
+}
+
+/// Send a plain text email via SMTP.
+#[allow(clippy::too_many_arguments)]
+async fn send_email(
+    smtp_host: &str,
+    smtp_port: u16,
+    smtp_tls: bool,
+    smtp_username: &str,
+    smtp_password: &str,
+    from: &str,
+    to: &str,
+    title: &str,
+    message: &str,
+) -> Result<(), String> {
+    let from_mailbox = match from.parse() {
+        Ok(mailbox) => mailbox,
+        Err(err) => return Err(format!("Invalid from address {}. Err: {}", from, err)),
+    };
+    let to_mailbox = match to.parse() {
+        Ok(mailbox) => mailbox,
+        Err(err) => return Err(format!("Invalid to address {}. Err: {}", to, err)),
+    };
+
+    let email;
+    match Message::builder()
+        .from(from_mailbox)
+        .to(to_mailbox)
+        .subject(title)
+        .body(message.to_string())
+    {
+        Ok(ok_email) => email = ok_email,
+        Err(err) => return Err(format!("Unable to build email. Err: {}", err)),
+    }
+
+    let transport_builder = if smtp_tls {
+        match AsyncSmtpTransport::<Tokio1Executor>::relay(smtp_host) {
+            Ok(builder) => builder,
+            Err(err) => return Err(format!("Invalid SMTP host {}. Err: {}", smtp_host, err)),
+        }
+    } else {
+        AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(smtp_host)
+    };
+
+    let mut transport_builder = transport_builder.port(smtp_port);
+    if !smtp_username.is_empty() {
+        transport_builder = transport_builder.credentials(Credentials::new(
+            smtp_username.to_string(),
+            smtp_password.to_string(),
+        ));
+    }
+    let transport = transport_builder.build();
+
+    match transport.send(email).await {
+        Ok(_) => Ok(()),
+        Err(err) => Err(format!("SMTP error: {}", err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use tiny_http::{Response, Server};
+
+    const BOT_TOKEN: &str = "123456:ABCdefGhIJKlmnoPQRstuVWXyz";
+
+    /// Local stand-in for a notification api. Answers one request with `status_code` and
+    /// returns the url & body it received.
+    fn serve_once(status_code: u16) -> (String, thread::JoinHandle<(String, String)>) {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let api_url = format!("http://{}", server.server_addr().to_ip().unwrap());
+        let request_handle = thread::spawn(move || {
+            let mut request = server.recv().unwrap();
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body).unwrap();
+            let url = request.url().to_string();
+            request.respond(Response::empty(status_code)).unwrap();
+            (url, body)
+        });
+        (api_url, request_handle)
+    }
+
+    fn telegram_channel(api_url: &str) -> NotificationChannel {
+        NotificationChannel::Telegram {
+            bot_token: BOT_TOKEN.to_string(),
+            chat_id: "-100123".to_string(),
+            api_url: api_url.to_string(),
+        }
+    }
+
+    fn send_test_message(channel: &NotificationChannel) -> Result<(), String> {
+        tauri::async_runtime::block_on(send_to_channel(
+            &Client::new(),
+            NodeEvent::NodeOffline,
+            "Node not online.",
+            "Double check if your node is started.",
+            channel,
+            "xe_0000000000000000000000000000000000000000",
+        ))
+    }
+
+    #[test]
+    fn telegram_message_is_posted_to_the_bot_api() {
+        let (api_url, request_handle) = serve_once(200);
+
+        assert_eq!(send_test_message(&telegram_channel(&api_url)), Ok(()));
+
+        let (url, body) = request_handle.join().unwrap();
+        assert_eq!(url, format!("/bot{}/sendMessage", BOT_TOKEN));
+        let payload: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(payload["chat_id"], "-100123");
+        assert_eq!(
+            payload["text"],
+            "Node not online.\nDouble check if your node is started."
+        );
+    }
+
+    #[test]
+    fn failed_telegram_message_does_not_leak_the_bot_token() {
+        let (api_url, request_handle) = serve_once(500);
+        let err = send_test_message(&telegram_channel(&api_url)).unwrap_err();
+        request_handle.join().unwrap();
+        assert_eq!(err, "Received http status 500 Internal Server Error");
+
+        // Nothing listens on the port of a dropped server, the request itself fails.
+        let unreachable_url = {
+            let server = Server::http("127.0.0.1:0").unwrap();
+            format!("http://{}", server.server_addr().to_ip().unwrap())
+        };
+        let err = send_test_message(&telegram_channel(&unreachable_url)).unwrap_err();
+        assert!(err.starts_with("Request failed"), "{}", err);
+        assert!(!err.contains(BOT_TOKEN), "{}", err);
+    }
+
+    #[test]
+    fn rate_limit_only_starts_after_a_delivered_notification() {
+        let event = NodeEvent::LotteryWon;
+        let rate_limit_seconds = 3600;
+        let deliver = |status_code: u16| {
+            let (api_url, request_handle) = serve_once(status_code);
+            let channel = NotificationChannel::Discord {
+                webhook_url: format!("{}/api/webhooks/1/token", api_url),
+            };
+            let send_result = send_test_message(&channel);
+            request_handle.join().unwrap();
+            if send_result.is_ok() {
+                mark_sent(event);
+            }
+            send_result
+        };
+
+        assert!(!is_rate_limited(event, rate_limit_seconds));
+        assert!(deliver(502).is_err());
+        assert!(!is_rate_limited(event, rate_limit_seconds));
+        assert!(deliver(204).is_ok());
+        assert!(is_rate_limited(event, rate_limit_seconds));
+        assert!(!is_rate_limited(event, 0));
+    }
+}
@@ -0,0 +1,306 @@
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+use tokio::sync::oneshot;
+
+use crate::logging::{get_cached_known_secrets, log_error, log_info, redact};
+use crate::BackendCommunicator;
+
+/// Event on which stdout & stderr lines of running processes are emitted to the frontend.
+const PROCESS_OUTPUT_LISTENER: &str = "process_output_listener";
+
+#[cfg(target_os = "windows")]
+const WINDOWS_CREATE_NO_WINDOW: u32 = 0x08000000;
+
+/// Result of a process which ran to completion.
+#[derive(Debug, Clone)]
+pub struct ProcessOutput {
+    pub exit_code: Option<i32>, // None if the process was terminated by a signal
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// A process which can be cancelled from the frontend.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunningProcess {
+    pub id: u64,
+    pub command: String, // eg. docker info
+    pub started_at: i64, // Unix timestamp in seconds
+}
+
+/// A single line of process output, emitted on `PROCESS_OUTPUT_LISTENER`.
+#[derive(Debug, Clone, Serialize)]
+struct ProcessOutputLine {
+    process_id: u64,
+    stream: &'static str, // stdout or stderr
+    line: String,
+}
+
+struct RegisteredProcess {
+    running_process: RunningProcess,
+    cancel_sender: oneshot::Sender<()>,
+}
+
+static NEXT_PROCESS_ID: AtomicU64 = AtomicU64::new(1);
+static RUNNING_PROCESSES: Mutex<BTreeMap<u64, RegisteredProcess>> = Mutex::new(BTreeMap::new());
+
+fn running_processes() -> MutexGuard<'static, BTreeMap<u64, RegisteredProcess>> {
+    match RUNNING_PROCESSES.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// Create a command with piped output, which does not open a console window on Windows.
+fn new_command(program: &str, args: &[&str], envs: &[(String, String)]) -> Command {
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .envs(envs.iter().map(|(key, value)| (key, value)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    #[cfg(target_os = "windows")]
+    command.creation_flags(WINDOWS_CREATE_NO_WINDOW);
+
+    command
+}
+
+/// Build the event of a line of output, with secrets such as private keys and device tokens masked.
+fn build_process_output_line(
+    process_id: u64,
+    stream: &'static str,
+    line: &str,
+    known_secrets: &[String],
+) -> ProcessOutputLine {
+    ProcessOutputLine {
+        process_id,
+        stream,
+        line: redact(line.trim_end(), known_secrets),
+    }
+}
+
+/// Read a stream line by line, emitting every line to the frontend with secrets masked.
+/// Returns the complete, unmasked output.
+async fn read_lines<R: AsyncRead + Unpin>(
+    reader: Option<R>,
+    process_id: u64,
+    stream: &'static str,
+    backend_communicator: &BackendCommunicator,
+) -> String {
+    let mut collected_output = String::new();
+    let mut reader = match reader {
+        Some(reader) => BufReader::new(reader),
+        None => return collected_output,
+    };
+
+    let known_secrets = get_cached_known_secrets(backend_communicator);
+    let mut line_bytes: Vec<u8> = vec![];
+    loop {
+        line_bytes.clear();
+        match reader.read_until(b'\n', &mut line_bytes).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {
+                let line = String::from_utf8_lossy(&line_bytes).to_string();
+                let process_output_line =
+                    build_process_output_line(process_id, stream, &line, &known_secrets);
+                let _ = backend_communicator
+                    .front_end_window
+                    .emit(PROCESS_OUTPUT_LISTENER, process_output_line);
+                collected_output.push_str(&line);
+            }
+        }
+    }
+    collected_output
+}
+
+/// Run a process without blocking, streaming its output to the frontend.
+/// The process is killed when the timeout passes or when it is cancelled from the frontend.
+pub async fn run_process(
+    program: &str,
+    args: &[&str],
+    timeout: Duration,
+    backend_communicator: &BackendCommunicator,
+) -> Result<ProcessOutput, String> {
+    run_process_with_env(program, args, &[], timeout, backend_communicator).await
+}
+
+/// Run a process like `run_process`, with additional environment variables, eg. DOCKER_HOST.
+pub async fn run_process_with_env(
+    program: &str,
+    args: &[&str],
+    envs: &[(String, String)],
+    timeout: Duration,
+    backend_communicator: &BackendCommunicator,
+) -> Result<ProcessOutput, String> {
+    // Only the file name, the full path to the Edge CLI adds nothing to the log.
+    let program_name = Path::new(program)
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string())
+        .unwrap_or_else(|| program.to_string());
+    let command_description = format!("{} {}", program_name, args.join(" "))
+        .trim()
+        .to_string();
+
+    let mut child;
+    match new_command(program, args, envs).spawn() {
+        Ok(ok_child) => child = ok_child,
+        Err(err) => {
+            return Err(format!(
+                "Could not run `{}`. Error: {}",
+                command_description, err
+            ))
+        }
+    }
+
+    let process_id = NEXT_PROCESS_ID.fetch_add(1, Ordering::SeqCst);
+    let (cancel_sender, cancel_receiver) = oneshot::channel::<()>();
+    running_processes().insert(
+        process_id,
+        RegisteredProcess {
+            running_process: RunningProcess {
+                id: process_id,
+                command: command_description.clone(),
+                started_at: Utc::now().timestamp(),
+            },
+            cancel_sender,
+        },
+    );
+
+    // Reading until both pipes close, the process has exited by then.
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    let read_output = async {
+        tokio::join!(
+            read_lines(stdout, process_id, "stdout", backend_communicator),
+            read_lines(stderr, process_id, "stderr", backend_communicator)
+        )
+    };
+
+    let run_result = tokio::select! {
+        (stdout, stderr) = read_output => Ok((stdout, stderr)),
+        _ = tokio::time::sleep(timeout) => Err(format!(
+            "`{}` did not finish within {} seconds and was stopped.",
+            command_description,
+            timeout.as_secs()
+        )),
+        _ = cancel_receiver => Err(format!("`{}` was cancelled.", command_description)),
+    };
+    running_processes().remove(&process_id);
+
+    let (stdout, stderr) = match run_result {
+        Ok(output) => output,
+        Err(err_message) => {
+            if let Err(err) = child.kill().await {
+                log_error!(
+                    format!("Unable to stop `{}`. Err: {}", command_description, err),
+                    backend_communicator,
+                );
+            }
+            log_error!(err_message.clone(), backend_communicator);
+            return Err(err_message);
+        }
+    };
+
+    match child.wait().await {
+        Ok(exit_status) => Ok(ProcessOutput {
+            exit_code: exit_status.code(),
+            stdout,
+            stderr,
+        }),
+        Err(err) => Err(format!(
+            "`{}` running status could not be checked. Error: {}",
+            command_description, err
+        )),
+    }
+}
+
+/// Returns all processes which are currently running.
+pub fn list_running_processes() -> Vec<RunningProcess> {
+    running_processes()
+        .values()
+        .map(|registered_process| registered_process.running_process.clone())
+        .collect()
+}
+
+/// Cancel a running process, it is killed by the task which started it.
+pub fn cancel_process(
+    process_id: u64,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    match running_processes().remove(&process_id) {
+        Some(registered_process) => {
+            let _ = registered_process.cancel_sender.send(());
+            let ok_message = format!(
+                "Cancelling `{}`.",
+                registered_process.running_process.command
+            );
+            log_info!(ok_message.clone(), backend_communicator);
+            Ok(ok_message)
+        }
+        None => {
+            let err_message = format!("No running process with id {}.", process_id);
+            log_error!(err_message.clone(), backend_communicator);
+            Err(err_message)
+        }
+    }
+}
+
+/// Cancel all running processes, eg. when the program quits. Returns how many were cancelled.
+pub fn cancel_all_processes() -> usize {
+    let registered_processes: Vec<RegisteredProcess> = std::mem::take(&mut *running_processes())
+        .into_values()
+        .collect();
+    let cancelled_processes = registered_processes.len();
+    for registered_process in registered_processes {
+        let _ = registered_process.cancel_sender.send(());
+    }
+    cancelled_processes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRIVATE_KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+    const DEVICE_TOKEN: &str =
+        "eyJhZGRyZXNzIjoieGVfd2FsbGV0Iiwic3Rha2UiOiJzdGFrZV9pZCIsInByaXZhdGVLZXkiOiIuLi4ifQ==";
+
+    #[test]
+    fn masks_private_key_in_process_output() {
+        let line = format!("Device private key: {}\n", PRIVATE_KEY);
+        let process_output_line = build_process_output_line(1, "stdout", &line, &[]);
+        assert!(!process_output_line.line.contains(PRIVATE_KEY));
+        assert!(process_output_line.line.contains("[REDACTED]"));
+    }
+
+    #[test]
+    fn masks_known_private_key_in_process_output() {
+        let line = format!("{} is already registered.", PRIVATE_KEY);
+        let process_output_line =
+            build_process_output_line(1, "stderr", &line, &[PRIVATE_KEY.to_string()]);
+        assert_eq!(
+            process_output_line.line,
+            "[REDACTED] is already registered."
+        );
+        assert_eq!(process_output_line.stream, "stderr");
+    }
+
+    #[test]
+    fn masks_device_token_in_process_output() {
+        let line = format!("-e EDGE_DEVICE_TOKEN={}\r\n", DEVICE_TOKEN);
+        let process_output_line = build_process_output_line(2, "stdout", &line, &[]);
+        assert!(!process_output_line.line.contains(DEVICE_TOKEN));
+        assert!(process_output_line.line.contains("[REDACTED]"));
+        assert_eq!(process_output_line.process_id, 2);
+    }
+}
@@ -0,0 +1,170 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::AppHandle;
+
+use crate::cli_versions::update_checker::is_cli_update_in_progress;
+use crate::config::flush_config;
+use crate::config::getters::get_shutdown_config;
+use crate::control_edge_cli::stop_step;
+use crate::logging::{flush_log, log_error, log_info};
+use crate::process_runner::cancel_all_processes;
+use crate::status_api::stop_status_api;
+use crate::BackendCommunicator;
+
+/// Set once quitting started. Downloads and new background tasks check it to stop early.
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+static BACKGROUND_TASKS: Mutex<Vec<JoinHandle<()>>> = Mutex::new(vec![]);
+
+fn background_tasks() -> MutexGuard<'static, Vec<JoinHandle<()>>> {
+    match BACKGROUND_TASKS.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// Whether the program is quitting.
+pub fn is_shutting_down() -> bool {
+    SHUTTING_DOWN.load(Ordering::SeqCst)
+}
+
+/// Spawn a task which is cancelled when the program quits. Processes it started are killed with it.
+pub fn spawn_background_task<F>(task: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    if is_shutting_down() {
+        return;
+    }
+    let join_handle = tauri::async_runtime::spawn(task);
+    let mut background_tasks = background_tasks();
+    background_tasks.retain(|background_task| !background_task.inner().is_finished());
+    background_tasks.push(join_handle);
+}
+
+/// Cancel all background tasks, returns how many were still running.
+fn cancel_background_tasks() -> usize {
+    let background_tasks: Vec<JoinHandle<()>> = background_tasks().drain(..).collect();
+    let mut cancelled_tasks = 0;
+    for background_task in background_tasks {
+        if !background_task.inner().is_finished() {
+            background_task.abort();
+            cancelled_tasks += 1;
+        }
+    }
+    cancelled_tasks
+}
+
+/// Let an Edge CLI update which is installing finish, so the device is not left stopped or
+/// without its binary. Its download is cancelled right away as the program is quitting.
+async fn wait_for_cli_update(backend_communicator: &BackendCommunicator) {
+    if !is_cli_update_in_progress() {
+        return;
+    }
+    log_info!(
+        "Waiting for the Edge CLI update to finish before quitting.".to_string(),
+        backend_communicator,
+    );
+    let wait_timeout =
+        Duration::from_secs(get_shutdown_config(backend_communicator).stop_timeout_seconds);
+    let wait_result = tokio::time::timeout(wait_timeout, async {
+        while is_cli_update_in_progress() {
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        }
+    })
+    .await;
+    if wait_result.is_err() {
+        log_error!(
+            format!(
+                "The Edge CLI update did not finish within {} seconds, quitting anyway.",
+                wait_timeout.as_secs()
+            ),
+            backend_communicator,
+        );
+    }
+}
+
+/// Stop the device if configured, otherwise leave it running without the GUI.
+async fn stop_device_on_quit(backend_communicator: &BackendCommunicator) {
+    let shutdown_config = get_shutdown_config(backend_communicator);
+    if !shutdown_config.stop_device_on_quit {
+        log_info!(
+            "Your node keeps running after quitting.".to_string(),
+            backend_communicator,
+        );
+        return;
+    }
+
+    log_info!(
+        "Stopping your node before quitting.".to_string(),
+        backend_communicator,
+    );
+    let stop_timeout = Duration::from_secs(shutdown_config.stop_timeout_seconds);
+    match tokio::time::timeout(stop_timeout, stop_step(false, backend_communicator)).await {
+        Ok(Ok(ok_message)) => log_info!(ok_message, backend_communicator),
+        Ok(Err(err)) => log_error!(
+            format!("Unable to stop your node before quitting. {}", err),
+            backend_communicator,
+        ),
+        Err(_) => log_error!(
+            format!(
+                "Your node did not stop within {} seconds, quitting anyway.",
+                stop_timeout.as_secs()
+            ),
+            backend_communicator,
+        ),
+    }
+}
+
+/// Quit the program: cancel downloads, let a running Edge CLI update finish, cancel background
+/// tasks & processes, stop the device if configured and flush the config & log.
+/// Later calls while quitting are ignored.
+pub async fn shutdown(app_handle: AppHandle) {
+    if SHUTTING_DOWN.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let backend_communicator = match BackendCommunicator::from_app_handle(&app_handle) {
+        Ok(backend_communicator) => backend_communicator,
+        Err(err) => {
+            eprintln!("Unable to shut down cleanly. Err: {}", err);
+            app_handle.exit(0);
+            return;
+        }
+    };
+    log_info!(
+        "Quitting the Staking GUI.".to_string(),
+        &backend_communicator,
+    );
+
+    wait_for_cli_update(&backend_communicator).await;
+    let cancelled_tasks = cancel_background_tasks();
+    let cancelled_processes = cancel_all_processes();
+    stop_status_api();
+    log_info!(
+        format!(
+            "Cancelled {} background tasks and {} running processes.",
+            cancelled_tasks, cancelled_processes
+        ),
+        &backend_communicator,
+    );
+
+    stop_device_on_quit(&backend_communicator).await;
+
+    if let Err(err) = flush_config(&backend_communicator) {
+        log_error!(err, &backend_communicator);
+    }
+    log_info!("Goodbye.".to_string(), &backend_communicator);
+    if let Err(err) = flush_log(&backend_communicator) {
+        eprintln!("{}", err);
+    }
+    app_handle.exit(0);
+}
+
+/// Start quitting in the background, eg. from the tray menu.
+pub fn request_shutdown(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(shutdown(app_handle));
+}
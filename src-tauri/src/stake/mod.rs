@@ -0,0 +1,417 @@
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+use crate::config::getters::{get_network, get_network_definitions, get_node_address};
+use crate::config::setters::set_stake_id;
+use crate::config::{Network, NetworkDefinition};
+use crate::logging::{log_error, log_info};
+use crate::BackendCommunicator;
+
+pub mod monitor;
+
+const INDEX_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Reasons a stake can not be used for this device, serialized as `{ "kind": ..., "message": ... }`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+pub enum StakeError {
+    NotAssigned(String),       // No stake is assigned to the device address.
+    AssignedElsewhere(String), // The stake is assigned to another device.
+    WrongNetwork(String),      // The stake exists on another network than the one in the config.
+    UnsupportedType(String),   // The stake is not a host, gateway or governance stake.
+    Unlocking(String),         // An unlock was requested for the stake.
+    Released(String),          // The stake was released and no longer exists.
+    IndexUnavailable(String),  // The index could not be reached or returned an unexpected response.
+    ConfigFailed(String),      // The stake is valid, but could not be stored.
+}
+
+impl fmt::Display for StakeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            StakeError::NotAssigned(message)
+            | StakeError::AssignedElsewhere(message)
+            | StakeError::WrongNetwork(message)
+            | StakeError::UnsupportedType(message)
+            | StakeError::Unlocking(message)
+            | StakeError::Released(message)
+            | StakeError::IndexUnavailable(message)
+            | StakeError::ConfigFailed(message) => message,
+        };
+        write!(f, "{}", message)
+    }
+}
+
+/// Stake types which can back a device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StakeType {
+    Host,
+    Gateway,
+    Governance,
+}
+
+impl fmt::Display for StakeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stake_type = match self {
+            StakeType::Host => "host",
+            StakeType::Gateway => "gateway",
+            StakeType::Governance => "governance",
+        };
+        write!(f, "{}", stake_type)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StakeState {
+    Active,    // The stake backs the device.
+    Unlocking, // An unlock was requested, the stake is released after the unlock period.
+    Released,  // The tokens were returned to the wallet.
+}
+
+/// A stake as returned by the index at /stake/<id>.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct IndexStake {
+    id: String,
+    hash: String,
+    #[serde(rename = "type")]
+    stake_type: String,
+    wallet: String,
+    device: Option<String>,
+    amount: u64,
+    unlock_requested: Option<u64>, // Unix timestamp in milliseconds
+    released: Option<u64>,         // Unix timestamp in milliseconds
+}
+
+/// A validated stake of this device.
+#[derive(Debug, Clone, Serialize)]
+pub struct StakeInfo {
+    pub id: String,
+    pub hash: String,
+    pub stake_type: StakeType,
+    pub state: StakeState,
+    pub network: String, // eg. mainnet
+    pub wallet: String,  // XE address of the wallet which created the stake
+    pub device: String,  // XE address of the device the stake is assigned to
+    pub amount: u64,     // In microXE
+}
+
+fn create_index_client() -> Result<Client, StakeError> {
+    Client::builder()
+        .timeout(INDEX_REQUEST_TIMEOUT)
+        .build()
+        .map_err(|err| {
+            StakeError::IndexUnavailable(format!(
+                "Unable to create index http client. Err: {}",
+                err
+            ))
+        })
+}
+
+/// GET a JSON document from the index. None if the index does not know it.
+async fn fetch_index_json(client: &Client, url: &str) -> Result<Option<Value>, StakeError> {
+    let response = client.get(url).send().await.map_err(|err| {
+        StakeError::IndexUnavailable(format!("Index request {} failed: {}", url, err))
+    })?;
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(StakeError::IndexUnavailable(format!(
+            "Index request {} returned http status {}",
+            url,
+            response.status()
+        )));
+    }
+    response.json::<Value>().await.map(Some).map_err(|err| {
+        StakeError::IndexUnavailable(format!("Index response of {} is not JSON: {}", url, err))
+    })
+}
+
+/// Returns the id of the stake assigned to a device address, None if there is none.
+async fn fetch_device_stake_id(
+    client: &Client,
+    index_url: &str,
+    address: &str,
+) -> Result<Option<String>, StakeError> {
+    let url = format!(
+        "{}/device/{}/stake",
+        index_url.trim_end_matches('/'),
+        address
+    );
+    let device_stake = match fetch_index_json(client, &url).await? {
+        Some(device_stake) => device_stake,
+        None => return Ok(None),
+    };
+
+    // The stake is either referenced by id, or embedded.
+    let stake_id = match &device_stake["stake"] {
+        Value::String(stake_id) => Some(stake_id.clone()),
+        Value::Object(stake) => stake.get("id").and_then(Value::as_str).map(String::from),
+        _ => device_stake["id"].as_str().map(String::from),
+    };
+    Ok(stake_id.filter(|stake_id| !stake_id.is_empty()))
+}
+
+async fn fetch_stake(
+    client: &Client,
+    index_url: &str,
+    stake_id: &str,
+) -> Result<Option<IndexStake>, StakeError> {
+    let url = format!("{}/stake/{}", index_url.trim_end_matches('/'), stake_id);
+    match fetch_index_json(client, &url).await? {
+        Some(stake) => serde_json::from_value::<IndexStake>(stake)
+            .map(Some)
+            .map_err(|err| {
+                StakeError::IndexUnavailable(format!("Unexpected stake from index: {}", err))
+            }),
+        None => Ok(None),
+    }
+}
+
+/// Check whether a stake can back the device.
+fn validate_stake(
+    index_stake: IndexStake,
+    address: &str,
+    network: &Network,
+) -> Result<StakeInfo, StakeError> {
+    let stake_type = match index_stake.stake_type.as_str() {
+        "host" => StakeType::Host,
+        "gateway" => StakeType::Gateway,
+        "governance" => StakeType::Governance,
+        other => {
+            let err_message = format!(
+                "Stake {} is a {} stake. Only host, gateway and governance stakes can run a device.",
+                index_stake.id, other
+            );
+            return Err(StakeError::UnsupportedType(err_message));
+        }
+    };
+
+    let device = index_stake.device.unwrap_or_default();
+    let state = if index_stake.released.is_some() {
+        StakeState::Released
+    } else if index_stake.unlock_requested.is_some() {
+        StakeState::Unlocking
+    } else {
+        StakeState::Active
+    };
+
+    match state {
+        StakeState::Released => Err(StakeError::Released(format!(
+            "Stake {} was released. Create a new stake and assign the device to it.",
+            index_stake.id
+        ))),
+        StakeState::Unlocking => Err(StakeError::Unlocking(format!(
+            "An unlock was requested for stake {}, it no longer backs a device.",
+            index_stake.id
+        ))),
+        StakeState::Active if device != address => Err(StakeError::AssignedElsewhere(format!(
+            "Stake {} is assigned to device {}, not to this device {}.",
+            index_stake.id, device, address
+        ))),
+        StakeState::Active => Ok(StakeInfo {
+            id: index_stake.id,
+            hash: index_stake.hash,
+            stake_type,
+            state,
+            network: network.to_string(),
+            wallet: index_stake.wallet,
+            device,
+            amount: index_stake.amount,
+        }),
+    }
+}
+
+fn get_index_url(
+    network: &Network,
+    network_definitions: &BTreeMap<String, NetworkDefinition>,
+) -> Result<String, StakeError> {
+    match network_definitions.get(network.as_str()) {
+        Some(network_definition) => Ok(network_definition.index_url.clone()),
+        None => Err(StakeError::IndexUnavailable(format!(
+            "Network {} is not defined.",
+            network
+        ))),
+    }
+}
+
+/// Look up the stake assigned to the device address on the index of every known network.
+/// Returns the validated stake of the configured network.
+pub async fn discover_stake(
+    backend_communicator: &BackendCommunicator,
+) -> Result<StakeInfo, StakeError> {
+    let address = get_node_address(backend_communicator);
+    if !address.starts_with("xe_") {
+        return Err(StakeError::NotAssigned(
+            "Device address is not set. Create a device token first.".to_string(),
+        ));
+    }
+    let network = get_network(backend_communicator);
+    let network_definitions = get_network_definitions(backend_communicator);
+    let client = create_index_client()?;
+    let index_url = get_index_url(&network, &network_definitions)?;
+
+    log_info!(
+        format!("Looking up stake of device {} on {}.", address, network),
+        backend_communicator,
+    );
+    if let Some(stake_id) = fetch_device_stake_id(&client, &index_url, &address).await? {
+        return match fetch_stake(&client, &index_url, &stake_id).await? {
+            Some(index_stake) => validate_stake(index_stake, &address, &network),
+            None => Err(StakeError::Released(format!(
+                "Stake {} no longer exists on {}.",
+                stake_id, network
+            ))),
+        };
+    }
+
+    // Not on the configured network, the device may have been assigned on another one.
+    for (other_network, network_definition) in network_definitions.iter() {
+        if other_network == network.as_str() {
+            continue;
+        }
+        // An unreachable index of another network must not hide the actual error.
+        if let Ok(Some(stake_id)) =
+            fetch_device_stake_id(&client, &network_definition.index_url, &address).await
+        {
+            return Err(StakeError::WrongNetwork(format!(
+                "Device {} is assigned to stake {} on {}, but the GUI is on {}. Switch networks to use it.",
+                address, stake_id, other_network, network
+            )));
+        }
+    }
+
+    Err(StakeError::NotAssigned(format!(
+        "No stake is assigned to device {} on {}. Has your assign device transaction been confirmed?",
+        address, network
+    )))
+}
+
+/// Check whether the stake in the config still backs the device on the configured network.
+pub(crate) async fn check_configured_stake(
+    stake_id: &str,
+    backend_communicator: &BackendCommunicator,
+) -> Result<StakeInfo, StakeError> {
+    let address = get_node_address(backend_communicator);
+    let network = get_network(backend_communicator);
+    let network_definitions = get_network_definitions(backend_communicator);
+    let client = create_index_client()?;
+    let index_url = get_index_url(&network, &network_definitions)?;
+
+    match fetch_stake(&client, &index_url, stake_id).await? {
+        Some(index_stake) => validate_stake(index_stake, &address, &network),
+        None => Err(StakeError::Released(format!(
+            "Stake {} no longer exists on {}.",
+            stake_id, network
+        ))),
+    }
+}
+
+/// Discover and validate the stake of this device, then store its id in the config.
+pub async fn discover_and_set_stake(
+    backend_communicator: &BackendCommunicator,
+) -> Result<StakeInfo, StakeError> {
+    let stake_info = match discover_stake(backend_communicator).await {
+        Ok(stake_info) => stake_info,
+        Err(err) => {
+            log_error!(err.to_string(), backend_communicator);
+            return Err(err);
+        }
+    };
+
+    set_stake_id(stake_info.id.clone(), backend_communicator).map_err(StakeError::ConfigFailed)?;
+    log_info!(
+        format!(
+            "Found active {} stake {} on {}.",
+            stake_info.stake_type, stake_info.id, stake_info.network
+        ),
+        backend_communicator,
+    );
+    Ok(stake_info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEVICE_ADDRESS: &str = "xe_3F129e5Be5f0E3d3B53F7a1b2c3d4e5f6A7b8C9d";
+    const OTHER_DEVICE_ADDRESS: &str = "xe_9d8C7b6A5f4e3d2c1b0a9F8e7D6c5B4a3F2e1D0c";
+
+    fn index_stake(index_stake_json: &str) -> IndexStake {
+        serde_json::from_str(index_stake_json).unwrap()
+    }
+
+    fn host_stake() -> IndexStake {
+        index_stake(&format!(
+            r#"{{"id": "a1b2c3", "hash": "0123abcd", "type": "host", "wallet": "xe_wallet", "device": "{}", "amount": 10000000000, "unlockRequested": null}}"#,
+            DEVICE_ADDRESS
+        ))
+    }
+
+    #[test]
+    fn active_stake_of_this_device_is_valid() {
+        let stake_info = validate_stake(host_stake(), DEVICE_ADDRESS, &Network::Testnet).unwrap();
+        assert_eq!(stake_info.id, "a1b2c3");
+        assert_eq!(stake_info.hash, "0123abcd");
+        assert_eq!(stake_info.stake_type, StakeType::Host);
+        assert_eq!(stake_info.state, StakeState::Active);
+        assert_eq!(stake_info.network, "testnet");
+        assert_eq!(stake_info.device, DEVICE_ADDRESS);
+        assert_eq!(stake_info.amount, 10_000_000_000);
+    }
+
+    #[test]
+    fn stake_of_another_device_is_rejected() {
+        let err =
+            validate_stake(host_stake(), OTHER_DEVICE_ADDRESS, &Network::Mainnet).unwrap_err();
+        assert!(matches!(err, StakeError::AssignedElsewhere(_)), "{:?}", err);
+
+        let mut unassigned_stake = host_stake();
+        unassigned_stake.device = None;
+        let err = validate_stake(unassigned_stake, DEVICE_ADDRESS, &Network::Mainnet).unwrap_err();
+        assert!(matches!(err, StakeError::AssignedElsewhere(_)), "{:?}", err);
+    }
+
+    #[test]
+    fn unlocking_and_released_stakes_are_rejected() {
+        let mut unlocking_stake = host_stake();
+        unlocking_stake.unlock_requested = Some(1_700_000_000_000);
+        let err = validate_stake(unlocking_stake, DEVICE_ADDRESS, &Network::Mainnet).unwrap_err();
+        assert!(matches!(err, StakeError::Unlocking(_)), "{:?}", err);
+
+        // Released takes precedence over the unlock request.
+        let mut released_stake = host_stake();
+        released_stake.unlock_requested = Some(1_700_000_000_000);
+        released_stake.released = Some(1_700_100_000_000);
+        let err = validate_stake(released_stake, DEVICE_ADDRESS, &Network::Mainnet).unwrap_err();
+        assert!(matches!(err, StakeError::Released(_)), "{:?}", err);
+    }
+
+    #[test]
+    fn only_device_stake_types_are_valid() {
+        for (stake_type_str, stake_type) in [
+            ("gateway", StakeType::Gateway),
+            ("governance", StakeType::Governance),
+        ] {
+            let mut device_stake = host_stake();
+            device_stake.stake_type = stake_type_str.to_string();
+            let stake_info =
+                validate_stake(device_stake, DEVICE_ADDRESS, &Network::Mainnet).unwrap();
+            assert_eq!(stake_info.stake_type, stake_type);
+        }
+
+        let mut unknown_stake = host_stake();
+        unknown_stake.stake_type = "stargate".to_string();
+        let err = validate_stake(unknown_stake, DEVICE_ADDRESS, &Network::Mainnet).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Stake a1b2c3 is a stargate stake. Only host, gateway and governance stakes can run a device."
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use chrono::Utc;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::config::get_config;
+use crate::config::getters::get_stake_monitor_config;
+use crate::control_edge_cli::stop_step;
+use crate::docker::get_edge_device_container_state;
+use crate::logging::{log_error, log_info, log_warn};
+use crate::notifications::{notify, NodeEvent};
+use crate::shutdown::spawn_background_task;
+use crate::BackendCommunicator;
+
+use super::{check_configured_stake, StakeError, StakeInfo};
+
+/// Give Docker and the frontend time to start before the first check.
+const STARTUP_DELAY_SECONDS: u64 = 120;
+
+/// Result of the last stake check.
+#[derive(Debug, Serialize, Clone)]
+pub struct StakeCheckStatus {
+    pub checked_at: i64, // Unix timestamp in seconds
+    pub stake_id: String,
+    pub backs_device: bool, // false if the stake is unlocking, released or assigned to another device
+    pub stake: Option<StakeInfo>,
+    pub error: Option<StakeError>,
+    pub device_stopped: bool, // Was the device stopped because of this check?
+}
+
+static LAST_STAKE_CHECK_STATUS: Mutex<Option<StakeCheckStatus>> = Mutex::new(None);
+
+/// Returns the cached result of the last stake check, if any.
+pub fn get_last_stake_check_status() -> Option<StakeCheckStatus> {
+    match LAST_STAKE_CHECK_STATUS.lock() {
+        Ok(last_stake_check_status) => last_stake_check_status.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+}
+
+/// Stop the device if it is running. Returns whether it was stopped.
+async fn stop_device_without_stake(backend_communicator: &BackendCommunicator) -> bool {
+    match get_edge_device_container_state(backend_communicator).await {
+        Ok(container_state) if container_state == "running" => {
+            match stop_step(false, backend_communicator).await {
+                Ok(_) => true,
+                Err(err) => {
+                    log_error!(
+                        format!("Unable to stop the device without stake. Err: {}", err),
+                        backend_communicator,
+                    );
+                    false
+                }
+            }
+        }
+        Ok(_) => false,
+        Err(err) => {
+            log_error!(err, backend_communicator);
+            false
+        }
+    }
+}
+
+/// Check the stake of the device on the index.
+/// Alerts when the stake stops backing the device and stops the device if configured.
+/// The index being unreachable is not treated as a lost stake.
+pub async fn check_stake(
+    backend_communicator: &BackendCommunicator,
+) -> Result<StakeCheckStatus, String> {
+    let config = get_config(backend_communicator)?;
+    if !config.initialized || config.stake_id.is_empty() || config.stake_id == "Unset" {
+        return Err(String::from("No stake set up yet."));
+    }
+
+    let (stake, error) = match check_configured_stake(&config.stake_id, backend_communicator).await
+    {
+        Ok(stake_info) => (Some(stake_info), None),
+        Err(StakeError::IndexUnavailable(err)) => return Err(err),
+        Err(err) => (None, Some(err)),
+    };
+    let backs_device = error.is_none();
+
+    let previous_backs_device = get_last_stake_check_status()
+        .map(|stake_check_status| stake_check_status.backs_device)
+        .unwrap_or(true);
+
+    let mut device_stopped = false;
+    if let Some(err) = &error {
+        log_error!(
+            format!(
+                "Stake {} no longer backs this device. {}",
+                config.stake_id, err
+            ),
+            backend_communicator,
+        );
+        if get_stake_monitor_config(backend_communicator).stop_device {
+            device_stopped = stop_device_without_stake(backend_communicator).await;
+        }
+
+        // Only alert on the change, the check repeats while the stake stays invalid.
+        if previous_backs_device || device_stopped {
+            let message = if device_stopped {
+                format!("{} The node was stopped.", err)
+            } else {
+                err.to_string()
+            };
+            if let Err(notify_err) = notify(
+                NodeEvent::StakeChanged,
+                "Stake no longer backs your node.",
+                &message,
+                backend_communicator,
+            )
+            .await
+            {
+                log_error!(notify_err, backend_communicator);
+            }
+        }
+    } else if !previous_backs_device {
+        log_info!(
+            format!("Stake {} backs this device again.", config.stake_id),
+            backend_communicator,
+        );
+    }
+
+    let stake_check_status = StakeCheckStatus {
+        checked_at: Utc::now().timestamp(),
+        stake_id: config.stake_id,
+        backs_device,
+        stake,
+        error,
+        device_stopped,
+    };
+    if let Ok(mut last_stake_check_status) = LAST_STAKE_CHECK_STATUS.lock() {
+        *last_stake_check_status = Some(stake_check_status.clone());
+    }
+    Ok(stake_check_status)
+}
+
+/// Periodically check whether the stake still backs the device.
+/// The interval is re-read from config each round, 0 pauses checking.
+pub fn start_stake_monitor(backend_communicator: BackendCommunicator) {
+    spawn_background_task(async move {
+        tokio::time::sleep(Duration::from_secs(STARTUP_DELAY_SECONDS)).await;
+        loop {
+            let check_interval_minutes =
+                get_stake_monitor_config(&backend_communicator).check_interval_minutes;
+            if check_interval_minutes == 0 {
+                tokio::time::sleep(Duration::from_secs(3600)).await;
+                continue;
+            }
+
+            if let Err(err) = check_stake(&backend_communicator).await {
+                log_warn!(
+                    format!("Skipped stake check. {}", err),
+                    &backend_communicator,
+                );
+            }
+
+            tokio::time::sleep(Duration::from_secs(check_interval_minutes * 60)).await;
+        }
+    });
+}
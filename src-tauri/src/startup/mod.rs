@@ -0,0 +1,277 @@
+use chrono::Utc;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::check_requirements::check_edge::get_edge_cli_binary;
+use crate::config::get_config;
+use crate::config::getters::get_startup_config;
+use crate::config::StartupConfig;
+use crate::control_edge_cli::cli_output::EdgeCliError;
+use crate::control_edge_cli::{get_edge_cli_version, start_step};
+use crate::docker::{get_docker_status, get_edge_device_container_state};
+use crate::logging::{log_error, log_info, log_warn};
+use crate::notifications::show_desktop_notification;
+use crate::shutdown::spawn_background_task;
+use crate::tray::request_tray_refresh;
+use crate::BackendCommunicator;
+
+/// Event on which every change of the startup status is emitted to the frontend.
+const STARTUP_STATUS_LISTENER: &str = "startup_status_listener";
+/// First retry interval, doubled after every failed attempt up to `max_retry_interval_seconds`.
+const INITIAL_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Phase of starting the node at launch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupPhase {
+    Idle, // Autostart is off or the device is not set up.
+    WaitingForDocker,
+    CheckingCli,
+    StartingDevice,
+    Started,
+    Failed,
+}
+
+/// Current phase of the startup, emitted on `STARTUP_STATUS_LISTENER`.
+#[derive(Debug, Clone, Serialize)]
+pub struct StartupStatus {
+    pub phase: StartupPhase,
+    pub attempt: u32, // Attempt within the phase, starting at 1.
+    pub message: String,
+    pub updated_at: i64, // Unix timestamp in seconds
+}
+
+static STARTUP_STATUS: Mutex<Option<StartupStatus>> = Mutex::new(None);
+
+/// Prevents a second startup from starting the device at the same time.
+static STARTUP_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Returns the current startup status, None if the startup did not run yet.
+pub fn get_startup_status() -> Option<StartupStatus> {
+    match STARTUP_STATUS.lock() {
+        Ok(startup_status) => startup_status.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+}
+
+fn set_startup_status(
+    phase: StartupPhase,
+    attempt: u32,
+    message: String,
+    backend_communicator: &BackendCommunicator,
+) -> StartupStatus {
+    if phase == StartupPhase::Failed {
+        log_error!(format!("Startup: {}", message), backend_communicator);
+    } else {
+        log_info!(format!("Startup: {}", message), backend_communicator);
+    }
+
+    let startup_status = StartupStatus {
+        phase,
+        attempt,
+        message,
+        updated_at: Utc::now().timestamp(),
+    };
+    match STARTUP_STATUS.lock() {
+        Ok(mut last_startup_status) => *last_startup_status = Some(startup_status.clone()),
+        Err(poisoned) => *poisoned.into_inner() = Some(startup_status.clone()),
+    }
+    let _ = backend_communicator
+        .front_end_window
+        .emit(STARTUP_STATUS_LISTENER, startup_status.clone());
+    if phase == StartupPhase::Started || phase == StartupPhase::Failed {
+        request_tray_refresh();
+    }
+    startup_status
+}
+
+fn next_retry_interval(retry_interval: Duration, startup_config: &StartupConfig) -> Duration {
+    let max_retry_interval = Duration::from_secs(startup_config.max_retry_interval_seconds.max(1));
+    (retry_interval * 2).min(max_retry_interval)
+}
+
+/// Wait until Docker is running. Problems which waiting does not solve, eg. Windows-container
+/// mode, fail immediately.
+async fn wait_for_docker(
+    startup_config: &StartupConfig,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    let deadline = Instant::now() + Duration::from_secs(startup_config.docker_timeout_minutes * 60);
+    let mut retry_interval = INITIAL_RETRY_INTERVAL;
+    let mut attempt = 1;
+
+    loop {
+        set_startup_status(
+            StartupPhase::WaitingForDocker,
+            attempt,
+            format!("Waiting for Docker to be ready. Attempt {}.", attempt),
+            backend_communicator,
+        );
+        let err = match get_docker_status(backend_communicator).await {
+            Ok(docker_info) => return docker_info.to_check_result(),
+            Err(err) => err,
+        };
+
+        if Instant::now() + retry_interval > deadline {
+            return Err(format!(
+                "Docker was not ready within {} minutes. Is Docker Desktop set to start when you log in? {}",
+                startup_config.docker_timeout_minutes, err
+            ));
+        }
+        log_warn!(
+            format!(
+                "Docker not ready yet, retrying in {} seconds. {}",
+                retry_interval.as_secs(),
+                err
+            ),
+            backend_communicator,
+        );
+        tokio::time::sleep(retry_interval).await;
+        retry_interval = next_retry_interval(retry_interval, startup_config);
+        attempt += 1;
+    }
+}
+
+/// Check that the Edge CLI runs, installing it if it is missing.
+async fn verify_edge_cli(backend_communicator: &BackendCommunicator) -> Result<String, String> {
+    set_startup_status(
+        StartupPhase::CheckingCli,
+        1,
+        "Checking the Edge CLI.".to_string(),
+        backend_communicator,
+    );
+    match get_edge_cli_version(backend_communicator).await {
+        Ok(version) => Ok(format!("Edge CLI {} installed.", version)),
+        Err(EdgeCliError::NotInstalled(_)) => {
+            if get_edge_cli_binary(backend_communicator).await {
+                Ok("Edge CLI installed.".to_string())
+            } else {
+                Err("The Edge CLI is not installed and could not be downloaded.".to_string())
+            }
+        }
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Start the device, retrying with back-off. A running device counts as started.
+async fn start_device(
+    startup_config: &StartupConfig,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    if let Ok(container_state) = get_edge_device_container_state(backend_communicator).await {
+        if container_state == "running" {
+            return Ok("Device is already running.".to_string());
+        }
+    }
+
+    let start_attempts = startup_config.start_attempts.max(1);
+    let mut retry_interval = INITIAL_RETRY_INTERVAL;
+    let mut attempt = 1;
+    loop {
+        set_startup_status(
+            StartupPhase::StartingDevice,
+            attempt,
+            format!(
+                "Starting the device. Attempt {}/{}.",
+                attempt, start_attempts
+            ),
+            backend_communicator,
+        );
+        let err = match start_step(false, backend_communicator).await {
+            Ok(ok_message) => return Ok(ok_message),
+            Err(err) => err,
+        };
+
+        if attempt >= start_attempts {
+            return Err(format!(
+                "Could not start the device after {} attempts. {}",
+                start_attempts, err
+            ));
+        }
+        tokio::time::sleep(retry_interval).await;
+        retry_interval = next_retry_interval(retry_interval, startup_config);
+        attempt += 1;
+    }
+}
+
+/// Wait for Docker, verify the Edge CLI and start the device, if autostart is enabled.
+/// Runs in the backend, so it also works while the window is minimized or hidden.
+/// Returns the final status, or an error if a startup is already running.
+pub async fn run_startup(
+    backend_communicator: &BackendCommunicator,
+) -> Result<StartupStatus, String> {
+    let config = match get_config(backend_communicator) {
+        Ok(config) => config,
+        Err(err) => {
+            return Ok(set_startup_status(
+                StartupPhase::Failed,
+                1,
+                err,
+                backend_communicator,
+            ))
+        }
+    };
+    if !config.is_auto_start_enabled || !config.initialized {
+        return Ok(set_startup_status(
+            StartupPhase::Idle,
+            1,
+            "Autostart is off or the device is not set up yet.".to_string(),
+            backend_communicator,
+        ));
+    }
+    if STARTUP_RUNNING.swap(true, Ordering::SeqCst) {
+        let err_message = "Startup is already running.".to_string();
+        log_warn!(err_message.clone(), backend_communicator);
+        return Err(err_message);
+    }
+
+    let startup_config = get_startup_config(backend_communicator);
+    let startup_result = async {
+        wait_for_docker(&startup_config, backend_communicator).await?;
+        verify_edge_cli(backend_communicator).await?;
+        start_device(&startup_config, backend_communicator).await
+    }
+    .await;
+
+    let startup_status = match startup_result {
+        Ok(ok_message) => {
+            let startup_status = set_startup_status(
+                StartupPhase::Started,
+                1,
+                format!("Your node has successfully autostarted. {}", ok_message),
+                backend_communicator,
+            );
+            show_desktop_notification(
+                "Node Autostarted",
+                "Your Edge node has successfully autostarted!",
+                backend_communicator,
+            );
+            startup_status
+        }
+        Err(err) => {
+            let startup_status = set_startup_status(
+                StartupPhase::Failed,
+                1,
+                format!("Could not start your node automatically. {} If the error persists, please contact support on the Edge Discord: https://ed.ge/discord.", err),
+                backend_communicator,
+            );
+            show_desktop_notification(
+                "Node Autostart Failed",
+                "Could not start your node automatically. Open the Staking GUI for details.",
+                backend_communicator,
+            );
+            startup_status
+        }
+    };
+    STARTUP_RUNNING.store(false, Ordering::SeqCst);
+    Ok(startup_status)
+}
+
+/// Run the startup in the background when the program launches.
+pub fn start_startup_orchestrator(backend_communicator: BackendCommunicator) {
+    spawn_background_task(async move {
+        let _ = run_startup(&backend_communicator).await;
+    });
+}
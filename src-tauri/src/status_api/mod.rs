@@ -0,0 +1,443 @@
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::Manager;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::config::get_config;
+use crate::config::getters::get_status_api_config;
+use crate::control_edge_cli::get_edge_cli_version;
+use crate::docker::get_edge_device_container_state;
+use crate::logging::log_error;
+use crate::node_status::{get_node_status, NodeStatus};
+use crate::BackendCommunicator;
+
+/// Docker and CLI probes are cached so frequent scrapes do not spawn processes every time.
+const PROBE_CACHE_DURATION: Duration = Duration::from_secs(60);
+
+/// The running server, kept to stop it when the program quits.
+static STATUS_API_SERVER: Mutex<Option<Arc<Server>>> = Mutex::new(None);
+
+/// Config summary without secrets such as the private key or notification tokens.
+#[derive(Serialize, Clone)]
+struct ConfigSummary {
+    initialized: bool,
+    is_auto_start_enabled: bool,
+    launch_minimized: bool,
+    last_node_payment: u64,
+    wallet_address: String,
+    network: String,
+    address: String,
+    stake_id: String,
+    notification_channels: usize,
+}
+
+/// Body of the /status endpoint.
+#[derive(Serialize, Clone)]
+struct StatusResponse {
+    gui_version: String,
+    config: Option<ConfigSummary>,
+    container_state: Option<String>,
+    cli_version: Option<String>,
+    node_status: NodeStatus,
+}
+
+/// Results of the process based probes, refreshed after `PROBE_CACHE_DURATION`.
+struct ProbeCache {
+    fetched_at: Instant,
+    container_state: Option<String>,
+    cli_version: Option<String>,
+}
+
+impl ProbeCache {
+    fn refresh_if_outdated(&mut self, backend_communicator: &BackendCommunicator) {
+        if self.fetched_at.elapsed() < PROBE_CACHE_DURATION {
+            return;
+        }
+        // The server runs on its own thread, outside of the async runtime.
+        tauri::async_runtime::block_on(async {
+            self.container_state = get_edge_device_container_state(backend_communicator)
+                .await
+                .ok();
+            self.cli_version = get_edge_cli_version(backend_communicator).await.ok();
+        });
+        self.fetched_at = Instant::now();
+    }
+}
+
+/// Start the local status API in a background thread if enabled in config.
+pub fn start_status_api(backend_communicator: BackendCommunicator) -> Result<String, String> {
+    let status_api_config = get_status_api_config(&backend_communicator);
+    if !status_api_config.enabled {
+        return Ok("Status API disabled in config.".to_string());
+    }
+
+    let address = format!(
+        "{}:{}",
+        status_api_config.bind_address, status_api_config.port
+    );
+    let server;
+    match Server::http(&address) {
+        Ok(ok_server) => server = Arc::new(ok_server),
+        Err(err) => {
+            let err_message = format!(
+                "Unable to start status API on {}. Is the port already in use? Err: {}",
+                address, err
+            );
+            log_error!(err_message.clone(), &backend_communicator);
+            return Err(err_message);
+        }
+    }
+
+    match STATUS_API_SERVER.lock() {
+        Ok(mut status_api_server) => *status_api_server = Some(server.clone()),
+        Err(poisoned) => *poisoned.into_inner() = Some(server.clone()),
+    }
+    thread::spawn(move || {
+        let mut probe_cache = ProbeCache {
+            fetched_at: Instant::now() - PROBE_CACHE_DURATION,
+            container_state: None,
+            cli_version: None,
+        };
+        for request in server.incoming_requests() {
+            handle_request(request, &mut probe_cache, &backend_communicator);
+        }
+    });
+
+    let ok_message = format!(
+        "Status API listening on http://{} (/health, /status, /metrics).",
+        address
+    );
+    Ok(ok_message)
+}
+
+/// Stop accepting requests, the server thread then ends.
+pub fn stop_status_api() {
+    let status_api_server = match STATUS_API_SERVER.lock() {
+        Ok(mut status_api_server) => status_api_server.take(),
+        Err(poisoned) => poisoned.into_inner().take(),
+    };
+    if let Some(server) = status_api_server {
+        server.unblock();
+    }
+}
+
+/// Route a request to the matching endpoint.
+fn handle_request(
+    request: Request,
+    probe_cache: &mut ProbeCache,
+    backend_communicator: &BackendCommunicator,
+) {
+    let path = request.url().split('?').next().unwrap_or("").to_string();
+
+    let response = if request.method() != &Method::Get {
+        text_response("Method not allowed", 405, "text/plain")
+    } else {
+        match path.as_str() {
+            "/health" => text_response("{\"status\":\"ok\"}", 200, "application/json"),
+            "/status" => {
+                probe_cache.refresh_if_outdated(backend_communicator);
+                let status_response = build_status_response(probe_cache, backend_communicator);
+                match serde_json::to_string(&status_response) {
+                    Ok(json_str) => text_response(&json_str, 200, "application/json"),
+                    Err(err) => text_response(&err.to_string(), 500, "text/plain"),
+                }
+            }
+            "/metrics" => {
+                probe_cache.refresh_if_outdated(backend_communicator);
+                let status_response = build_status_response(probe_cache, backend_communicator);
+                text_response(
+                    &render_metrics(&status_response),
+                    200,
+                    "text/plain; version=0.0.4",
+                )
+            }
+            _ => text_response("Not found", 404, "text/plain"),
+        }
+    };
+
+    if let Err(err) = request.respond(response) {
+        log_error!(
+            format!(
+                "Status API could not send response for {}. Err: {}",
+                path, err
+            ),
+            backend_communicator,
+        );
+    }
+}
+
+fn text_response(
+    body: &str,
+    status_code: u16,
+    content_type: &str,
+) -> Response<std::io::Cursor<Vec<u8>>> {
+    let response = Response::from_string(body).with_status_code(status_code);
+    match Header::from_bytes(&b"Content-Type"[..], content_type.as_bytes()) {
+        Ok(header) => response.with_header(header),
+        Err(_) => response,
+    }
+}
+
+/// Collect config summary, probes and in-memory node status.
+fn build_status_response(
+    probe_cache: &ProbeCache,
+    backend_communicator: &BackendCommunicator,
+) -> StatusResponse {
+    let config_summary = match get_config(backend_communicator) {
+        Ok(config) => Some(ConfigSummary {
+            initialized: config.initialized,
+            is_auto_start_enabled: config.is_auto_start_enabled,
+            launch_minimized: config.launch_minimized,
+            last_node_payment: config.last_node_payment,
+            wallet_address: config.wallet_address,
+            network: config.network.to_string(),
+            address: config.address,
+            stake_id: config.stake_id,
+            notification_channels: config.notifications.channels.len(),
+        }),
+        Err(_) => None,
+    };
+
+    StatusResponse {
+        gui_version: backend_communicator
+            .front_end_window
+            .app_handle()
+            .package_info()
+            .version
+            .to_string(),
+        config: config_summary,
+        container_state: probe_cache.container_state.clone(),
+        cli_version: probe_cache.cli_version.clone(),
+        node_status: get_node_status(),
+    }
+}
+
+/// Escape a Prometheus label value.
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Write a single metric with help & type lines.
+fn push_metric(
+    metrics: &mut String,
+    name: &str,
+    metric_type: &str,
+    help: &str,
+    samples: &[(String, f64)],
+) {
+    let _ = writeln!(metrics, "# HELP {} {}", name, help);
+    let _ = writeln!(metrics, "# TYPE {} {}", name, metric_type);
+    for (labels, value) in samples {
+        let _ = writeln!(metrics, "{}{} {}", name, labels, value);
+    }
+}
+
+/// Render the status in the Prometheus text exposition format.
+fn render_metrics(status_response: &StatusResponse) -> String {
+    let mut metrics = String::new();
+    let node_status = &status_response.node_status;
+
+    let online_samples = match node_status.online {
+        Some(online) => vec![(String::new(), if online { 1.0 } else { 0.0 })],
+        None => vec![],
+    };
+    push_metric(
+        &mut metrics,
+        "edge_node_online",
+        "gauge",
+        "Whether the node is online according to the index (1) or not (0).",
+        &online_samples,
+    );
+
+    let container_running = status_response.container_state.as_deref() == Some("running");
+    push_metric(
+        &mut metrics,
+        "edge_device_container_running",
+        "gauge",
+        "Whether the Edge device container is running.",
+        &[(String::new(), if container_running { 1.0 } else { 0.0 })],
+    );
+
+    push_metric(
+        &mut metrics,
+        "edge_device_restarts_total",
+        "counter",
+        "Number of times the device was (re)started by the GUI since launch.",
+        &[(String::new(), node_status.device_starts_total as f64)],
+    );
+
+    push_metric(
+        &mut metrics,
+        "edge_earnings_xe_total",
+        "counter",
+        "Earnings received since launch in XE.",
+        &[
+            (
+                "{kind=\"node\"}".to_string(),
+                node_status.node_earnings_total as f64 / 1_000_000.0,
+            ),
+            (
+                "{kind=\"lottery\"}".to_string(),
+                node_status.lottery_earnings_total as f64 / 1_000_000.0,
+            ),
+        ],
+    );
+
+    if let Some(config_summary) = &status_response.config {
+        // Index timestamps are in milliseconds.
+        push_metric(
+            &mut metrics,
+            "edge_last_payout_timestamp_seconds",
+            "gauge",
+            "Unix time of the last processed node payout.",
+            &[(
+                String::new(),
+                config_summary.last_node_payment as f64 / 1000.0,
+            )],
+        );
+    }
+
+    if let Some(requirement_report) = &node_status.last_requirement_report {
+        let check_samples: Vec<(String, f64)> = requirement_report
+            .checks
+            .iter()
+            .map(|check| {
+                (
+                    format!("{{check=\"{}\"}}", escape_label_value(&check.name)),
+                    if check.passed { 1.0 } else { 0.0 },
+                )
+            })
+            .collect();
+        push_metric(
+            &mut metrics,
+            "edge_requirement_check_passed",
+            "gauge",
+            "Result of the last system requirement check per check.",
+            &check_samples,
+        );
+        push_metric(
+            &mut metrics,
+            "edge_requirement_check_timestamp_seconds",
+            "gauge",
+            "Unix time of the last system requirement check.",
+            &[(String::new(), requirement_report.checked_at as f64)],
+        );
+    }
+
+    if let Some(cli_version) = &status_response.cli_version {
+        push_metric(
+            &mut metrics,
+            "edge_cli_info",
+            "gauge",
+            "Installed Edge CLI version.",
+            &[(
+                format!("{{version=\"{}\"}}", escape_label_value(cli_version)),
+                1.0,
+            )],
+        );
+    }
+
+    metrics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_status::{RequirementCheckResult, RequirementReport};
+
+    fn status_response() -> StatusResponse {
+        StatusResponse {
+            gui_version: "1.0.0".to_string(),
+            config: Some(ConfigSummary {
+                initialized: true,
+                is_auto_start_enabled: true,
+                launch_minimized: false,
+                last_node_payment: 1_700_000_000_500,
+                wallet_address: String::new(),
+                network: "mainnet".to_string(),
+                address: String::new(),
+                stake_id: String::new(),
+                notification_channels: 0,
+            }),
+            container_state: Some("running".to_string()),
+            cli_version: Some("1.2.3 \"beta\"".to_string()),
+            node_status: NodeStatus {
+                online: Some(true),
+                device_starts_total: 2,
+                node_earnings_total: 1_500_000,
+                lottery_earnings_total: 0,
+                last_requirement_report: Some(RequirementReport {
+                    checked_at: 1_700_000_000,
+                    passed: false,
+                    checks: vec![
+                        RequirementCheckResult {
+                            name: "docker".to_string(),
+                            passed: true,
+                            message: String::new(),
+                        },
+                        RequirementCheckResult {
+                            name: "disk".to_string(),
+                            passed: false,
+                            message: String::new(),
+                        },
+                    ],
+                    docker: None,
+                }),
+            },
+        }
+    }
+
+    fn sample_lines(metrics: &str) -> Vec<&str> {
+        metrics
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .collect()
+    }
+
+    #[test]
+    fn renders_all_metrics() {
+        let metrics = render_metrics(&status_response());
+        assert_eq!(
+            sample_lines(&metrics),
+            vec![
+                "edge_node_online 1",
+                "edge_device_container_running 1",
+                "edge_device_restarts_total 2",
+                "edge_earnings_xe_total{kind=\"node\"} 1.5",
+                "edge_earnings_xe_total{kind=\"lottery\"} 0",
+                "edge_last_payout_timestamp_seconds 1700000000.5",
+                "edge_requirement_check_passed{check=\"docker\"} 1",
+                "edge_requirement_check_passed{check=\"disk\"} 0",
+                "edge_requirement_check_timestamp_seconds 1700000000",
+                "edge_cli_info{version=\"1.2.3 \\\"beta\\\"\"} 1",
+            ]
+        );
+        assert!(metrics.contains("# TYPE edge_earnings_xe_total counter\n"));
+        assert!(metrics.contains("# TYPE edge_node_online gauge\n"));
+    }
+
+    #[test]
+    fn skips_unknown_values() {
+        let mut status_response = status_response();
+        status_response.config = None;
+        status_response.container_state = None;
+        status_response.cli_version = None;
+        status_response.node_status = NodeStatus::default();
+
+        let metrics = render_metrics(&status_response);
+        let samples = sample_lines(&metrics);
+        assert!(!samples
+            .iter()
+            .any(|line| line.starts_with("edge_node_online")));
+        assert!(samples.contains(&"edge_device_container_running 0"));
+        assert!(!metrics.contains("edge_last_payout_timestamp_seconds"));
+        assert!(!metrics.contains("edge_requirement_check_passed"));
+        assert!(!metrics.contains("edge_cli_info"));
+    }
+}
@@ -0,0 +1,220 @@
+use chrono::Utc;
+use serde::Serialize;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::check_requirements;
+use crate::check_requirements::check_edge::{
+    get_edge_cli_checksum, hash_file, CHECKSUM_CACHE_SECONDS,
+};
+use crate::check_requirements::check_specifications::{
+    get_os_info, get_processor_info, OsInfo, ProcessorInfo,
+};
+use crate::check_requirements::RequirementChecks;
+use crate::config::get_config;
+use crate::config::getters::get_edge_binary_name;
+use crate::control_edge_cli::get_edge_cli_version;
+use crate::docker::{get_docker_command_output, get_edge_device_container_logs};
+use crate::logging::{export_recent_log, log_error, log_info, redact_secrets};
+use crate::node_status::{get_node_status, RequirementReport};
+use crate::BackendCommunicator;
+
+/// Number of log entries in a bundle.
+const BUNDLE_LOG_ENTRIES: usize = 5000;
+/// Number of Edge device container log lines in a bundle.
+const BUNDLE_CONTAINER_LOG_LINES: usize = 1000;
+
+/// Contents of system.json.
+#[derive(Serialize)]
+struct SystemReport {
+    gui_version: String,
+    created_at: String, // RFC 3339
+    os: OsInfo,
+    processor: ProcessorInfo,
+}
+
+/// Contents of edge_cli.json.
+#[derive(Serialize, Default)]
+struct EdgeCliReport {
+    binary_name: String, // eg. edge or edgetest.exe
+    version: Option<String>,
+    installed_checksum: Option<String>, // SHA256 of the installed binary
+    published_checksum: Option<String>, // SHA256 published for the latest release
+    checksum_matches: Option<bool>,     // None if either checksum is unknown
+    errors: Vec<String>,
+}
+
+/// Version and checksum status of the installed Edge CLI.
+async fn get_edge_cli_report(backend_communicator: &BackendCommunicator) -> EdgeCliReport {
+    let binary_name = get_edge_binary_name(backend_communicator);
+    let binary_path = format!("{}{}", backend_communicator.data_dir, binary_name);
+    let mut edge_cli_report = EdgeCliReport {
+        binary_name,
+        ..Default::default()
+    };
+
+    match get_edge_cli_version(backend_communicator).await {
+        Ok(version) => edge_cli_report.version = Some(version),
+        Err(err) => edge_cli_report.errors.push(err.to_string()),
+    }
+    if Path::new(&binary_path).exists() {
+        match hash_file(Path::new(&binary_path), backend_communicator) {
+            Ok(checksum) => edge_cli_report.installed_checksum = Some(checksum),
+            Err(err) => edge_cli_report.errors.push(err),
+        }
+    }
+    match get_edge_cli_checksum(CHECKSUM_CACHE_SECONDS, backend_communicator).await {
+        Ok(checksum) => edge_cli_report.published_checksum = Some(checksum.trim().to_string()),
+        Err(err) => edge_cli_report.errors.push(err),
+    }
+    if let (Some(installed_checksum), Some(published_checksum)) = (
+        &edge_cli_report.installed_checksum,
+        &edge_cli_report.published_checksum,
+    ) {
+        edge_cli_report.checksum_matches = Some(installed_checksum == published_checksum);
+    }
+    edge_cli_report
+}
+
+/// Run every requirement check for a current report. The Edge CLI is reported in edge_cli.json.
+async fn get_requirement_report(
+    backend_communicator: &BackendCommunicator,
+) -> Option<RequirementReport> {
+    let requirement_checks = RequirementChecks {
+        edge_cli_binary: false,
+        ..RequirementChecks::all()
+    };
+    let _ = check_requirements::main(&requirement_checks, backend_communicator).await;
+    get_node_status().last_requirement_report
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    match serde_json::to_string_pretty(value) {
+        Ok(json_str) => json_str,
+        Err(err) => format!("Unable to serialize. Err: {}", err),
+    }
+}
+
+/// Output of a diagnostic, or the reason it is missing.
+fn output_or_error(output: Result<String, String>) -> String {
+    match output {
+        Ok(output) => output,
+        Err(err) => format!("Unavailable: {}", err),
+    }
+}
+
+/// A directory gets a timestamped bundle name, any other path is used as is.
+fn get_bundle_path(output_path: &str) -> PathBuf {
+    let output_path = Path::new(output_path);
+    if output_path.is_dir() {
+        output_path.join(format!(
+            "edge-staking-gui-support-{}.zip",
+            Utc::now().format("%Y%m%d-%H%M%S")
+        ))
+    } else {
+        output_path.to_path_buf()
+    }
+}
+
+fn write_bundle(bundle_path: &Path, bundle_files: &[(&str, String)]) -> Result<(), String> {
+    let bundle_file = File::create(bundle_path).map_err(|err| {
+        format!(
+            "Unable to create support bundle {}. Err: {}",
+            bundle_path.display(),
+            err
+        )
+    })?;
+    let mut zip_writer = ZipWriter::new(bundle_file);
+    let file_options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for (file_name, content) in bundle_files {
+        zip_writer
+            .start_file(*file_name, file_options)
+            .map_err(|err| {
+                format!(
+                    "Unable to add {} to support bundle. Err: {}",
+                    file_name, err
+                )
+            })?;
+        zip_writer.write_all(content.as_bytes()).map_err(|err| {
+            format!(
+                "Unable to write {} to support bundle. Err: {}",
+                file_name, err
+            )
+        })?;
+    }
+    zip_writer
+        .finish()
+        .map_err(|err| format!("Unable to finish support bundle. Err: {}", err))?;
+    Ok(())
+}
+
+/// Create a zip with everything support needs to diagnose the node: the recent log, the config
+/// without secrets, the requirement report, system info, Docker version & info, the Edge CLI
+/// version & checksum status and the last Edge device container logs.
+/// `output_path` is a zip file or a directory to create it in. Returns the path of the zip.
+pub async fn create_support_bundle(
+    output_path: &str,
+    backend_communicator: &BackendCommunicator,
+) -> Result<String, String> {
+    log_info!("Creating support bundle.".to_string(), backend_communicator);
+
+    let config_json = match get_config(backend_communicator) {
+        Ok(config) => to_json(&config.without_secrets()),
+        Err(err) => format!("Unavailable: {}", err),
+    };
+    let system_report = SystemReport {
+        gui_version: backend_communicator
+            .front_end_window
+            .app_handle()
+            .package_info()
+            .version
+            .to_string(),
+        created_at: Utc::now().to_rfc3339(),
+        os: get_os_info(backend_communicator),
+        processor: get_processor_info(backend_communicator),
+    };
+    let requirement_report = get_requirement_report(backend_communicator).await;
+    let docker_version = get_docker_command_output(&["version"], backend_communicator).await;
+    let docker_info = get_docker_command_output(&["info"], backend_communicator).await;
+    let edge_cli_report = get_edge_cli_report(backend_communicator).await;
+    let container_logs =
+        get_edge_device_container_logs(BUNDLE_CONTAINER_LOG_LINES, backend_communicator).await;
+
+    let bundle_files: Vec<(&str, String)> = vec![
+        (
+            "log.txt",
+            export_recent_log(BUNDLE_LOG_ENTRIES, backend_communicator),
+        ),
+        ("config.json", config_json),
+        ("requirements.json", to_json(&requirement_report)),
+        ("system.json", to_json(&system_report)),
+        ("docker_version.txt", output_or_error(docker_version)),
+        ("docker_info.txt", output_or_error(docker_info)),
+        ("edge_cli.json", to_json(&edge_cli_report)),
+        ("container_logs.txt", output_or_error(container_logs)),
+    ]
+    .into_iter()
+    .map(|(file_name, content)| (file_name, redact_secrets(&content, backend_communicator)))
+    .collect();
+
+    let bundle_path = get_bundle_path(output_path);
+    match write_bundle(&bundle_path, &bundle_files) {
+        Ok(_) => {
+            let bundle_path_str = bundle_path.display().to_string();
+            log_info!(
+                format!("Support bundle written to {}", bundle_path_str),
+                backend_communicator,
+            );
+            Ok(bundle_path_str)
+        }
+        Err(err) => {
+            log_error!(err.clone(), backend_communicator);
+            Err(err)
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use chrono::{Local, TimeZone};
+use std::time::Duration;
+use tauri::{
+    AppHandle, CustomMenuItem, Icon, Manager, SystemTray, SystemTrayEvent, SystemTrayMenu,
+    SystemTrayMenuItem,
+};
+use tokio::sync::Notify;
+
+use crate::check_requirements::{self, RequirementChecks};
+use crate::config::getters::{get_config_path_as_str, get_last_node_payment};
+use crate::control_edge_cli::{self, get_edge_cli_version, start_step, stop_step};
+use crate::docker::get_edge_device_container_state;
+use crate::logging::{get_log_path, log_error, log_info, log_warn};
+use crate::node_status::get_node_status;
+use crate::process_runner::run_process;
+use crate::shutdown::{request_shutdown, spawn_background_task};
+use crate::BackendCommunicator;
+
+/// Refresh interval for changes the GUI is not told about, eg. the device stopped outside the GUI.
+const TRAY_REFRESH_INTERVAL: Duration = Duration::from_secs(300);
+const OPEN_PATH_TIMEOUT: Duration = Duration::from_secs(30);
+const TRAY_ICON_ONLINE: &[u8] = include_bytes!("../../icons/32x32.png");
+const TRAY_ICON_OFFLINE: &[u8] = include_bytes!("../../icons/tray-offline.png");
+
+/// Wakes the tray updater when the node status changes.
+static TRAY_REFRESH: Notify = Notify::const_new();
+
+/// State of the node as shown in the tray.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TrayNodeState {
+    Online,
+    Offline, // Device runs, but the index does not see it online.
+    Stopped,
+    Unknown, // Docker not reachable or not reported by the index yet.
+}
+
+impl TrayNodeState {
+    fn title(&self) -> &'static str {
+        match self {
+            TrayNodeState::Online => "Node: online",
+            TrayNodeState::Offline => "Node: offline",
+            TrayNodeState::Stopped => "Node: stopped",
+            TrayNodeState::Unknown => "Node: unknown",
+        }
+    }
+}
+
+/// Tray menu with the node status on top, updated by `start_tray_updater`.
+pub fn create_system_tray() -> SystemTray {
+    let info_item = |id: &str, title: &str| CustomMenuItem::new(id.to_string(), title).disabled();
+    let tray_menu = SystemTrayMenu::new()
+        .add_item(info_item("node_status", TrayNodeState::Unknown.title()))
+        .add_item(info_item("last_payout", "Last payout: unknown"))
+        .add_item(info_item("cli_version", "Edge CLI: unknown"))
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(CustomMenuItem::new("show".to_string(), "Show"))
+        .add_item(CustomMenuItem::new(
+            "start_device".to_string(),
+            "Start Node",
+        ))
+        .add_item(CustomMenuItem::new("stop_device".to_string(), "Stop Node"))
+        .add_item(CustomMenuItem::new(
+            "restart_device".to_string(),
+            "Restart Node",
+        ))
+        .add_item(CustomMenuItem::new(
+            "update_device".to_string(),
+            "Update Node",
+        ))
+        .add_item(CustomMenuItem::new("run_checks".to_string(), "Run Checks"))
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(CustomMenuItem::new("open_log".to_string(), "Open Log"))
+        .add_item(CustomMenuItem::new(
+            "open_config_dir".to_string(),
+            "Open Config Folder",
+        ))
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(CustomMenuItem::new("quit".to_string(), "Quit"));
+    SystemTray::new()
+        .with_menu(tray_menu)
+        .with_tooltip("Edge Staking GUI")
+}
+
+/// Ask the tray updater to refresh the tray now.
+pub fn request_tray_refresh() {
+    TRAY_REFRESH.notify_one();
+}
+
+fn format_last_payout(last_node_payment: u64) -> String {
+    // Index timestamps are in milliseconds.
+    match Local
+        .timestamp_millis_opt(last_node_payment as i64)
+        .single()
+    {
+        Some(last_payout) if last_node_payment > 0 => {
+            format!("Last payout: {}", last_payout.format("%Y-%m-%d %H:%M"))
+        }
+        _ => "Last payout: none yet".to_string(),
+    }
+}
+
+/// Update the status items, the enabled actions, the icon and the tooltip.
+async fn refresh_tray(backend_communicator: &BackendCommunicator) -> Result<(), String> {
+    let container_state = get_edge_device_container_state(backend_communicator)
+        .await
+        .ok();
+    let node_state = match (container_state.as_deref(), get_node_status().online) {
+        (None, _) => TrayNodeState::Unknown,
+        (Some("running"), Some(true)) => TrayNodeState::Online,
+        (Some("running"), Some(false)) => TrayNodeState::Offline,
+        (Some("running"), None) => TrayNodeState::Unknown,
+        (Some(_), _) => TrayNodeState::Stopped,
+    };
+    let cli_version = match get_edge_cli_version(backend_communicator).await {
+        Ok(version) => format!("Edge CLI: {}", version),
+        Err(_) => "Edge CLI: not installed".to_string(),
+    };
+    let last_payout = format_last_payout(get_last_node_payment(backend_communicator));
+
+    let tray_handle = backend_communicator
+        .front_end_window
+        .app_handle()
+        .tray_handle();
+    let err_message = |err: tauri::Error| format!("Unable to update the tray. Err: {}", err);
+    tray_handle
+        .get_item("node_status")
+        .set_title(node_state.title())
+        .map_err(err_message)?;
+    tray_handle
+        .get_item("last_payout")
+        .set_title(last_payout)
+        .map_err(err_message)?;
+    tray_handle
+        .get_item("cli_version")
+        .set_title(cli_version)
+        .map_err(err_message)?;
+    let device_running = container_state.as_deref() == Some("running");
+    tray_handle
+        .get_item("start_device")
+        .set_enabled(!device_running)
+        .map_err(err_message)?;
+    tray_handle
+        .get_item("stop_device")
+        .set_enabled(container_state.is_none() || device_running)
+        .map_err(err_message)?;
+
+    let tray_icon = if node_state == TrayNodeState::Online {
+        TRAY_ICON_ONLINE
+    } else {
+        TRAY_ICON_OFFLINE
+    };
+    tray_handle
+        .set_icon(Icon::Raw(tray_icon.to_vec()))
+        .map_err(err_message)?;
+    tray_handle
+        .set_tooltip(&format!("Edge Staking GUI - {}", node_state.title()))
+        .map_err(err_message)?;
+    Ok(())
+}
+
+/// Keep the tray up to date, on every status change and periodically.
+pub fn start_tray_updater(backend_communicator: BackendCommunicator) {
+    spawn_background_task(async move {
+        loop {
+            if let Err(err) = refresh_tray(&backend_communicator).await {
+                log_warn!(err, &backend_communicator);
+            }
+            tokio::select! {
+                _ = TRAY_REFRESH.notified() => {}
+                _ = tokio::time::sleep(TRAY_REFRESH_INTERVAL) => {}
+            }
+        }
+    });
+}
+
+/// Open a file or folder with the default program of the OS.
+/// The exit code is ignored, explorer returns 1 also when it opened the path.
+async fn open_path(path: &str, backend_communicator: &BackendCommunicator) -> Result<(), String> {
+    let program = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    run_process(program, &[path], OPEN_PATH_TIMEOUT, backend_communicator)
+        .await
+        .map(|_| ())
+        .map_err(|err| format!("Unable to open {}. Err: {}", path, err))
+}
+
+fn show_main_window(app_handle: &AppHandle) {
+    if let Some(window) = app_handle.get_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Run a device action started from the tray menu in the background.
+fn device_action_from_tray(app_handle: &AppHandle, action: &str) {
+    let backend_communicator = match BackendCommunicator::from_app_handle(app_handle) {
+        Ok(backend_communicator) => backend_communicator,
+        Err(err) => {
+            eprintln!("Unable to run {} from tray. Err: {}", action, err);
+            return;
+        }
+    };
+    let action = action.to_string();
+    spawn_background_task(async move {
+        match action.as_str() {
+            "start_device" => match start_step(false, &backend_communicator).await {
+                Ok(ok_message) => log_info!(ok_message, &backend_communicator),
+                Err(err) => log_error!(
+                    format!("Unable to start the device. {}", err),
+                    &backend_communicator,
+                ),
+            },
+            "stop_device" => match stop_step(false, &backend_communicator).await {
+                Ok(ok_message) => log_info!(ok_message, &backend_communicator),
+                Err(err) => log_error!(
+                    format!("Unable to stop the device. {}", err),
+                    &backend_communicator,
+                ),
+            },
+            "restart_device" => {
+                control_edge_cli::device_restart(false, &backend_communicator).await;
+            }
+            "update_device" => {
+                control_edge_cli::device_update(false, &backend_communicator).await;
+            }
+            "run_checks" => {
+                let _ = check_requirements::main(&RequirementChecks::all(), &backend_communicator)
+                    .await;
+            }
+            "open_log" => {
+                if let Err(err) =
+                    open_path(&get_log_path(&backend_communicator), &backend_communicator).await
+                {
+                    log_error!(err, &backend_communicator);
+                }
+            }
+            "open_config_dir" => {
+                let config_path = get_config_path_as_str(&backend_communicator);
+                let config_dir = std::path::Path::new(&config_path)
+                    .parent()
+                    .map(|config_dir| config_dir.to_string_lossy().to_string())
+                    .unwrap_or(backend_communicator.data_dir.clone());
+                if let Err(err) = open_path(&config_dir, &backend_communicator).await {
+                    log_error!(err, &backend_communicator);
+                }
+            }
+            _ => {}
+        }
+        request_tray_refresh();
+    });
+}
+
+pub fn handle_system_tray_event(app_handle: &AppHandle, event: SystemTrayEvent) {
+    match event {
+        SystemTrayEvent::DoubleClick { .. } => show_main_window(app_handle),
+        SystemTrayEvent::MenuItemClick { id, .. } => match id.as_str() {
+            "show" => show_main_window(app_handle),
+            "quit" => request_shutdown(app_handle),
+            action => device_action_from_tray(app_handle, action),
+        },
+        _ => {}
+    }
+}
@@ -1,27 +1,82 @@
-use chrono::{DateTime, Utc};
 use futures::StreamExt;
-use reqwest::Client;
-use std::fs::{File, OpenOptions};
+use reqwest::header::RANGE;
+use reqwest::{Client, Proxy, StatusCode};
+use std::fs::{self, OpenOptions};
 use std::io::Write;
-use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
+use crate::config::getters::get_download_config;
+use crate::logging::{log_debug, log_error, log_info, log_warn};
+use crate::shutdown::is_shutting_down;
 use crate::BackendCommunicator;
 
-#[derive(Clone, serde::Serialize)]
-struct Payload {
-    message: String,
+/// Event on which download progress is emitted to the frontend.
+const DOWNLOAD_PROGRESS_LISTENER: &str = "download_progress_listener";
+/// Attempts before a download is given up, including the first attempt.
+const MAX_DOWNLOAD_ATTEMPTS: u32 = 5;
+/// Backoff before the first retry, doubled on every following retry.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(2);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Maximum time without receiving any data before an attempt is aborted.
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+/// Minimum time between two progress events.
+const PROGRESS_EMIT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Progress of a running download, emitted on `DOWNLOAD_PROGRESS_LISTENER`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DownloadProgress {
+    pub url: String,
+    pub bytes_downloaded: u64,
+    pub total_bytes: Option<u64>, // None if the server does not send a Content-Length
+    pub bytes_per_second: f64,
+    pub eta_seconds: Option<u64>, // None if the total size is unknown
+    pub finished: bool,
+}
+
+/// Reason a download attempt failed, decides whether the download is retried.
+enum DownloadAttemptError {
+    Retryable(String),
+    Fatal(String),
+    NotFound(String),  // The server answered 404, the file is not published.
+    Cancelled(String), // The program is quitting, the part file is kept to resume later.
 }
 
-/// Download a file from a url to a local download path
+/// Download a file from a url to a local download path.
+/// Besides http(s) urls, `file://` urls and local paths are copied, eg. from an offline mirror.
+/// Downloads to a temporary `.part` file first, which is renamed once complete.
+/// Interrupted downloads are resumed with a HTTP Range request and retried with exponential backoff.
+/// The file is not made executable, binaries get their permissions once they are verified.
 pub async fn download_file(
     download_url: String,
     download_path_str: String,
     backend_communicator: &BackendCommunicator,
 ) -> Result<(), String> {
-    let download_path = PathBuf::new();
-    let download_path = download_path.join(download_path_str.clone());
+    download_to_path(download_url, download_path_str, false, backend_communicator)
+        .await
+        .map(|_| ())
+}
 
-    log_and_emit(
+/// Download a file which may not be published, eg. a detached signature.
+/// Returns false if the file does not exist at the url, which is only logged at debug level.
+pub async fn download_optional_file(
+    download_url: String,
+    download_path_str: String,
+    backend_communicator: &BackendCommunicator,
+) -> Result<bool, String> {
+    download_to_path(download_url, download_path_str, true, backend_communicator).await
+}
+
+/// Download or copy a url to the download path. Returns false if the url does not exist and
+/// `is_optional` is set, a missing url is an error otherwise.
+async fn download_to_path(
+    download_url: String,
+    download_path_str: String,
+    is_optional: bool,
+    backend_communicator: &BackendCommunicator,
+) -> Result<bool, String> {
+    let part_path_str = format!("{}.part", download_path_str);
+
+    log_info!(
         format!(
             "Preparing to download {} to {}.",
             download_url.clone(),
@@ -30,198 +85,403 @@ pub async fn download_file(
         backend_communicator,
     );
 
-    let client = Client::new();
-    let response = client
-        .get(download_url.clone())
-        .send()
-        .await
-        .or(Err(format!(
-            "Error while downloading file {} to {}.",
-            download_url.clone(),
-            download_path_str.clone()
-        )));
-
-    let filesize;
-    let valid_response;
-    match response {
-        Ok(ok_response) => {
-            valid_response = ok_response;
-            match valid_response.content_length() {
-                Some(ok_filesize) => filesize = ok_filesize,
-                None => {
-                    let error_message = "Unable to read filesize from valid response.".to_string();
-                    log_and_emit(error_message.clone(), backend_communicator);
-                    return Err(error_message);
-                }
-            };
-            log_and_emit(
-                format!("Download Headers: {:#?}", valid_response.headers()),
+    let is_found = if download_url.starts_with("http://") || download_url.starts_with("https://") {
+        download_remote_file(
+            &download_url,
+            &part_path_str,
+            is_optional,
+            backend_communicator,
+        )
+        .await?
+    } else {
+        copy_local_file(
+            &download_url,
+            &part_path_str,
+            is_optional,
+            backend_communicator,
+        )?
+    };
+    if !is_found {
+        return Ok(false);
+    }
+
+    // Only replace the existing file once the download is complete.
+    if let Err(err) = fs::rename(&part_path_str, &download_path_str) {
+        let error_message = format!("Error moving downloaded file to {}. Potential solution: Run the program as administrator. Error = {}", download_path_str, err);
+        log_error!(error_message.clone(), backend_communicator);
+        return Err(error_message);
+    }
+
+    log_info!(
+        format!("File downloaded: {} !", download_url),
+        backend_communicator,
+    );
+
+    Ok(true)
+}
+
+/// Set excecutable permission on Unix system (including MacOS).
+#[cfg(target_family = "unix")]
+pub fn set_unix_permissions(file_path_str: String, backend_communicator: &BackendCommunicator) {
+    use std::os::unix::fs::PermissionsExt;
+    let permissions_code = 0o744; //rwxr--r--
+    match fs::set_permissions(
+        file_path_str.clone(),
+        PermissionsExt::from_mode(permissions_code),
+    ) {
+        Ok(_) => {
+            let ok_msg = format!(
+                "Set file {} permissions to {}",
+                file_path_str.clone(),
+                permissions_code
+            );
+            log_info!(ok_msg, backend_communicator);
+        }
+        Err(err) => {
+            let err_msg = format!(
+                "Could not set file {} permissions to {}. Err: {}",
+                file_path_str.clone(),
+                permissions_code,
+                err
+            );
+            log_error!(err_msg, backend_communicator);
+        }
+    }
+}
+
+/// Copy a file from a `file://` url or local path into the part file.
+/// Returns false if the file does not exist and `is_optional` is set.
+fn copy_local_file(
+    source_url: &str,
+    part_path_str: &str,
+    is_optional: bool,
+    backend_communicator: &BackendCommunicator,
+) -> Result<bool, String> {
+    let mut source_path_str = source_url.strip_prefix("file://").unwrap_or(source_url);
+    // file:///C:/edge on Windows
+    if cfg!(target_os = "windows") && source_path_str.starts_with('/') {
+        source_path_str = &source_path_str[1..];
+    }
+
+    match fs::copy(source_path_str, part_path_str) {
+        Ok(bytes_copied) => {
+            emit_download_progress(
+                DownloadProgress {
+                    url: source_url.to_string(),
+                    bytes_downloaded: bytes_copied,
+                    total_bytes: Some(bytes_copied),
+                    bytes_per_second: 0.0,
+                    eta_seconds: Some(0),
+                    finished: true,
+                },
                 backend_communicator,
             );
+            Ok(true)
         }
-        Err(_) => {
-            log_and_emit(
-                "Unable to read filesize from response.".to_string(),
+        Err(err) if is_optional && err.kind() == std::io::ErrorKind::NotFound => {
+            let _ = fs::remove_file(part_path_str);
+            log_debug!(
+                format!("{} does not exist, skipping it.", source_path_str),
                 backend_communicator,
             );
-            return Err("Unable to read filesize from response.".to_string());
+            Ok(false)
+        }
+        Err(err) => {
+            let _ = fs::remove_file(part_path_str);
+            let error_message = format!("Unable to copy {}. Err: {}", source_path_str, err);
+            log_error!(error_message.clone(), backend_communicator);
+            Err(error_message)
         }
     }
+}
 
-    log_and_emit(
-        format!(
-            "Opening file: {}. Program may be temporarily unresponsive while writing.",
-            download_path_str.clone()
-        ),
-        backend_communicator,
-    );
-    let mut file;
-    match File::create(download_path) {
-        Ok(ok_file) => file = ok_file,
+/// Download a http(s) url into the part file, retrying failed attempts.
+/// Returns false if the server answers 404 and `is_optional` is set.
+async fn download_remote_file(
+    download_url: &str,
+    part_path_str: &str,
+    is_optional: bool,
+    backend_communicator: &BackendCommunicator,
+) -> Result<bool, String> {
+    let download_config = get_download_config(backend_communicator);
+    let mut client_builder = Client::builder().connect_timeout(CONNECT_TIMEOUT);
+    if !download_config.proxy.is_empty() {
+        match Proxy::all(&download_config.proxy) {
+            Ok(proxy) => client_builder = client_builder.proxy(proxy),
+            Err(err) => {
+                let error_message = format!(
+                    "Invalid download proxy {}. Err: {}",
+                    download_config.proxy, err
+                );
+                log_error!(error_message.clone(), backend_communicator);
+                return Err(error_message);
+            }
+        }
+    }
+
+    let client;
+    match client_builder.build() {
+        Ok(ok_client) => client = ok_client,
         Err(err) => {
-            let error_message = format!("Error creating File based on filename. Potential solution: Run the program as administrator. Error = {}", err);
+            let error_message = format!("Unable to create download http client. Err: {}", err);
+            log_error!(error_message.clone(), backend_communicator);
             return Err(error_message);
         }
     }
 
-    let mut bytes_downloaded: u64 = 0;
-    let mut download_stream = valid_response.bytes_stream();
-    let mut chunk_counter: u64 = 0;
-    while let Some(stream_content) = download_stream.next().await {
-        let stream_chunk = match stream_content {
-            Ok(ok_chunk) => ok_chunk,
-            Err(_) => {
-                let error_message = format!("Unable to read chunk {}", chunk_counter);
-                log_and_emit(error_message.clone(), backend_communicator);
+    let mut retry_delay = INITIAL_RETRY_DELAY;
+    let mut attempt: u32 = 1;
+    loop {
+        match download_attempt(&client, download_url, part_path_str, backend_communicator).await {
+            Ok(_) => return Ok(true),
+            Err(DownloadAttemptError::NotFound(error_message)) if is_optional => {
+                log_debug!(error_message, backend_communicator);
+                let _ = fs::remove_file(part_path_str);
+                return Ok(false);
+            }
+            Err(DownloadAttemptError::Fatal(error_message))
+            | Err(DownloadAttemptError::NotFound(error_message)) => {
+                log_error!(error_message.clone(), backend_communicator);
+                let _ = fs::remove_file(part_path_str);
                 return Err(error_message);
             }
-        };
-        match file.write_all(&stream_chunk) {
-            Ok(_) => {}
-            Err(_) => {
-                let error_message = "Unable to write chunk.".to_string();
-                log_and_emit(error_message.clone(), backend_communicator);
+            Err(DownloadAttemptError::Cancelled(error_message)) => {
+                log_warn!(error_message.clone(), backend_communicator);
                 return Err(error_message);
             }
+            Err(DownloadAttemptError::Retryable(error_message)) => {
+                if attempt >= MAX_DOWNLOAD_ATTEMPTS || is_shutting_down() {
+                    let error_message = format!(
+                        "Download of {} failed after {} attempts. Err: {}",
+                        download_url, attempt, error_message
+                    );
+                    log_error!(error_message.clone(), backend_communicator);
+                    return Err(error_message);
+                }
+                log_warn!(
+                    format!(
+                        "Download attempt {}/{} failed, retrying in {} seconds. Err: {}",
+                        attempt,
+                        MAX_DOWNLOAD_ATTEMPTS,
+                        retry_delay.as_secs(),
+                        error_message
+                    ),
+                    backend_communicator,
+                );
+                tokio::time::sleep(retry_delay).await;
+                retry_delay *= 2;
+                attempt += 1;
+            }
         }
-        let bytes_added = stream_chunk.len() as u64;
-        bytes_downloaded += bytes_added;
-        if chunk_counter % 250 == 0 {
-            log_and_emit(
-                format!(
-                    "Downloading file: {} to {}. Downloaded {} / {} bytes",
-                    download_url.clone(),
-                    download_path_str,
-                    bytes_downloaded,
-                    filesize
-                ),
-                backend_communicator,
-            )
+    }
+}
+
+/// Download (the rest of) a file into the part file, resuming from its current size.
+async fn download_attempt(
+    client: &Client,
+    download_url: &str,
+    part_path_str: &str,
+    backend_communicator: &BackendCommunicator,
+) -> Result<(), DownloadAttemptError> {
+    let resume_from = match fs::metadata(part_path_str) {
+        Ok(metadata) => metadata.len(),
+        Err(_) => 0,
+    };
+
+    let mut request = client.get(download_url);
+    if resume_from > 0 {
+        request = request.header(RANGE, format!("bytes={}-", resume_from));
+    }
+
+    let response;
+    match request.send().await {
+        Ok(ok_response) => response = ok_response,
+        Err(err) => {
+            return Err(DownloadAttemptError::Retryable(format!(
+                "Error while downloading file {}. Err: {}",
+                download_url,
+                err.without_url()
+            )))
         }
-        chunk_counter += 1;
     }
 
-    log_and_emit(
-        format!("File downloaded: {} !", download_url),
-        backend_communicator,
-    );
+    let status = response.status();
+    if status == StatusCode::RANGE_NOT_SATISFIABLE {
+        // Part file does not match the remote file, start over.
+        let _ = fs::remove_file(part_path_str);
+        return Err(DownloadAttemptError::Retryable(
+            "Server rejected resume request.".to_string(),
+        ));
+    }
+    if !status.is_success() {
+        let error_message = format!(
+            "Error while downloading file {}. Received http status {}",
+            download_url, status
+        );
+        if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(DownloadAttemptError::Retryable(error_message));
+        }
+        if status == StatusCode::NOT_FOUND {
+            return Err(DownloadAttemptError::NotFound(error_message));
+        }
+        return Err(DownloadAttemptError::Fatal(error_message));
+    }
 
-    #[cfg(target_family = "unix")]
-    /// Set excecutable permission on Unix system (including MacOS).
-    fn set_unix_permissions(download_path_str: String, backend_communicator: &BackendCommunicator) {
-        use std::fs;
-        use std::os::unix::fs::PermissionsExt;
-        let permissions_code = 0o744; //rwxr--r--
-        match fs::set_permissions(
-            download_path_str.clone(),
-            PermissionsExt::from_mode(permissions_code),
-        ) {
-            Ok(_) => {
-                let ok_msg = format!(
-                    "Set file {} permissions to {}",
-                    download_path_str.clone(),
-                    permissions_code
-                );
-                log_and_emit(ok_msg, backend_communicator);
+    // Servers without Range support answer with the full file.
+    let is_resumed = resume_from > 0 && status == StatusCode::PARTIAL_CONTENT;
+    let mut bytes_downloaded = if is_resumed { resume_from } else { 0 };
+    let total_bytes = response
+        .content_length()
+        .map(|content_length| content_length + bytes_downloaded);
+
+    if is_resumed {
+        log_info!(
+            format!(
+                "Resuming download of {} at {} bytes.",
+                download_url, bytes_downloaded
+            ),
+            backend_communicator,
+        );
+    }
+
+    let mut file;
+    match OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(is_resumed)
+        .truncate(!is_resumed)
+        .open(part_path_str)
+    {
+        Ok(ok_file) => file = ok_file,
+        Err(err) => {
+            let error_message = format!("Error creating File based on filename. Potential solution: Run the program as administrator. Error = {}", err);
+            return Err(DownloadAttemptError::Fatal(error_message));
+        }
+    }
+
+    let started_at = Instant::now();
+    let bytes_at_start = bytes_downloaded;
+    let mut last_emitted_at: Option<Instant> = None;
+    let mut download_stream = response.bytes_stream();
+    loop {
+        if is_shutting_down() {
+            return Err(DownloadAttemptError::Cancelled(format!(
+                "Download of {} cancelled, the program is quitting.",
+                download_url
+            )));
+        }
+        let stream_content = match tokio::time::timeout(READ_TIMEOUT, download_stream.next()).await
+        {
+            Ok(Some(stream_content)) => stream_content,
+            Ok(None) => break,
+            Err(_) => {
+                return Err(DownloadAttemptError::Retryable(format!(
+                    "No data received for {} seconds.",
+                    READ_TIMEOUT.as_secs()
+                )))
             }
+        };
+        let stream_chunk = match stream_content {
+            Ok(ok_chunk) => ok_chunk,
             Err(err) => {
-                let err_msg = format!(
-                    "Could not set file {} permissions to {}. Err: {}",
-                    download_path_str.clone(),
-                    permissions_code,
-                    err
-                );
-                log_and_emit(err_msg, backend_communicator);
+                return Err(DownloadAttemptError::Retryable(format!(
+                    "Unable to read chunk. Err: {}",
+                    err.without_url()
+                )))
             }
+        };
+        if let Err(err) = file.write_all(&stream_chunk) {
+            return Err(DownloadAttemptError::Fatal(format!(
+                "Unable to write chunk. Err: {}",
+                err
+            )));
         }
-    }
+        bytes_downloaded += stream_chunk.len() as u64;
 
-    #[cfg(target_family = "unix")]
-    set_unix_permissions(download_path_str.clone(), backend_communicator);
-
-    Ok(())
-}
+        let should_emit = match last_emitted_at {
+            Some(emitted_at) => emitted_at.elapsed() >= PROGRESS_EMIT_INTERVAL,
+            None => true,
+        };
+        if should_emit {
+            emit_download_progress(
+                build_download_progress(
+                    download_url,
+                    bytes_downloaded,
+                    bytes_at_start,
+                    total_bytes,
+                    started_at,
+                    false,
+                ),
+                backend_communicator,
+            );
+            last_emitted_at = Some(Instant::now());
+        }
+    }
 
-/// Convenience function, logs a message and emits an event with the message as payload.
-pub fn log_and_emit(message: String, backend_communicator: &BackendCommunicator) {
-    match log_message(message.clone(), backend_communicator) {
-        Ok(_) => {}
-        Err(err_str) => println!("{}", err_str),
+    if let Some(total_bytes) = total_bytes {
+        if bytes_downloaded < total_bytes {
+            return Err(DownloadAttemptError::Retryable(format!(
+                "Connection closed after {} / {} bytes.",
+                bytes_downloaded, total_bytes
+            )));
+        }
     }
-    emit_event(message, backend_communicator);
-    {}
-}
 
-pub fn get_log_path(backend_communicator: &BackendCommunicator) -> String {
-    let log_name = "log.txt".to_string();
-    let log_path_str = format!("{}{}", backend_communicator.data_dir.clone(), log_name);
-    log_path_str
+    emit_download_progress(
+        build_download_progress(
+            download_url,
+            bytes_downloaded,
+            bytes_at_start,
+            total_bytes,
+            started_at,
+            true,
+        ),
+        backend_communicator,
+    );
+    Ok(())
 }
 
-/// Log a message locally
-pub fn log_message(
-    message: String,
-    backend_communicator: &BackendCommunicator,
-) -> Result<String, String> {
-    let dt: DateTime<Utc> = Utc::now();
-    let log_path_str = get_log_path(backend_communicator);
+/// Calculate the transfer rate and remaining time of the current attempt.
+fn build_download_progress(
+    download_url: &str,
+    bytes_downloaded: u64,
+    bytes_at_start: u64,
+    total_bytes: Option<u64>,
+    started_at: Instant,
+    finished: bool,
+) -> DownloadProgress {
+    let elapsed_seconds = started_at.elapsed().as_secs_f64();
+    let bytes_per_second = if elapsed_seconds > 0.0 {
+        (bytes_downloaded - bytes_at_start) as f64 / elapsed_seconds
+    } else {
+        0.0
+    };
+    let eta_seconds = match total_bytes {
+        Some(total_bytes) if bytes_per_second > 0.0 => Some(
+            (total_bytes.saturating_sub(bytes_downloaded) as f64 / bytes_per_second).ceil() as u64,
+        ),
+        _ => None,
+    };
 
-    let file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(log_path_str.clone());
-    match file {
-        Ok(ok_file) => {
-            let mut valid_file = ok_file;
-            let mut complete_log_string = dt.format("%d %B %Y %H:%M:%S%.3f %Z ").to_string();
-            complete_log_string.push_str(&message);
-            complete_log_string.push('\n');
-
-            match valid_file.write(complete_log_string.as_bytes()) {
-                Ok(_) => {
-                    println!("Logged: {}", complete_log_string.clone());
-                    Ok(complete_log_string)
-                }
-                Err(_) => Err("Unable to write log to file.".to_string()),
-            }
-        }
-        Err(_) => Err(format!("Unable to open log file {}", log_path_str)),
+    DownloadProgress {
+        url: download_url.to_string(),
+        bytes_downloaded,
+        total_bytes,
+        bytes_per_second,
+        eta_seconds,
+        finished,
     }
 }
 
-/// Emit an event to the main window
-pub fn emit_event(message: String, backend_communicator: &BackendCommunicator) {
-    // window.center();
-    backend_communicator
+/// Emit download progress to the main window. Not logged, as it is emitted frequently.
+fn emit_download_progress(
+    download_progress: DownloadProgress,
+    backend_communicator: &BackendCommunicator,
+) {
+    if let Err(err) = backend_communicator
         .front_end_window
-        .emit(
-            &backend_communicator.status_listener.clone(),
-            message.clone(),
-        )
-        .unwrap();
-    println!(
-        "Sent event on listener: {},  payload: {}",
-        &backend_communicator.status_listener, message
-    );
-    {}
+        .emit(DOWNLOAD_PROGRESS_LISTENER, download_progress)
+    {
+        eprintln!("Unable to emit download progress. Err: {}", err);
+    }
 }
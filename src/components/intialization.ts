@@ -92,23 +92,17 @@ export async function sync_initialization_status(deviceInitializedref: Ref<boole
 async function initial_device_start_from_frontend() {
   const appLocalDataDirPath = await appLocalDataDir()
 
-  const stake_ID = await get_stake_id_via_index()
-
-  if (stake_ID) {
-    const set_stake_id_from_frontend: boolean = await invoke('set_stake_id_from_frontend', {
-      stake: stake_ID,
+  // The backend only stores the stake if it is active and assigned to this device.
+  try {
+    await invoke('discover_stake_from_frontend', {
       datadir: appLocalDataDirPath,
       window: appWindow
     })
-
-    if (!set_stake_id_from_frontend) {
-      // Error while setting stake id
-      return false
-    }
   }
-  else {
+  catch (e) {
+    const stake_error = e as { kind: string, message: string }
     // eslint-disable-next-line max-len
-    const err_message = 'Could not derive stake via Index. Has your assign device transaction been confirmed? Please try again. If the error persists, contact support on the Edge Discord: https://ed.ge/discord.'
+    const err_message = `Could not use your stake: ${stake_error.message} Please try again. If the error persists, contact support on the Edge Discord: https://ed.ge/discord.`
     await invoke('log_and_emit_from_frontend', {
       message: err_message,
       datadir: appLocalDataDirPath,
@@ -117,21 +111,16 @@ async function initial_device_start_from_frontend() {
     return false
   }
 
-  // Stop node to ensure clean slate where latest config is used.
-  // Assumes the container is successfully removed via the CLI command.
+  // Restart node to ensure clean slate where latest config is used.
+  // Assumes the container is successfully removed via the CLI stop command.
   // https://github.com/edge/cli/blob/f71951a34144313b1e1500dfc9d0b7963c765b11/src/device/cli/stop.ts#L38-L39
-  const deviceStopped = await invoke('device_stop_from_frontend', {
+  const deviceRestart: { success: boolean } = await invoke('device_restart_from_frontend', {
     checklatestbinary: true,
     datadir: appLocalDataDirPath,
     window: appWindow
   })
-  if (deviceStopped) {
-    const has_device_started_from_frontend_successfully: boolean = await invoke('device_start_from_frontend', {
-      checklatestbinary: true,
-      datadir: appLocalDataDirPath,
-      window: appWindow
-    })
-    return has_device_started_from_frontend_successfully
+  if (deviceRestart.success) {
+    return true
   }
   else {
     // eslint-disable-next-line max-len
@@ -298,39 +287,6 @@ async function auto_recheck_node_online(deviceInitializedref: Ref<boolean>,
   }
 }
 
-/**
- *
- * @param node_address XE node address
- * Checks if the XE node address is online.
- */
-export async function get_stake_id_via_index() {
-  const appLocalDataDirPath = await appLocalDataDir()
-  try {
-    const index_url = await get_index_url()
-    const node_address: string = await invoke('get_node_address_from_frontend', {
-      datadir: appLocalDataDirPath,
-      window: appWindow
-    })
-
-    const stake_id = (await stake.deviceStake(index_url, node_address)).stake
-    return stake_id
-  }
-  catch (e) {
-    const error_string = JSON.stringify(e)
-    const err_msg_1 = 'Stake not found http error code:' + error_string
-
-    await invoke('log_and_emit_from_frontend', {
-      message: err_msg_1,
-      datadir: appLocalDataDirPath,
-      window: appWindow
-    })
-    return false
-  }
-
-
-}
-
-
 /**
  *
  * @param node_address XE node address
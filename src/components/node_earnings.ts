@@ -2,7 +2,7 @@ import { appLocalDataDir } from '@tauri-apps/api/path'
 import { appWindow } from '@tauri-apps/api/window'
 import { get_node_wallet_from_config } from './intialization'
 import { invoke } from '@tauri-apps/api'
-import { send_notification } from './notification'
+import { send_node_event_notification } from './notification'
 import { exchangeRate, tx } from '@edge/index-utils'
 import { get_index_url } from './utils'
 
@@ -84,7 +84,11 @@ export async function check_node_earnings() {
       datadir: appLocalDataDirPath,
       window: appWindow
     })
-    send_notification('Received Edge Node Earnings', ok_message)
+    await invoke('record_earnings_from_frontend', {
+      amount: current_tx_amount,
+      islottery: false
+    })
+    send_node_event_notification('earnings_received', 'Received Edge Node Earnings', ok_message)
   }
 
   async function send_lottery_earning_notification(tx: tx.Tx) {
@@ -99,6 +103,10 @@ export async function check_node_earnings() {
       datadir: appLocalDataDirPath,
       window: appWindow
     })
-    send_notification('Congratulations! You won the Edge Lottery!', ok_message)
+    await invoke('record_earnings_from_frontend', {
+      amount: current_tx_amount,
+      islottery: true
+    })
+    send_node_event_notification('lottery_won', 'Congratulations! You won the Edge Lottery!', ok_message)
   }
 }
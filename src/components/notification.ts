@@ -21,3 +21,19 @@ export async function send_notification(title: string, body: string) {
   }
 
 }
+
+/**
+ * Send a desktop notification and forward the node event to the configured backend notification channels.
+ * @param event eg. 'earnings_received'. See NodeEvent in the backend.
+ */
+export async function send_node_event_notification(event: string, title: string, body: string) {
+  await send_notification(title, body)
+  const appLocalDataDirPath = await appLocalDataDir()
+  await invoke('notify_node_event_from_frontend', {
+    event: event,
+    title: title,
+    message: body,
+    datadir: appLocalDataDirPath,
+    window: appWindow
+  })
+}
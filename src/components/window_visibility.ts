@@ -15,9 +15,11 @@ export async function get_launch_minimized_status() {
 
 /**
  * Load and set launch minimized settings
+ * --minimized or --show passed at launch take precedence over the setting
  */
 export async function sync_launch_minimized_status() {
-  const window_must_be_minimized = await get_launch_minimized_status()
+  const first_launch_minimized: boolean | null = await invoke('get_first_launch_minimized_from_frontend')
+  const window_must_be_minimized = first_launch_minimized ?? await get_launch_minimized_status()
   if (window_must_be_minimized) {
     const appLocalDataDirPath = await appLocalDataDir()
     const ok_message = 'App was launched in a minimized state.'